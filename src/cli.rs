@@ -0,0 +1,1402 @@
+use std::{io::Write, path::Path};
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand, ValueEnum};
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    db::{filter_rows, filter_rows_by_type, JiraDatabase},
+    models::{
+        BugReport, Comment, Epic, IssueType, ItemRow, LinkKind, Priority, Severity, SortOrder,
+        Sprint, SprintState, Status, Story,
+    },
+    ui::{get_row_string, get_sprint_row_string, ROW_HEADER, SPRINT_ROW_HEADER},
+};
+
+/// A tiny issue tracker. Runs the interactive pages when no command is given.
+#[derive(Debug, Parser)]
+#[command(name = "my-jira")]
+pub struct Cli {
+    /// Database file to use (.json, or .db for SQLite)
+    #[arg(long, global = true, default_value = "data/db.json")]
+    pub db: String,
+
+    /// Config file to use, defaults to config.json next to the database
+    #[arg(long, global = true)]
+    pub config: Option<String>,
+
+    /// Output format of the list commands
+    #[arg(long, global = true, value_enum, default_value_t = Format::Table)]
+    pub format: Format,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    pub fn config_path(&self) -> String {
+        match &self.config {
+            Some(config) => config.clone(),
+            None => Path::new(&self.db)
+                .with_file_name("config.json")
+                .to_string_lossy()
+                .into_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Create, list and change epics
+    Epic {
+        #[command(subcommand)]
+        command: EpicCommand,
+    },
+    /// Create, list and change stories
+    Story {
+        #[command(subcommand)]
+        command: StoryCommand,
+    },
+    /// Register users and list their work
+    User {
+        #[command(subcommand)]
+        command: UserCommand,
+    },
+    /// Plan, start and complete sprints
+    Sprint {
+        #[command(subcommand)]
+        command: SprintCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EpicCommand {
+    /// Create an epic and print its key
+    Create {
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value = "")]
+        description: String,
+        /// blocker, critical, major, minor or trivial
+        #[arg(long, value_parser = parse_priority, default_value = "major")]
+        priority: Priority,
+        /// Username of the epic's owner
+        #[arg(long)]
+        owner: Option<String>,
+        /// Due date as YYYY-MM-DD
+        #[arg(long)]
+        due: Option<NaiveDate>,
+    },
+    /// List all epics
+    List {
+        /// id or priority
+        #[arg(long, value_parser = parse_sort_order, default_value = "id")]
+        sort: SortOrder,
+        /// Only list epics with this label
+        #[arg(long)]
+        label: Option<String>,
+    },
+    /// Change the priority of an epic
+    Priority {
+        epic: String,
+        #[arg(value_parser = parse_priority)]
+        priority: Priority,
+    },
+    /// Set the owner of an epic, or clear it when no user is given
+    Assign { epic: String, user: Option<String> },
+    /// Set the due date (YYYY-MM-DD) of an epic, or clear it when no date is given
+    Due {
+        epic: String,
+        due: Option<NaiveDate>,
+    },
+    /// Comment on an epic
+    Comment { epic: String, body: String },
+    /// Print the comments on an epic
+    Comments { epic: String },
+    /// Add a label to an epic
+    Label { epic: String, label: String },
+    /// Remove a label from an epic
+    Unlabel { epic: String, label: String },
+    /// Close an epic
+    Close { epic: String },
+    /// Reopen a closed epic, resetting it to the status its stories roll up to
+    Reopen { epic: String },
+    /// Delete an epic along with its stories
+    Delete { epic: String },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum StoryCommand {
+    /// Create a story in an epic and print its key
+    Create {
+        #[arg(long)]
+        epic: String,
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value = "")]
+        description: String,
+        /// Story point estimate, one of the configured point scale
+        #[arg(long)]
+        points: Option<u32>,
+        /// blocker, critical, major, minor or trivial
+        #[arg(long, value_parser = parse_priority, default_value = "major")]
+        priority: Priority,
+        /// Username of the story's assignee
+        #[arg(long)]
+        assignee: Option<String>,
+        /// Due date as YYYY-MM-DD
+        #[arg(long)]
+        due: Option<NaiveDate>,
+        /// bug, task, story or spike
+        #[arg(long = "type", value_parser = parse_issue_type, default_value = "story")]
+        issue_type: IssueType,
+        /// Severity of a bug: critical, major, minor or cosmetic
+        #[arg(long, value_parser = parse_severity)]
+        severity: Option<Severity>,
+        /// Steps to reproduce a bug
+        #[arg(long)]
+        steps: Option<String>,
+    },
+    /// List the stories of an epic
+    List {
+        #[arg(long)]
+        epic: String,
+        /// id or priority
+        #[arg(long, value_parser = parse_sort_order, default_value = "id")]
+        sort: SortOrder,
+        /// Only list stories with this label
+        #[arg(long)]
+        label: Option<String>,
+        /// Only list stories of this type
+        #[arg(long = "type", value_parser = parse_issue_type)]
+        issue_type: Option<IssueType>,
+    },
+    /// Change the status of a story (open, in-progress, resolved or closed)
+    Status {
+        story: String,
+        #[arg(value_parser = parse_status)]
+        status: Status,
+        /// Resolve or close the story even if open stories still block it
+        #[arg(long)]
+        force: bool,
+    },
+    /// Move a story to a state of the workflow, or print the states it may move to
+    State {
+        story: String,
+        /// Name of the new state
+        state: Option<String>,
+        /// Resolve or close the story even if open stories still block it
+        #[arg(long)]
+        force: bool,
+    },
+    /// Link a story to another one: blocks, blocked-by or relates-to
+    Link {
+        story: String,
+        #[arg(value_parser = parse_link)]
+        link: (LinkKind, bool),
+        other: String,
+    },
+    /// Remove every link between two stories
+    Unlink { story: String, other: String },
+    /// Print the links of a story
+    Links { story: String },
+    /// Estimate a story, or clear its estimate when no points are given
+    Points { story: String, points: Option<u32> },
+    /// Change the priority of a story
+    Priority {
+        story: String,
+        #[arg(value_parser = parse_priority)]
+        priority: Priority,
+    },
+    /// Change the type of a story (bug, task, story or spike)
+    Type {
+        story: String,
+        #[arg(value_parser = parse_issue_type)]
+        issue_type: IssueType,
+    },
+    /// Update the severity and steps to reproduce of a bug
+    Bug {
+        story: String,
+        #[arg(long, value_parser = parse_severity)]
+        severity: Option<Severity>,
+        #[arg(long)]
+        steps: Option<String>,
+    },
+    /// Assign a story to a user, or unassign it when no user is given
+    Assign { story: String, user: Option<String> },
+    /// Set the due date (YYYY-MM-DD) of a story, or clear it when no date is given
+    Due {
+        story: String,
+        due: Option<NaiveDate>,
+    },
+    /// Comment on a story
+    Comment { story: String, body: String },
+    /// Print the comments on a story
+    Comments { story: String },
+    /// Add a label to a story
+    Label { story: String, label: String },
+    /// Remove a label from a story
+    Unlabel { story: String, label: String },
+    /// Move a story to another epic
+    Move { story: String, epic: String },
+    /// Delete a story
+    Delete { story: String },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum UserCommand {
+    /// Register a user
+    Add {
+        username: String,
+        /// Display name, the username by default
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// List all registered users
+    List,
+    /// Remove a user, unassigning everything they own or work on
+    Remove { username: String },
+    /// List the epics and stories of a user, yourself by default
+    Work { username: Option<String> },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SprintCommand {
+    /// Create a sprint and print its key
+    Create {
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value = "")]
+        goal: String,
+        /// Start date as YYYY-MM-DD, the day it is started by default
+        #[arg(long)]
+        start: Option<NaiveDate>,
+        /// End date as YYYY-MM-DD
+        #[arg(long)]
+        end: Option<NaiveDate>,
+    },
+    /// List all sprints
+    List,
+    /// List the stories of a sprint
+    Show { sprint: String },
+    /// List the unfinished stories that are in no planned or active sprint
+    Backlog,
+    /// Pull a story from any epic into a sprint
+    Add { sprint: String, story: String },
+    /// Put a story of a sprint back into the backlog
+    Remove { sprint: String, story: String },
+    /// Start a planned sprint
+    Start { sprint: String },
+    /// Complete the active sprint, moving its unfinished stories back to the backlog
+    Complete {
+        sprint: String,
+        /// Planned sprint to move the unfinished stories into instead
+        #[arg(long)]
+        next: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+fn parse_status(input: &str) -> Result<Status> {
+    match input.to_lowercase().as_str() {
+        "open" => Ok(Status::Open),
+        "in-progress" | "inprogress" | "in_progress" => Ok(Status::InProgress),
+        "resolved" => Ok(Status::Resolved),
+        "closed" => Ok(Status::Closed),
+        _ => Err(anyhow!(
+            "expected one of open, in-progress, resolved or closed"
+        )),
+    }
+}
+
+fn parse_priority(input: &str) -> Result<Priority> {
+    match input.to_lowercase().as_str() {
+        "blocker" => Ok(Priority::Blocker),
+        "critical" => Ok(Priority::Critical),
+        "major" => Ok(Priority::Major),
+        "minor" => Ok(Priority::Minor),
+        "trivial" => Ok(Priority::Trivial),
+        _ => Err(anyhow!(
+            "expected one of blocker, critical, major, minor or trivial"
+        )),
+    }
+}
+
+fn parse_issue_type(input: &str) -> Result<IssueType> {
+    IssueType::parse(input).ok_or_else(|| anyhow!("expected one of bug, task, story or spike"))
+}
+
+fn parse_severity(input: &str) -> Result<Severity> {
+    match input.to_lowercase().as_str() {
+        "critical" => Ok(Severity::Critical),
+        "major" => Ok(Severity::Major),
+        "minor" => Ok(Severity::Minor),
+        "cosmetic" => Ok(Severity::Cosmetic),
+        _ => Err(anyhow!(
+            "expected one of critical, major, minor or cosmetic"
+        )),
+    }
+}
+
+// the kind of link along with whether it starts from the other story
+fn parse_link(input: &str) -> Result<(LinkKind, bool)> {
+    match input.to_lowercase().as_str() {
+        "blocks" => Ok((LinkKind::Blocks, false)),
+        "blocked-by" | "is-blocked-by" => Ok((LinkKind::Blocks, true)),
+        "relates-to" => Ok((LinkKind::RelatesTo, false)),
+        _ => Err(anyhow!("expected one of blocks, blocked-by or relates-to")),
+    }
+}
+
+fn parse_sort_order(input: &str) -> Result<SortOrder> {
+    match input.to_lowercase().as_str() {
+        "id" => Ok(SortOrder::Id),
+        "priority" => Ok(SortOrder::Priority),
+        _ => Err(anyhow!("expected id or priority")),
+    }
+}
+
+// runs a single command against the database and writes its output to `out`
+pub fn run(
+    command: Command,
+    format: Format,
+    db: &JiraDatabase,
+    out: &mut impl Write,
+) -> Result<()> {
+    match command {
+        Command::Epic { command } => run_epic_command(command, format, db, out),
+        Command::Story { command } => run_story_command(command, format, db, out),
+        Command::User { command } => run_user_command(command, format, db, out),
+        Command::Sprint { command } => run_sprint_command(command, format, db, out),
+    }
+}
+
+fn run_epic_command(
+    command: EpicCommand,
+    format: Format,
+    db: &JiraDatabase,
+    out: &mut impl Write,
+) -> Result<()> {
+    match command {
+        EpicCommand::Create {
+            name,
+            description,
+            priority,
+            owner,
+            due,
+        } => {
+            let mut epic = Epic::new(name, description);
+            epic.priority = priority;
+            epic.owner = owner;
+            epic.due = due;
+            let epic_id = db.create_epic(epic)?;
+            writeln!(out, "{}", db.config.issue_key(epic_id))?;
+        }
+        EpicCommand::List { sort, label } => write_rows(
+            &filter_rows(db.epic_rows(sort)?, label.as_deref()),
+            format,
+            out,
+        )?,
+        EpicCommand::Priority { epic, priority } => {
+            db.update_epic_priority(epic_id(db, &epic)?, priority)?
+        }
+        EpicCommand::Assign { epic, user } => db.assign_epic(epic_id(db, &epic)?, user)?,
+        EpicCommand::Due { epic, due } => db.update_epic_due_date(epic_id(db, &epic)?, due)?,
+        EpicCommand::Comment { epic, body } => db.add_comment(epic_id(db, &epic)?, body)?,
+        EpicCommand::Comments { epic } => write_comments(db, epic_id(db, &epic)?, format, out)?,
+        EpicCommand::Label { epic, label } => db.add_label(epic_id(db, &epic)?, &label)?,
+        EpicCommand::Unlabel { epic, label } => db.remove_label(epic_id(db, &epic)?, &label)?,
+        EpicCommand::Close { epic } => db.close_epic(epic_id(db, &epic)?)?,
+        EpicCommand::Reopen { epic } => db.reopen_epic(epic_id(db, &epic)?)?,
+        EpicCommand::Delete { epic } => db.delete_epic(epic_id(db, &epic)?)?,
+    }
+
+    Ok(())
+}
+
+fn run_story_command(
+    command: StoryCommand,
+    format: Format,
+    db: &JiraDatabase,
+    out: &mut impl Write,
+) -> Result<()> {
+    match command {
+        StoryCommand::Create {
+            epic,
+            name,
+            description,
+            points,
+            priority,
+            assignee,
+            due,
+            issue_type,
+            severity,
+            steps,
+        } => {
+            if issue_type != IssueType::Bug && (severity.is_some() || steps.is_some()) {
+                return Err(anyhow!("Only bugs have a severity and steps to reproduce!"));
+            }
+            let mut story = Story::new(name, description);
+            story.points = points;
+            story.priority = priority;
+            story.assignee = assignee;
+            story.due = due;
+            story.issue_type = issue_type;
+            if issue_type == IssueType::Bug {
+                story.bug = Some(BugReport {
+                    severity: severity.unwrap_or_default(),
+                    steps_to_reproduce: steps.unwrap_or_default(),
+                });
+            }
+            let story_id = db.create_story(story, epic_id(db, &epic)?)?;
+            writeln!(out, "{}", db.config.issue_key(story_id))?;
+        }
+        StoryCommand::List {
+            epic,
+            sort,
+            label,
+            issue_type,
+        } => {
+            let rows = filter_rows_by_type(db.story_rows(epic_id(db, &epic)?, sort)?, issue_type);
+            write_rows(&filter_rows(rows, label.as_deref()), format, out)?
+        }
+        StoryCommand::Type { story, issue_type } => {
+            db.update_story_type(story_id(db, &story)?, issue_type)?
+        }
+        StoryCommand::Bug {
+            story,
+            severity,
+            steps,
+        } => {
+            let story_id = story_id(db, &story)?;
+            let current = db.read_story(story_id)?.bug.unwrap_or_default();
+            let bug = BugReport {
+                severity: severity.unwrap_or(current.severity),
+                steps_to_reproduce: steps.unwrap_or(current.steps_to_reproduce),
+            };
+            db.update_bug_report(story_id, bug)?
+        }
+        StoryCommand::Priority { story, priority } => {
+            db.update_story_priority(story_id(db, &story)?, priority)?
+        }
+        StoryCommand::State {
+            story, state: None, ..
+        } => {
+            let story_id = story_id(db, &story)?;
+            let state = db.config.workflow.story_state(&db.read_story(story_id)?);
+            let row = StateRow {
+                key: db.config.issue_key(story_id),
+                next: db.config.workflow.next_states(&state).join(", "),
+                state,
+            };
+            write_rows(&[row], format, out)?
+        }
+        StoryCommand::State {
+            story,
+            state: Some(state),
+            force,
+        } => match force {
+            true => db.force_story_state(story_id(db, &story)?, &state)?,
+            false => db.update_story_state(story_id(db, &story)?, &state)?,
+        },
+        StoryCommand::Status {
+            story,
+            status,
+            force,
+        } => match force {
+            true => db.force_story_status(story_id(db, &story)?, status)?,
+            false => db.update_story_status(story_id(db, &story)?, status)?,
+        },
+        StoryCommand::Link {
+            story,
+            link: (kind, inbound),
+            other,
+        } => {
+            let (story_id, other_id) = (story_id(db, &story)?, story_id(db, &other)?);
+            match inbound {
+                true => db.link_stories(other_id, kind, story_id)?,
+                false => db.link_stories(story_id, kind, other_id)?,
+            }
+        }
+        StoryCommand::Unlink { story, other } => {
+            db.unlink_stories(story_id(db, &story)?, story_id(db, &other)?)?
+        }
+        StoryCommand::Links { story } => write_links(db, story_id(db, &story)?, format, out)?,
+        StoryCommand::Points { story, points } => {
+            db.update_story_points(story_id(db, &story)?, points)?
+        }
+        StoryCommand::Assign { story, user } => db.assign_story(story_id(db, &story)?, user)?,
+        StoryCommand::Due { story, due } => db.update_story_due_date(story_id(db, &story)?, due)?,
+        StoryCommand::Comment { story, body } => db.add_comment(story_id(db, &story)?, body)?,
+        StoryCommand::Comments { story } => write_comments(db, story_id(db, &story)?, format, out)?,
+        StoryCommand::Label { story, label } => db.add_label(story_id(db, &story)?, &label)?,
+        StoryCommand::Unlabel { story, label } => db.remove_label(story_id(db, &story)?, &label)?,
+        StoryCommand::Move { story, epic } => {
+            db.move_story(story_id(db, &story)?, epic_id(db, &epic)?)?
+        }
+        StoryCommand::Delete { story } => {
+            let story_id = story_id(db, &story)?;
+            let db_state = db.read_db()?;
+            let (epic_id, _) = db_state
+                .epics
+                .iter()
+                .find(|(_, epic)| epic.stories.contains(&story_id))
+                .ok_or_else(|| anyhow!("Invalid Story ID!"))?;
+            db.delete_story(*epic_id, story_id)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_user_command(
+    command: UserCommand,
+    format: Format,
+    db: &JiraDatabase,
+    out: &mut impl Write,
+) -> Result<()> {
+    match command {
+        UserCommand::Add { username, name } => {
+            let name = name.unwrap_or_else(|| username.clone());
+            db.add_user(username, name)?
+        }
+        UserCommand::List => {
+            let rows = db
+                .read_db()?
+                .users
+                .into_iter()
+                .sorted_by(|(a, _), (b, _)| a.cmp(b))
+                .map(|(username, user)| UserRow {
+                    username,
+                    name: user.name,
+                })
+                .collect_vec();
+            write_rows(&rows, format, out)?
+        }
+        UserCommand::Remove { username } => db.remove_user(&username)?,
+        UserCommand::Work { username } => {
+            let username = username.unwrap_or_else(|| db.config.current_user());
+            write_rows(&db.work_rows(&username)?, format, out)?
+        }
+    }
+
+    Ok(())
+}
+
+fn run_sprint_command(
+    command: SprintCommand,
+    format: Format,
+    db: &JiraDatabase,
+    out: &mut impl Write,
+) -> Result<()> {
+    match command {
+        SprintCommand::Create {
+            name,
+            goal,
+            start,
+            end,
+        } => {
+            let mut sprint = Sprint::new(name, goal);
+            sprint.start = start;
+            sprint.end = end;
+            let sprint_id = db.create_sprint(sprint)?;
+            writeln!(out, "{}", db.config.sprint_key(sprint_id))?;
+        }
+        SprintCommand::List => {
+            let db_state = db.read_db()?;
+            let rows = db_state
+                .sprints
+                .iter()
+                .sorted_by_key(|(id, _)| **id)
+                .map(|(id, sprint)| SprintRow {
+                    key: db.config.sprint_key(*id),
+                    name: sprint.name.clone(),
+                    goal: sprint.goal.clone(),
+                    state: sprint.state,
+                    start: sprint.start,
+                    end: sprint.end,
+                    stories: sprint
+                        .stories
+                        .iter()
+                        .map(|id| db.config.issue_key(*id))
+                        .join(" "),
+                })
+                .collect_vec();
+            write_rows(&rows, format, out)?
+        }
+        SprintCommand::Show { sprint } => {
+            write_rows(&db.sprint_rows(sprint_id(db, &sprint)?)?, format, out)?
+        }
+        SprintCommand::Backlog => write_rows(&db.backlog_rows()?, format, out)?,
+        SprintCommand::Add { sprint, story } => {
+            db.add_story_to_sprint(sprint_id(db, &sprint)?, story_id(db, &story)?)?
+        }
+        SprintCommand::Remove { sprint, story } => {
+            db.remove_story_from_sprint(sprint_id(db, &sprint)?, story_id(db, &story)?)?
+        }
+        SprintCommand::Start { sprint } => db.start_sprint(sprint_id(db, &sprint)?)?,
+        SprintCommand::Complete { sprint, next } => {
+            let next_sprint_id = match next {
+                Some(next) => Some(sprint_id(db, &next)?),
+                None => None,
+            };
+            db.complete_sprint(sprint_id(db, &sprint)?, next_sprint_id)?
+        }
+    }
+
+    Ok(())
+}
+
+// oldest first
+fn write_comments(
+    db: &JiraDatabase,
+    item_id: usize,
+    format: Format,
+    out: &mut impl Write,
+) -> Result<()> {
+    let db_state = db.read_db()?;
+    write_rows(db_state.comments(item_id).unwrap_or_default(), format, out)
+}
+
+// outbound links first, then the links other stories have to this one
+fn write_links(
+    db: &JiraDatabase,
+    story_id: usize,
+    format: Format,
+    out: &mut impl Write,
+) -> Result<()> {
+    let db_state = db.read_db()?;
+    let outbound = db_state
+        .stories
+        .get(&story_id)
+        .ok_or_else(|| anyhow!("Invalid Story ID!"))?
+        .links
+        .iter()
+        .map(|link| (link.kind.outbound_label(), link.target));
+    let inbound = db_state
+        .inbound_links(story_id)
+        .into_iter()
+        .map(|(id, kind)| (kind.inbound_label(), id));
+    let rows = outbound
+        .chain(inbound)
+        .map(|(link, id)| LinkRow {
+            link,
+            key: db.config.issue_key(id),
+        })
+        .collect_vec();
+    write_rows(&rows, format, out)
+}
+
+// what the listing commands print: tables in the layout of the pages, json and csv straight
+// from the serialized fields
+trait OutputRow: Serialize {
+    // printed above the table, if the table has one
+    const HEADER: Option<&'static str>;
+    // csv headers, serialize only writes them along with the first row
+    const FIELDS: &'static [&'static str];
+
+    fn table_row(&self) -> String;
+}
+
+impl OutputRow for ItemRow {
+    const HEADER: Option<&'static str> = Some(ROW_HEADER);
+    const FIELDS: &'static [&'static str] = &[
+        "key",
+        "id",
+        "name",
+        "description",
+        "status",
+        "points",
+        "priority",
+        "assignee",
+        "labels",
+        "due",
+        "issue_type",
+    ];
+
+    fn table_row(&self) -> String {
+        get_row_string(self)
+    }
+}
+
+#[derive(Serialize)]
+struct SprintRow {
+    key: String,
+    name: String,
+    goal: String,
+    state: SprintState,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    // space separated story keys, which keeps the row flat for csv
+    stories: String,
+}
+
+impl OutputRow for SprintRow {
+    const HEADER: Option<&'static str> = Some(SPRINT_ROW_HEADER);
+    const FIELDS: &'static [&'static str] =
+        &["key", "name", "goal", "state", "start", "end", "stories"];
+
+    fn table_row(&self) -> String {
+        let mut sprint = Sprint::new(self.name.clone(), self.goal.clone());
+        sprint.state = self.state;
+        sprint.start = self.start;
+        sprint.end = self.end;
+        get_sprint_row_string(&self.key, &sprint)
+    }
+}
+
+#[derive(Serialize)]
+struct UserRow {
+    username: String,
+    name: String,
+}
+
+impl OutputRow for UserRow {
+    const HEADER: Option<&'static str> = None;
+    const FIELDS: &'static [&'static str] = &["username", "name"];
+
+    fn table_row(&self) -> String {
+        format!("{} | {}", self.username, self.name)
+    }
+}
+
+// one comment per line in tables
+impl OutputRow for Comment {
+    const HEADER: Option<&'static str> = None;
+    const FIELDS: &'static [&'static str] = &["author", "timestamp", "body"];
+
+    fn table_row(&self) -> String {
+        format!(
+            "{} {}: {}",
+            self.timestamp.to_rfc3339(),
+            self.author,
+            self.body.lines().join(" ")
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct LinkRow {
+    // how the link reads from the story, e.g. "is blocked by"
+    link: &'static str,
+    key: String,
+}
+
+impl OutputRow for LinkRow {
+    const HEADER: Option<&'static str> = None;
+    const FIELDS: &'static [&'static str] = &["link", "key"];
+
+    fn table_row(&self) -> String {
+        format!("{} {}", self.link, self.key)
+    }
+}
+
+#[derive(Serialize)]
+struct StateRow {
+    key: String,
+    state: String,
+    // the states the story may move to, comma separated as state names may contain spaces
+    next: String,
+}
+
+impl OutputRow for StateRow {
+    const HEADER: Option<&'static str> = None;
+    const FIELDS: &'static [&'static str] = &["key", "state", "next"];
+
+    fn table_row(&self) -> String {
+        format!("state: {}\nnext: {}", self.state, self.next)
+    }
+}
+
+fn write_rows<R: OutputRow>(rows: &[R], format: Format, out: &mut impl Write) -> Result<()> {
+    match format {
+        Format::Table => {
+            if let Some(header) = R::HEADER {
+                writeln!(out, "{}", header)?;
+            }
+            for row in rows {
+                writeln!(out, "{}", row.table_row())?;
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, rows)?;
+            writeln!(out)?;
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(&mut *out);
+            if rows.is_empty() {
+                writer.write_record(R::FIELDS)?;
+            }
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+// resolves an issue key or bare id typed on the command line to an existing epic
+fn epic_id(db: &JiraDatabase, input: &str) -> Result<usize> {
+    let db_state = db.read_db()?;
+    db.config
+        .parse_issue_key(input)
+        .filter(|id| db_state.epics.contains_key(id))
+        .ok_or_else(|| anyhow!("Invalid Epic ID!"))
+}
+
+fn story_id(db: &JiraDatabase, input: &str) -> Result<usize> {
+    let db_state = db.read_db()?;
+    db.config
+        .parse_issue_key(input)
+        .filter(|id| db_state.stories.contains_key(id))
+        .ok_or_else(|| anyhow!("Invalid Story ID!"))
+}
+
+fn sprint_id(db: &JiraDatabase, input: &str) -> Result<usize> {
+    let db_state = db.read_db()?;
+    db.config
+        .parse_sprint_key(input)
+        .filter(|id| db_state.sprints.contains_key(id))
+        .ok_or_else(|| anyhow!("Invalid Sprint ID!"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::MockDB;
+
+    fn run_args(db: &JiraDatabase, args: &[&str]) -> Result<String> {
+        let cli = Cli::try_parse_from([&["my-jira"], args].concat())?;
+        let mut out = vec![];
+        run(cli.command.unwrap(), cli.format, db, &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn cli_should_default_to_interactive_mode_and_data_db() {
+        let cli = Cli::try_parse_from(["my-jira"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.db, "data/db.json");
+
+        let cli = Cli::try_parse_from(["my-jira", "epic", "list", "--db", "other.db"]).unwrap();
+        assert_eq!(cli.db, "other.db");
+    }
+
+    #[test]
+    fn cli_should_find_the_config_next_to_the_database() {
+        let cli = Cli::try_parse_from(["my-jira"]).unwrap();
+        assert_eq!(cli.config_path(), "data/config.json");
+
+        let cli = Cli::try_parse_from(["my-jira", "--db", "/srv/team/db.json"]).unwrap();
+        assert_eq!(cli.config_path(), "/srv/team/config.json");
+
+        let cli = Cli::try_parse_from(["my-jira", "--db", "other.db"]).unwrap();
+        assert_eq!(cli.config_path(), "config.json");
+
+        let cli =
+            Cli::try_parse_from(["my-jira", "epic", "list", "--config", "team.json"]).unwrap();
+        assert_eq!(cli.config_path(), "team.json");
+    }
+
+    #[test]
+    fn epic_commands_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        let output = run_args(&db, &["epic", "create", "--name", "Epic - Project 1"]).unwrap();
+        assert_eq!(output, "PROJ-1\n");
+
+        let output = run_args(&db, &["epic", "list"]).unwrap();
+        assert_eq!(
+            output.lines().collect::<Vec<_>>(),
+            vec![
+                ROW_HEADER,
+                "PROJ-1      | Epic - Project 1   | EPIC  | MAJOR    | -      | OPEN       "
+            ]
+        );
+
+        run_args(&db, &["epic", "priority", "PROJ-1", "blocker"]).unwrap();
+        assert_eq!(db.read_db().unwrap().epics[&1].priority, Priority::Blocker);
+
+        run_args(&db, &["epic", "close", "PROJ-1"]).unwrap();
+        assert_eq!(db.read_db().unwrap().epics[&1].status, Status::Closed);
+        run_args(&db, &["epic", "reopen", "PROJ-1"]).unwrap();
+        assert_eq!(db.read_db().unwrap().epics[&1].status, Status::Open);
+        assert!(run_args(&db, &["epic", "reopen", "PROJ-1"]).is_err());
+
+        run_args(&db, &["epic", "delete", "1"]).unwrap();
+        assert!(db.read_db().unwrap().epics.is_empty());
+    }
+
+    #[test]
+    fn story_commands_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+
+        let output = run_args(
+            &db,
+            &[
+                "story",
+                "create",
+                "--epic",
+                "PROJ-1",
+                "--name",
+                "Story",
+                "--points",
+                "3",
+                "--priority",
+                "critical",
+            ],
+        )
+        .unwrap();
+        assert_eq!(output, "PROJ-2\n");
+
+        run_args(&db, &["story", "status", "PROJ-2", "in-progress"]).unwrap();
+        run_args(&db, &["story", "points", "PROJ-2", "5"]).unwrap();
+
+        let output = run_args(&db, &["story", "list", "--epic", "1", "--format", "csv"]).unwrap();
+        let mut reader = csv::Reader::from_reader(output.as_bytes());
+        let header = reader.headers().unwrap().clone();
+        let rows = reader.records().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(rows.len(), 1);
+        let column = |name: &str| &rows[0][header.iter().position(|h| h == name).unwrap()];
+        assert_eq!(column("key"), "PROJ-2");
+        assert_eq!(column("name"), "Story");
+        assert_eq!(column("status"), "InProgress");
+        assert_eq!(column("points"), "5");
+        assert_eq!(column("priority"), "Critical");
+
+        run_args(&db, &["story", "delete", "2"]).unwrap();
+        assert!(db.read_db().unwrap().epics[&epic_id].stories.is_empty());
+    }
+
+    #[test]
+    fn state_command_should_follow_the_workflow() {
+        let mut db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        db.config.workflow.transitions.remove("CLOSED");
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        let output = run_args(&db, &["story", "state", "PROJ-2"]).unwrap();
+        assert_eq!(output, "state: OPEN\nnext: IN PROGRESS, RESOLVED, CLOSED\n");
+
+        run_args(&db, &["story", "state", "PROJ-2", "closed"]).unwrap();
+        let output = run_args(&db, &["story", "state", "PROJ-2"]).unwrap();
+        assert_eq!(output, "state: CLOSED\nnext: \n");
+        let output = run_args(&db, &["story", "state", "PROJ-2", "--format", "json"]).unwrap();
+        let rows: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(rows[0]["key"], "PROJ-2");
+        assert_eq!(rows[0]["state"], "CLOSED");
+        assert_eq!(rows[0]["next"], "");
+
+        // closed stories may no longer move
+        assert!(run_args(&db, &["story", "state", "PROJ-2", "open"]).is_err());
+        assert!(run_args(&db, &["story", "status", "PROJ-2", "open"]).is_err());
+        assert!(run_args(&db, &["story", "state", "PROJ-2", "unknown"]).is_err());
+    }
+
+    #[test]
+    fn move_command_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let other_epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+
+        run_args(&db, &["story", "move", "PROJ-2", "PROJ-3"]).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert!(db_state.epics[&epic_id].stories.is_empty());
+        assert_eq!(db_state.epics[&other_epic_id].stories, vec![story_id]);
+        assert!(run_args(&db, &["story", "move", "PROJ-2", "PROJ-3"]).is_err());
+        assert!(run_args(&db, &["story", "move", "PROJ-2", "PROJ-2"]).is_err());
+    }
+
+    #[test]
+    fn link_commands_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        for _ in 0..3 {
+            db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+        }
+
+        run_args(&db, &["story", "link", "PROJ-2", "blocks", "PROJ-3"]).unwrap();
+        run_args(&db, &["story", "link", "PROJ-2", "blocked-by", "PROJ-4"]).unwrap();
+        run_args(&db, &["story", "link", "PROJ-3", "relates-to", "PROJ-4"]).unwrap();
+        assert!(run_args(&db, &["story", "link", "PROJ-3", "blocks", "PROJ-4"]).is_err());
+
+        let output = run_args(&db, &["story", "links", "PROJ-2"]).unwrap();
+        assert_eq!(output, "blocks PROJ-3\nis blocked by PROJ-4\n");
+        let output = run_args(&db, &["story", "links", "PROJ-2", "--format", "csv"]).unwrap();
+        assert_eq!(output, "link,key\nblocks,PROJ-3\nis blocked by,PROJ-4\n");
+
+        let error = run_args(&db, &["story", "status", "PROJ-3", "resolved"]).unwrap_err();
+        assert_eq!(error.to_string(), "PROJ-3 is still blocked by PROJ-2");
+        run_args(&db, &["story", "status", "PROJ-3", "resolved", "--force"]).unwrap();
+
+        run_args(&db, &["story", "unlink", "PROJ-4", "PROJ-2"]).unwrap();
+        let output = run_args(&db, &["story", "links", "PROJ-2"]).unwrap();
+        assert_eq!(output, "blocks PROJ-3\n");
+    }
+
+    #[test]
+    fn issue_type_commands_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        db.create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+
+        run_args(
+            &db,
+            &[
+                "story",
+                "create",
+                "--epic",
+                "PROJ-1",
+                "--name",
+                "Crash on save",
+                "--type",
+                "bug",
+                "--severity",
+                "critical",
+            ],
+        )
+        .unwrap();
+        run_args(
+            &db,
+            &["story", "create", "--epic", "PROJ-1", "--name", "Research"],
+        )
+        .unwrap();
+        run_args(&db, &["story", "type", "PROJ-3", "spike"]).unwrap();
+        run_args(
+            &db,
+            &["story", "bug", "PROJ-2", "--steps", "click save twice"],
+        )
+        .unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(
+            db_state.stories[&2].bug,
+            Some(BugReport {
+                severity: Severity::Critical,
+                steps_to_reproduce: "click save twice".to_owned(),
+            })
+        );
+        assert_eq!(db_state.stories[&3].issue_type, IssueType::Spike);
+
+        let output = run_args(
+            &db,
+            &[
+                "story", "list", "--epic", "PROJ-1", "--type", "spike", "--format", "json",
+            ],
+        )
+        .unwrap();
+        let rows: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 1);
+        assert_eq!(rows[0]["key"], "PROJ-3");
+        assert_eq!(rows[0]["issue_type"], "Spike");
+
+        assert!(run_args(&db, &["story", "bug", "PROJ-3", "--severity", "minor"]).is_err());
+        assert!(run_args(
+            &db,
+            &["story", "create", "--epic", "PROJ-1", "--name", "", "--steps", "x"],
+        )
+        .is_err());
+        assert!(run_args(&db, &["story", "type", "PROJ-3", "chore"]).is_err());
+    }
+
+    #[test]
+    fn user_commands_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        run_args(&db, &["user", "add", "bob"]).unwrap();
+        run_args(&db, &["user", "add", "alice", "--name", "Alice"]).unwrap();
+        let output = run_args(&db, &["user", "list"]).unwrap();
+        assert_eq!(output, "alice | Alice\nbob | bob\n");
+        let output = run_args(&db, &["user", "list", "--format", "csv"]).unwrap();
+        assert_eq!(output, "username,name\nalice,Alice\nbob,bob\n");
+
+        run_args(
+            &db,
+            &["epic", "create", "--name", "Epic", "--owner", "alice"],
+        )
+        .unwrap();
+        run_args(
+            &db,
+            &[
+                "story",
+                "create",
+                "--epic",
+                "1",
+                "--name",
+                "Story",
+                "--assignee",
+                "bob",
+            ],
+        )
+        .unwrap();
+        run_args(&db, &["story", "assign", "PROJ-2", "alice"]).unwrap();
+
+        let output = run_args(&db, &["user", "work", "alice", "--format", "json"]).unwrap();
+        let rows: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 2);
+        assert_eq!(rows[0]["key"], "PROJ-1");
+        assert_eq!(rows[1]["key"], "PROJ-2");
+        assert_eq!(rows[1]["assignee"], "alice");
+
+        run_args(&db, &["epic", "assign", "PROJ-1"]).unwrap();
+        assert_eq!(db.read_db().unwrap().epics[&1].owner, None);
+
+        run_args(&db, &["user", "remove", "alice"]).unwrap();
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories[&2].assignee, None);
+        assert!(!db_state.users.contains_key("alice"));
+
+        assert!(run_args(&db, &["story", "assign", "PROJ-2", "carol"]).is_err());
+        assert!(run_args(&db, &["user", "add", "bob"]).is_err());
+    }
+
+    #[test]
+    fn sprint_commands_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let done_id = db
+            .create_story(Story::new("Done".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let open_id = db
+            .create_story(Story::new("Open".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        let output = run_args(
+            &db,
+            &[
+                "sprint",
+                "create",
+                "--name",
+                "Sprint 1",
+                "--start",
+                "2024-05-01",
+                "--end",
+                "2024-05-14",
+            ],
+        )
+        .unwrap();
+        assert_eq!(output, "SPRINT-1\n");
+        run_args(&db, &["sprint", "create", "--name", "Sprint 2"]).unwrap();
+
+        run_args(&db, &["sprint", "add", "SPRINT-1", "PROJ-2"]).unwrap();
+        run_args(&db, &["sprint", "add", "SPRINT-1", "PROJ-3"]).unwrap();
+        assert!(run_args(&db, &["sprint", "add", "SPRINT-2", "PROJ-3"]).is_err());
+        let output = run_args(&db, &["sprint", "backlog", "--format", "json"]).unwrap();
+        assert_eq!(output, "[]\n");
+
+        run_args(&db, &["sprint", "start", "SPRINT-1"]).unwrap();
+        assert!(run_args(&db, &["sprint", "start", "SPRINT-2"]).is_err());
+        db.update_story_status(done_id, Status::Resolved).unwrap();
+
+        let output = run_args(&db, &["sprint", "list"]).unwrap();
+        assert_eq!(
+            output.lines().nth(1).unwrap(),
+            "SPRINT-1    | Sprint 1           | ACTIVE     | 2024-05-01 - 2024-05-14"
+        );
+        let output = run_args(&db, &["sprint", "list", "--format", "json"]).unwrap();
+        let rows: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(rows[0]["key"], "SPRINT-1");
+        assert_eq!(rows[0]["state"], "Active");
+        assert_eq!(rows[0]["stories"], "PROJ-2 PROJ-3");
+        let output = run_args(&db, &["sprint", "list", "--format", "csv"]).unwrap();
+        assert_eq!(
+            output.lines().next().unwrap(),
+            "key,name,goal,state,start,end,stories"
+        );
+        assert_eq!(output.lines().count(), 3);
+
+        run_args(
+            &db,
+            &["sprint", "complete", "SPRINT-1", "--next", "SPRINT-2"],
+        )
+        .unwrap();
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.sprints[&1].stories, vec![done_id]);
+        assert_eq!(db_state.sprints[&2].stories, vec![open_id]);
+
+        let output = run_args(&db, &["sprint", "show", "SPRINT-2", "--format", "json"]).unwrap();
+        let rows: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(rows[0]["key"], "PROJ-3");
+
+        assert!(run_args(&db, &["sprint", "show", "PROJ-1"]).is_err());
+        assert!(run_args(&db, &["sprint", "show", "SPRINT-3"]).is_err());
+    }
+
+    #[test]
+    fn label_commands_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        db.create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        run_args(&db, &["epic", "label", "PROJ-1", "frontend"]).unwrap();
+        run_args(&db, &["story", "label", "PROJ-3", "tech-debt"]).unwrap();
+        run_args(&db, &["story", "label", "PROJ-3", "frontend"]).unwrap();
+        run_args(&db, &["story", "label", "PROJ-4", "frontend"]).unwrap();
+        run_args(&db, &["story", "unlabel", "PROJ-4", "frontend"]).unwrap();
+
+        let output = run_args(
+            &db,
+            &["epic", "list", "--label", "frontend", "--format", "json"],
+        )
+        .unwrap();
+        let rows: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 1);
+        assert_eq!(rows[0]["key"], "PROJ-1");
+        assert_eq!(rows[0]["labels"], "frontend");
+
+        let output = run_args(
+            &db,
+            &[
+                "story", "list", "--epic", "1", "--label", "frontend", "--format", "json",
+            ],
+        )
+        .unwrap();
+        let rows: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 1);
+        assert_eq!(rows[0]["key"], "PROJ-3");
+        assert_eq!(rows[0]["labels"], "frontend tech-debt");
+
+        assert!(run_args(&db, &["story", "unlabel", "PROJ-4", "frontend"]).is_err());
+        assert!(run_args(&db, &["epic", "label", "PROJ-1", "two words"]).is_err());
+    }
+
+    #[test]
+    fn due_commands_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        run_args(
+            &db,
+            &["epic", "create", "--name", "Epic", "--due", "2030-01-31"],
+        )
+        .unwrap();
+        run_args(&db, &["story", "create", "--epic", "1", "--name", "Story"]).unwrap();
+        run_args(&db, &["story", "due", "PROJ-2", "2030-02-28"]).unwrap();
+
+        let output = run_args(&db, &["story", "list", "--epic", "1", "--format", "json"]).unwrap();
+        let rows: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(rows[0]["due"], "2030-02-28");
+
+        run_args(&db, &["epic", "due", "PROJ-1"]).unwrap();
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics[&1].due, None);
+        assert_eq!(db_state.stories[&2].due, "2030-02-28".parse().ok());
+
+        assert!(run_args(&db, &["story", "due", "PROJ-2", "2030-02-30"]).is_err());
+        assert!(run_args(&db, &["story", "due", "PROJ-2", "tomorrow"]).is_err());
+    }
+
+    #[test]
+    fn comment_commands_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        run_args(&db, &["story", "comment", "PROJ-2", "first"]).unwrap();
+        run_args(&db, &["story", "comment", "PROJ-2", "second\nline"]).unwrap();
+
+        let output = run_args(&db, &["story", "comments", "PROJ-2"]).unwrap();
+        let user = db.config.current_user();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(&format!(" {}: first", user)));
+        assert!(lines[1].ends_with(&format!(" {}: second line", user)));
+
+        let output = run_args(&db, &["story", "comments", "PROJ-2", "--format", "json"]).unwrap();
+        let rows: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(rows[1]["author"], user.as_str());
+        assert_eq!(rows[1]["body"], "second\nline");
+
+        assert_eq!(run_args(&db, &["epic", "comments", "PROJ-1"]).unwrap(), "");
+        assert_eq!(
+            run_args(&db, &["epic", "comments", "PROJ-1", "--format", "csv"]).unwrap(),
+            "author,timestamp,body\n"
+        );
+        assert!(run_args(&db, &["epic", "comment", "PROJ-1", " "]).is_err());
+        assert!(run_args(&db, &["epic", "comment", "PROJ-2", "wrong kind"]).is_err());
+    }
+
+    #[test]
+    fn list_commands_should_write_json() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        let output = run_args(&db, &["epic", "list", "--format", "json"]).unwrap();
+        assert_eq!(output, "[]\n");
+
+        db.create_epic(Epic::new("epic".to_owned(), "description".to_owned()))
+            .unwrap();
+
+        let output = run_args(&db, &["--format", "json", "epic", "list"]).unwrap();
+        let rows: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 1);
+        assert_eq!(rows[0]["key"], "PROJ-1");
+        assert_eq!(rows[0]["id"], 1);
+        assert_eq!(rows[0]["name"], "epic");
+        assert_eq!(rows[0]["description"], "description");
+        assert_eq!(rows[0]["status"], "Open");
+        assert_eq!(rows[0]["points"], serde_json::Value::Null);
+        assert_eq!(rows[0]["priority"], "Major");
+    }
+
+    #[test]
+    fn list_commands_should_write_csv_header_without_rows() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        let output = run_args(&db, &["epic", "list", "--format", "csv"]).unwrap();
+
+        // the header has to match the one written along with the first row
+        db.create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let header = run_args(&db, &["epic", "list", "--format", "csv"]).unwrap();
+        assert_eq!(output, header.lines().next().unwrap().to_owned() + "\n");
+    }
+
+    #[test]
+    fn commands_should_error_for_invalid_input() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        assert!(run_args(&db, &["epic", "close", "PROJ-999"]).is_err());
+        assert!(run_args(&db, &["story", "list", "--epic", "1"]).is_err());
+        assert!(run_args(&db, &["story", "status", "1", "done"]).is_err());
+        assert!(run_args(&db, &["epic", "list", "--format", "xml"]).is_err());
+        assert!(run_args(&db, &["story", "points", "1", "4"]).is_err());
+    }
+}
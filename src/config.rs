@@ -0,0 +1,624 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Status, Story};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // prefix of the human-facing issue keys, e.g. "PROJ" for PROJ-42
+    pub project_key: String,
+    // the story point values an estimate may take
+    pub point_scale: Vec<u32>,
+    // username of whoever runs the tracker, falls back to $USER
+    pub user: Option<String>,
+    // whether a story's status follows the status of its sub-tasks
+    pub rollup_subtasks: bool,
+    // the states a story moves through and which moves are allowed
+    pub workflow: Workflow,
+    // how an epic's status follows the statuses of its stories
+    pub epic_rollup: EpicRollup,
+}
+
+// The status of an epic follows its stories:
+// - an epic without stories, or whose stories are all open, is open
+// - once any story has started or is done, the epic is in progress
+// - once every story is resolved or closed, the epic is resolved
+// Epics are only closed by hand, and the roll-up leaves closed epics alone until they are
+// reopened. Both of the flags below relax this.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EpicRollup {
+    // whether finished epics are resolved. Without it they stay in progress until closed.
+    pub resolve_when_done: bool,
+    // whether an epic whose stories are all closed closes by itself
+    pub close_when_all_closed: bool,
+}
+
+impl Default for EpicRollup {
+    fn default() -> Self {
+        Self {
+            resolve_when_done: true,
+            close_when_all_closed: false,
+        }
+    }
+}
+
+impl EpicRollup {
+    // the status of an epic with stories of the given statuses
+    pub fn status(&self, story_statuses: &[Status]) -> Status {
+        let all = |matches: fn(&Status) -> bool| story_statuses.iter().all(matches);
+
+        if all(|status| *status == Status::Open) {
+            return Status::Open;
+        }
+        if self.close_when_all_closed && all(|status| *status == Status::Closed) {
+            return Status::Closed;
+        }
+        if self.resolve_when_done
+            && all(|status| matches!(status, Status::Resolved | Status::Closed))
+        {
+            return Status::Resolved;
+        }
+        Status::InProgress
+    }
+}
+
+// a project-defined state. The category ties it to one of the built-in statuses, which epics,
+// sub-tasks and the roll-ups keep using. There is no separate setting for which states are done:
+// a state counts as done exactly when its category is Resolved or Closed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkflowState {
+    pub name: String,
+    pub category: Status,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workflow {
+    // in board order, the first state of a category is where stories of that status start
+    pub states: Vec<WorkflowState>,
+    // the states each state may move to, states without an entry are final
+    pub transitions: BTreeMap<String, Vec<String>>,
+}
+
+// the built-in statuses, any of which may move to any other
+impl Default for Workflow {
+    fn default() -> Self {
+        let states = Status::ALL
+            .iter()
+            .map(|status| WorkflowState {
+                name: status.to_string(),
+                category: *status,
+            })
+            .collect_vec();
+        let transitions = states
+            .iter()
+            .map(|from| {
+                let to = states
+                    .iter()
+                    .filter(|to| to.name != from.name)
+                    .map(|to| to.name.clone())
+                    .collect();
+                (from.name.clone(), to)
+            })
+            .collect();
+        Self {
+            states,
+            transitions,
+        }
+    }
+}
+
+impl Workflow {
+    // checks the workflow and spells every state in the transitions the way the states do, so
+    // the case-insensitive lookups and the transition map always agree
+    fn normalize(&mut self) -> Result<()> {
+        if self.states.is_empty() {
+            return Err(anyhow!("The workflow needs at least one state!"));
+        }
+        if let Some(name) = self
+            .states
+            .iter()
+            .map(|state| state.name.to_lowercase())
+            .duplicates()
+            .next()
+        {
+            return Err(anyhow!(
+                "The workflow has more than one state named '{}'!",
+                name
+            ));
+        }
+
+        let resolve = |name: &String| match self.state(name) {
+            Some(state) => Ok(state.name.clone()),
+            None => Err(anyhow!(
+                "The workflow transitions mention unknown state '{}'!",
+                name
+            )),
+        };
+        let mut transitions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (from, to) in &self.transitions {
+            let next = transitions.entry(resolve(from)?).or_default();
+            for name in to {
+                let name = resolve(name)?;
+                if !next.contains(&name) {
+                    next.push(name);
+                }
+            }
+        }
+        self.transitions = transitions;
+        Ok(())
+    }
+
+    // looks a state up by name, ignoring case
+    pub fn state(&self, name: &str) -> Option<&WorkflowState> {
+        self.states
+            .iter()
+            .find(|state| state.name.eq_ignore_ascii_case(name))
+    }
+
+    // the state stories of the given status start in
+    pub fn state_for_status(&self, status: Status) -> Option<&WorkflowState> {
+        self.states.iter().find(|state| state.category == status)
+    }
+
+    // the state a story is in. A recorded state only counts while it still belongs to the
+    // story's status, which sub-task roll-ups used to change without touching the state.
+    pub fn story_state(&self, story: &Story) -> String {
+        story
+            .state
+            .as_deref()
+            .and_then(|name| self.state(name))
+            .filter(|state| state.category == story.status)
+            .or_else(|| self.state_for_status(story.status))
+            .map_or_else(|| story.status.to_string(), |state| state.name.clone())
+    }
+
+    // the states a story in `from` may move to. Stories left in a state the workflow no longer
+    // knows may move anywhere, so they don't get stuck.
+    pub fn next_states(&self, from: &str) -> Vec<String> {
+        match self.state(from) {
+            Some(state) => self
+                .transitions
+                .get(&state.name)
+                .cloned()
+                .unwrap_or_default(),
+            None => self.states.iter().map(|state| state.name.clone()).collect(),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            project_key: "PROJ".to_owned(),
+            point_scale: vec![1, 2, 3, 5, 8, 13, 21],
+            user: None,
+            rollup_subtasks: false,
+            workflow: Workflow::default(),
+            epic_rollup: EpicRollup::default(),
+        }
+    }
+}
+
+impl Config {
+    // a missing config file is not an error, every setting simply keeps its default
+    pub fn load(file_path: &str) -> Result<Self> {
+        let data = match fs::read_to_string(file_path) {
+            Ok(data) => data,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut config: Config = serde_json::from_str(&data)?;
+
+        if config.project_key.is_empty()
+            || !config
+                .project_key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric())
+        {
+            return Err(anyhow!(
+                "Invalid project key '{}'! Only letters and digits are allowed.",
+                config.project_key
+            ));
+        }
+
+        if config.point_scale.is_empty() {
+            return Err(anyhow!("The point scale needs at least one value!"));
+        }
+
+        config.workflow.normalize()?;
+
+        Ok(config)
+    }
+
+    pub fn check_points(&self, points: Option<u32>) -> Result<()> {
+        match points {
+            Some(points) if !self.point_scale.contains(&points) => Err(anyhow!(
+                "Story points must be one of {}!",
+                self.point_scale.iter().join(", ")
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // the name recorded as the author of changes and used for "my work"
+    pub fn current_user(&self) -> String {
+        self.user
+            .clone()
+            .or_else(|| env::var("USER").ok())
+            .or_else(|| env::var("USERNAME").ok())
+            .unwrap_or_else(|| "unknown".to_owned())
+    }
+
+    pub fn issue_key(&self, id: usize) -> String {
+        format!("{}-{}", self.project_key, id)
+    }
+
+    pub fn sprint_key(&self, id: usize) -> String {
+        format!("SPRINT-{}", id)
+    }
+
+    // accepts either a sprint key such as "SPRINT-3" (case-insensitive) or a bare id
+    pub fn parse_sprint_key(&self, input: &str) -> Option<usize> {
+        let id = match input.rsplit_once('-') {
+            Some((prefix, id)) if prefix.eq_ignore_ascii_case("SPRINT") => id,
+            Some(_) => return None,
+            None => input,
+        };
+
+        id.parse::<usize>().ok()
+    }
+
+    // accepts either a full issue key such as "PROJ-42" (case-insensitive) or a bare id
+    pub fn parse_issue_key(&self, input: &str) -> Option<usize> {
+        let id = match input.rsplit_once('-') {
+            Some((prefix, id)) if prefix.eq_ignore_ascii_case(&self.project_key) => id,
+            Some(_) => return None,
+            None => input,
+        };
+
+        id.parse::<usize>().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn load_should_return_default_for_missing_file() {
+        let config = Config::load("INVALID_PATH").unwrap();
+
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn load_should_parse_json_file() {
+        let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+        write!(tmpfile, r#"{{ "project_key": "JIRA" }}"#).unwrap();
+
+        let config = Config::load(tmpfile.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(config.project_key, "JIRA");
+    }
+
+    #[test]
+    fn load_should_fail_with_invalid_project_key() {
+        let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+        write!(tmpfile, r#"{{ "project_key": "MY-PROJ" }}"#).unwrap();
+
+        let result = Config::load(tmpfile.path().to_str().unwrap());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn load_should_fail_with_empty_point_scale() {
+        let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+        write!(tmpfile, r#"{{ "point_scale": [] }}"#).unwrap();
+
+        let result = Config::load(tmpfile.path().to_str().unwrap());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn load_should_parse_workflow() {
+        let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            tmpfile,
+            r#"{{ "workflow": {{
+                "states": [
+                    {{ "name": "To Do", "category": "Open" }},
+                    {{ "name": "In Review", "category": "InProgress" }},
+                    {{ "name": "Done", "category": "Closed" }}
+                ],
+                "transitions": {{ "To Do": ["In Review"], "In Review": ["To Do", "Done"] }}
+            }} }}"#
+        )
+        .unwrap();
+
+        let config = Config::load(tmpfile.path().to_str().unwrap()).unwrap();
+
+        let workflow = &config.workflow;
+        assert_eq!(workflow.states.len(), 3);
+        assert_eq!(workflow.next_states("to do"), ["In Review"]);
+        assert!(workflow.next_states("Done").is_empty());
+        assert_eq!(
+            workflow.state_for_status(Status::InProgress).unwrap().name,
+            "In Review"
+        );
+        assert_eq!(workflow.state_for_status(Status::Resolved), None);
+    }
+
+    #[test]
+    fn load_should_fail_with_invalid_workflow() {
+        for workflow in [
+            r#"{ "states": [], "transitions": {} }"#,
+            r#"{ "states": [{ "name": "A", "category": "Open" }, { "name": "A", "category": "Closed" }], "transitions": {} }"#,
+            r#"{ "states": [{ "name": "A", "category": "Open" }], "transitions": { "A": ["B"] } }"#,
+            r#"{ "states": [{ "name": "Done", "category": "Resolved" }, { "name": "done", "category": "Closed" }], "transitions": {} }"#,
+        ] {
+            let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+            write!(tmpfile, r#"{{ "workflow": {} }}"#, workflow).unwrap();
+
+            let result = Config::load(tmpfile.path().to_str().unwrap());
+
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn load_should_resolve_transition_names_ignoring_case() {
+        let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            tmpfile,
+            r#"{{ "workflow": {{
+                "states": [
+                    {{ "name": "To Do", "category": "Open" }},
+                    {{ "name": "Done", "category": "Closed" }}
+                ],
+                "transitions": {{ "to do": ["DONE"], "TO DO": ["done"], "done": ["To do"] }}
+            }} }}"#
+        )
+        .unwrap();
+
+        let config = Config::load(tmpfile.path().to_str().unwrap()).unwrap();
+
+        let workflow = &config.workflow;
+        assert_eq!(workflow.next_states("To Do"), ["Done"]);
+        assert_eq!(workflow.next_states("Done"), ["To Do"]);
+        assert_eq!(workflow.transitions.keys().collect_vec(), ["Done", "To Do"]);
+    }
+
+    #[test]
+    fn default_workflow_should_allow_every_transition() {
+        let workflow = Workflow::default();
+
+        assert_eq!(
+            workflow.next_states("Open"),
+            ["IN PROGRESS", "RESOLVED", "CLOSED"]
+        );
+        assert_eq!(workflow.states[1].category, Status::InProgress);
+    }
+
+    #[test]
+    fn story_state_should_follow_the_status() {
+        let workflow = Workflow::default();
+        let mut story = Story::new("".to_owned(), "".to_owned());
+
+        assert_eq!(workflow.story_state(&story), "OPEN");
+
+        story.state = Some("In Progress".to_owned());
+        story.status = Status::InProgress;
+        assert_eq!(workflow.story_state(&story), "IN PROGRESS");
+
+        // a roll-up changed the status without touching the state
+        story.status = Status::Resolved;
+        assert_eq!(workflow.story_state(&story), "RESOLVED");
+
+        story.state = Some("Gone".to_owned());
+        assert_eq!(workflow.story_state(&story), "RESOLVED");
+        assert_eq!(workflow.next_states("Gone").len(), 4);
+    }
+
+    // every story status multiset of up to three stories, for every setting, against a table
+    // of the expected epic status by which statuses are present
+    #[test]
+    fn epic_rollup_should_follow_the_rules_for_every_combination() {
+        use Status::*;
+        // (open, in progress, resolved, closed present) and the expected status for the settings
+        // (resolve, no close), (resolve, close), (no resolve, no close), (no resolve, close)
+        let expected_by_presence = [
+            ([false, false, false, false], [Open, Open, Open, Open]),
+            ([true, false, false, false], [Open, Open, Open, Open]),
+            ([false, true, false, false], [InProgress; 4]),
+            ([true, true, false, false], [InProgress; 4]),
+            (
+                [false, false, true, false],
+                [Resolved, Resolved, InProgress, InProgress],
+            ),
+            ([true, false, true, false], [InProgress; 4]),
+            ([false, true, true, false], [InProgress; 4]),
+            ([true, true, true, false], [InProgress; 4]),
+            (
+                [false, false, false, true],
+                [Resolved, Closed, InProgress, Closed],
+            ),
+            ([true, false, false, true], [InProgress; 4]),
+            ([false, true, false, true], [InProgress; 4]),
+            ([true, true, false, true], [InProgress; 4]),
+            (
+                [false, false, true, true],
+                [Resolved, Resolved, InProgress, InProgress],
+            ),
+            ([true, false, true, true], [InProgress; 4]),
+            ([false, true, true, true], [InProgress; 4]),
+            ([true, true, true, true], [InProgress; 4]),
+        ];
+        let settings = [(true, false), (true, true), (false, false), (false, true)];
+
+        let mut combinations: Vec<Vec<Status>> = vec![vec![]];
+        for length in 1..=3 {
+            combinations.extend((0..length).map(|_| Status::ALL).multi_cartesian_product());
+        }
+        assert_eq!(combinations.len(), 1 + 4 + 16 + 64);
+
+        for statuses in &combinations {
+            let presence = Status::ALL.map(|status| statuses.contains(&status));
+            let (_, expected) = expected_by_presence
+                .iter()
+                .find(|(row, _)| *row == presence)
+                .unwrap();
+            for ((resolve_when_done, close_when_all_closed), expected) in
+                settings.iter().zip(expected)
+            {
+                let rollup = EpicRollup {
+                    resolve_when_done: *resolve_when_done,
+                    close_when_all_closed: *close_when_all_closed,
+                };
+                assert_eq!(
+                    rollup.status(statuses),
+                    *expected,
+                    "{:?} with {:?}",
+                    statuses,
+                    rollup
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn epic_rollup_should_handle_each_setting() {
+        use Status::*;
+        // (resolve_when_done, close_when_all_closed, story statuses, expected epic status)
+        let cases: &[(bool, bool, &[Status], Status)] = &[
+            (true, false, &[], Open),
+            (true, true, &[], Open),
+            (false, false, &[], Open),
+            (false, true, &[], Open),
+            (true, false, &[Open, Open], Open),
+            (true, true, &[Open, Open], Open),
+            (false, false, &[Open, Open], Open),
+            (false, true, &[Open, Open], Open),
+            (true, false, &[Open, InProgress], InProgress),
+            (true, true, &[Open, InProgress], InProgress),
+            (false, false, &[Open, InProgress], InProgress),
+            (false, true, &[Open, InProgress], InProgress),
+            (true, false, &[Open, Resolved], InProgress),
+            (true, true, &[Open, Closed], InProgress),
+            (false, false, &[InProgress, Closed], InProgress),
+            (false, true, &[InProgress, Resolved], InProgress),
+            (true, false, &[Resolved, Resolved], Resolved),
+            (true, true, &[Resolved, Resolved], Resolved),
+            (false, false, &[Resolved, Resolved], InProgress),
+            (false, true, &[Resolved, Resolved], InProgress),
+            (true, false, &[Closed, Closed], Resolved),
+            (true, true, &[Closed, Closed], Closed),
+            (false, false, &[Closed, Closed], InProgress),
+            (false, true, &[Closed, Closed], Closed),
+            (true, false, &[Resolved, Closed], Resolved),
+            (true, true, &[Resolved, Closed], Resolved),
+            (false, false, &[Resolved, Closed], InProgress),
+            (false, true, &[Resolved, Closed], InProgress),
+        ];
+
+        for (resolve_when_done, close_when_all_closed, statuses, expected) in cases {
+            let rollup = EpicRollup {
+                resolve_when_done: *resolve_when_done,
+                close_when_all_closed: *close_when_all_closed,
+            };
+            assert_eq!(
+                rollup.status(statuses),
+                *expected,
+                "{:?} with {:?}",
+                statuses,
+                rollup
+            );
+        }
+    }
+
+    #[test]
+    fn epic_rollup_should_handle_the_documented_cases() {
+        let rollup = EpicRollup::default();
+        use Status::*;
+
+        assert_eq!(rollup.status(&[]), Open);
+        assert_eq!(rollup.status(&[Open, Open]), Open);
+        assert_eq!(rollup.status(&[Open, InProgress]), InProgress);
+        // a finished story means work has started on the epic
+        assert_eq!(rollup.status(&[Open, Resolved]), InProgress);
+        assert_eq!(rollup.status(&[Resolved, Closed]), Resolved);
+        // closing stays manual
+        assert_eq!(rollup.status(&[Closed, Closed]), Resolved);
+
+        let rollup = EpicRollup {
+            resolve_when_done: false,
+            close_when_all_closed: true,
+        };
+        assert_eq!(rollup.status(&[Resolved, Closed]), InProgress);
+        assert_eq!(rollup.status(&[Closed, Closed]), Closed);
+    }
+
+    #[test]
+    fn load_should_parse_epic_rollup() {
+        let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            tmpfile,
+            r#"{{ "epic_rollup": {{ "close_when_all_closed": true }} }}"#
+        )
+        .unwrap();
+
+        let config = Config::load(tmpfile.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(
+            config.epic_rollup,
+            EpicRollup {
+                resolve_when_done: true,
+                close_when_all_closed: true,
+            }
+        );
+    }
+
+    #[test]
+    fn check_points_should_only_accept_the_point_scale() {
+        let config = Config::default();
+
+        assert!(config.check_points(None).is_ok());
+        assert!(config.check_points(Some(5)).is_ok());
+        assert!(config.check_points(Some(4)).is_err());
+    }
+
+    #[test]
+    fn current_user_should_prefer_the_configured_user() {
+        let config = Config {
+            user: Some("alice".to_owned()),
+            ..Config::default()
+        };
+
+        assert_eq!(config.current_user(), "alice");
+    }
+
+    #[test]
+    fn parse_issue_key_should_accept_keys_and_ids() {
+        let config = Config::default();
+
+        assert_eq!(config.issue_key(42), "PROJ-42");
+        assert_eq!(config.parse_issue_key("PROJ-42"), Some(42));
+        assert_eq!(config.parse_issue_key("proj-42"), Some(42));
+        assert_eq!(config.parse_issue_key("42"), Some(42));
+        assert_eq!(config.parse_issue_key("OTHER-42"), None);
+        assert_eq!(config.parse_issue_key("PROJ-"), None);
+        assert_eq!(config.parse_issue_key("PROJ"), None);
+    }
+}
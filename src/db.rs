@@ -1,489 +1,4072 @@
 #![allow(dead_code, unused_imports, private_in_public)]
-use crate::models::{DBState, Epic, Status, Story};
+use crate::config::{Config, WorkflowState};
+use crate::models::{
+    diff_events, join_labels, BugReport, Comment, DBState, Epic, Event, IssueType, ItemRow, Link,
+    LinkKind, Priority, Severity, SortOrder, Sprint, SprintState, Status, Story, Subtask, User,
+    CURRENT_SCHEMA_VERSION,
+};
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use itertools::Itertools;
-use serde_json;
-use std::{borrow::BorrowMut, cmp::max, error, fs};
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use serde_json::{self, json, Map, Value};
+use std::{
+    borrow::BorrowMut,
+    cell::Cell,
+    collections::BTreeSet,
+    error,
+    fmt::Display,
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
 pub struct JiraDatabase {
     pub database: Box<dyn Database>,
+    pub config: Config,
+    // the revision of the state the current page was drawn from, i.e. the one the user is
+    // looking at
+    seen_revision: Cell<Option<u64>>,
 }
 
 impl JiraDatabase {
     pub fn new(file_path: String) -> Self {
-        return Self {
-            database: Box::new(JSONFileDatabase::new(file_path)),
+        let extension = Path::new(&file_path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+
+        let database: Box<dyn Database> = match extension.as_str() {
+            "db" | "sqlite" | "sqlite3" => Box::new(SqliteDatabase::new(file_path)),
+            _ => Box::new(JSONFileDatabase::new(file_path)),
         };
+
+        Self::with_database(database)
+    }
+
+    pub fn with_database(database: Box<dyn Database>) -> Self {
+        Self {
+            database,
+            config: Config::default(),
+            seen_revision: Cell::new(None),
+        }
     }
 
     pub fn read_db(&self) -> Result<DBState> {
-        return self.database.read_db();
+        self.database.read_db()
     }
 
-    pub fn create_epic(&self, epic: Epic) -> Result<usize> {
-        let mut db_state = self.read_db()?;
+    // reads the state a page is about to show. Later writes are based on it, so changes someone
+    // else makes while the user is looking at the page are reported as conflicts.
+    pub fn view_db(&self) -> Result<DBState> {
+        let db_state = self.database.read_db()?;
+        self.seen_revision.set(Some(db_state.revision));
+        Ok(db_state)
+    }
 
-        let new_id = db_state.last_item_id + 1;
-        db_state.epics.insert(new_id, epic);
-        db_state.last_item_id = new_id;
+    // items are looked up through these rather than by indexing, since another session may
+    // have deleted them since the page was drawn
+    pub fn read_epic(&self, epic_id: usize) -> Result<Epic> {
+        self.read_db()?
+            .epics
+            .remove(&epic_id)
+            .ok_or_else(|| anyhow!("Invalid Epic ID!"))
+    }
 
-        self.database.write_db(&db_state)?;
+    pub fn read_story(&self, story_id: usize) -> Result<Story> {
+        self.read_db()?
+            .stories
+            .remove(&story_id)
+            .ok_or_else(|| anyhow!("Invalid Story ID!"))
+    }
 
-        return Ok(db_state.last_item_id);
+    pub fn read_sprint(&self, sprint_id: usize) -> Result<Sprint> {
+        self.read_db()?
+            .sprints
+            .remove(&sprint_id)
+            .ok_or_else(|| anyhow!("Invalid Sprint ID!"))
     }
 
-    pub fn create_story(&self, story: Story, epic_id: usize) -> Result<usize> {
-        let mut db_state = self.read_db()?;
+    // holds the database lock across the whole read-modify-write so that concurrent processes
+    // serialize their changes instead of overwriting each other. The write is based on the
+    // revision this session last saw, so changes made by someone else in the meantime are
+    // reported as a ConflictError rather than silently overwritten.
+    fn modify<T>(&self, change: impl FnOnce(&mut DBState) -> Result<T>) -> Result<T> {
+        let _lock = self.database.lock()?;
 
-        let new_id = db_state.last_item_id + 1;
-        db_state.stories.insert(new_id, story);
-        db_state
-            .epics
-            .get_mut(&epic_id)
-            .ok_or_else(|| anyhow!("Invalid Epic ID!"))?
-            .stories
-            .push(new_id);
-        db_state.last_item_id = new_id;
+        let mut db_state = self.database.read_db()?;
+        if let Some(seen_revision) = self.seen_revision.get() {
+            db_state.revision = seen_revision;
+        }
 
+        let previous_state = db_state.clone();
+        let result = change(&mut db_state)?;
+        let timestamp = Utc::now();
+        db_state.touch_changed_items(&previous_state, timestamp);
         self.database.write_db(&db_state)?;
+        self.seen_revision.set(Some(db_state.revision + 1));
+
+        let actor = self.config.current_user();
+        for (item_id, kind) in diff_events(&previous_state, &db_state) {
+            self.database.append_event(&Event {
+                timestamp,
+                actor: actor.clone(),
+                item_id,
+                kind,
+            })?;
+        }
 
-        return Ok(self.database.read_db()?.last_item_id);
+        Ok(result)
     }
 
-    pub fn delete_epic(&self, epic_id: usize) -> Result<()> {
-        let mut db_state = self.read_db()?;
+    // every recorded change to the given epic or story, oldest first
+    pub fn read_events(&self, item_id: usize) -> Result<Vec<Event>> {
+        let events = self.database.read_events()?;
+        Ok(events
+            .into_iter()
+            .filter(|event| event.item_id == item_id)
+            .collect())
+    }
+
+    // all epics, sorted by id or by priority
+    pub fn epic_rows(&self, sort_order: SortOrder) -> Result<Vec<ItemRow>> {
+        let db_state = self.read_db()?;
+        let rows = db_state
+            .epics
+            .keys()
+            .sorted()
+            .map(|id| self.epic_row(&db_state, *id))
+            .collect();
+        Ok(sort_rows(rows, sort_order))
+    }
+
+    fn epic_row(&self, db_state: &DBState, epic_id: usize) -> ItemRow {
+        let epic = &db_state.epics[&epic_id];
+        // epics are only estimated once at least one of their stories is
+        let points = epic
+            .stories
+            .iter()
+            .filter_map(|id| db_state.stories[id].points)
+            .reduce(|total, points| total + points);
+        ItemRow {
+            key: self.config.issue_key(epic_id),
+            id: epic_id,
+            name: epic.name.clone(),
+            description: epic.description.clone(),
+            status: epic.status,
+            points,
+            priority: epic.priority,
+            assignee: epic.owner.clone(),
+            labels: join_labels(&epic.labels),
+            due: epic.due,
+            issue_type: None,
+        }
+    }
 
-        db_state
+    // the stories of an epic, in the order they were added to it or by priority
+    pub fn story_rows(&self, epic_id: usize, sort_order: SortOrder) -> Result<Vec<ItemRow>> {
+        let db_state = self.read_db()?;
+        let epic = db_state
             .epics
             .get(&epic_id)
             .ok_or_else(|| anyhow!("Invalid Epic ID!"))?;
+        let rows = epic
+            .stories
+            .iter()
+            .map(|id| self.story_row(&db_state, *id))
+            .collect();
+        Ok(sort_rows(rows, sort_order))
+    }
+
+    // the stories of one epic, or of every epic, by priority
+    pub fn board_rows(&self, epic_id: Option<usize>) -> Result<Vec<ItemRow>> {
+        if let Some(epic_id) = epic_id {
+            return self.story_rows(epic_id, SortOrder::Priority);
+        }
+        let db_state = self.read_db()?;
+        let rows = db_state
+            .stories
+            .keys()
+            .sorted()
+            .map(|id| self.story_row(&db_state, *id))
+            .collect();
+        Ok(sort_rows(rows, SortOrder::Priority))
+    }
 
-        for story_id in &db_state.epics[&epic_id].stories {
-            db_state.stories.remove(&story_id);
+    // the stories of a sprint, in the order they were pulled in
+    pub fn sprint_rows(&self, sprint_id: usize) -> Result<Vec<ItemRow>> {
+        let db_state = self.read_db()?;
+        let sprint = db_state
+            .sprints
+            .get(&sprint_id)
+            .ok_or_else(|| anyhow!("Invalid Sprint ID!"))?;
+        Ok(sprint
+            .stories
+            .iter()
+            .map(|id| self.story_row(&db_state, *id))
+            .collect())
+    }
+
+    // unfinished stories of every epic that aren't in a planned or active sprint yet
+    pub fn backlog_rows(&self) -> Result<Vec<ItemRow>> {
+        let db_state = self.read_db()?;
+        let rows = db_state
+            .stories
+            .iter()
+            .filter(|(id, story)| {
+                matches!(story.status, Status::Open | Status::InProgress)
+                    && db_state.story_sprint(**id).is_none()
+            })
+            .map(|(id, _)| *id)
+            .sorted()
+            .map(|id| self.story_row(&db_state, id))
+            .collect();
+        Ok(sort_rows(rows, SortOrder::Priority))
+    }
+
+    fn story_row(&self, db_state: &DBState, story_id: usize) -> ItemRow {
+        let story = &db_state.stories[&story_id];
+        ItemRow {
+            key: self.config.issue_key(story_id),
+            id: story_id,
+            name: story.name.clone(),
+            description: story.description.clone(),
+            status: story.status,
+            points: story.points,
+            priority: story.priority,
+            assignee: story.assignee.clone(),
+            labels: join_labels(&story.labels),
+            due: story.due,
+            issue_type: Some(story.issue_type),
         }
+    }
+
+    pub fn create_epic(&self, epic: Epic) -> Result<usize> {
+        self.modify(|db_state| {
+            check_user(db_state, epic.owner.as_deref())?;
+
+            let new_id = db_state.last_item_id + 1;
+            db_state.epics.insert(new_id, epic);
+            db_state.last_item_id = new_id;
 
-        db_state.epics.remove(&epic_id);
+            Ok(new_id)
+        })
+    }
 
-        println!("{:?}", db_state);
+    pub fn create_story(&self, mut story: Story, epic_id: usize) -> Result<usize> {
+        self.config.check_points(story.points)?;
+        if story.reporter.is_none() {
+            story.reporter = Some(self.config.current_user());
+        }
+        story.set_issue_type(story.issue_type);
+        self.modify(|db_state| {
+            check_user(db_state, story.assignee.as_deref())?;
 
-        db_state.last_item_id = *max(
+            let new_id = db_state.last_item_id + 1;
+            db_state.stories.insert(new_id, story);
             db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or_else(|| anyhow!("Invalid Epic ID!"))?
                 .stories
-                .keys()
-                .sorted_by(|a, b| a.partial_cmp(b).unwrap())
-                .last()
-                .unwrap_or(&(0 as usize)),
+                .push(new_id);
+            db_state.last_item_id = new_id;
+            db_state.update_epic_status(epic_id, &self.config.epic_rollup);
+
+            Ok(new_id)
+        })
+    }
+
+    pub fn delete_epic(&self, epic_id: usize) -> Result<()> {
+        self.modify(|db_state| {
             db_state
                 .epics
-                .keys()
-                .sorted_by(|a, b| a.partial_cmp(b).unwrap())
-                .last()
-                .unwrap_or(&(0 as usize)),
-        );
+                .get(&epic_id)
+                .ok_or_else(|| anyhow!("Invalid Epic ID!"))?;
+
+            for story_id in &db_state.epics[&epic_id].stories {
+                if let Some(story) = db_state.stories.remove(story_id) {
+                    for subtask_id in story.subtasks {
+                        db_state.subtasks.remove(&subtask_id);
+                    }
+                }
+            }
+
+            db_state.epics.remove(&epic_id);
+            db_state.remove_dangling_references();
 
-        return Ok(self.database.write_db(&db_state)?);
+            Ok(())
+        })
     }
 
     pub fn delete_story(&self, epic_id: usize, story_id: usize) -> Result<()> {
-        let mut db_state = self.database.read_db()?;
-        db_state
-            .stories
-            .remove(&story_id)
-            .ok_or_else(|| anyhow!("Invalid Story ID!"))?;
-        let remaining_stories = Vec::from_iter(
+        self.modify(|db_state| {
+            let story = db_state
+                .stories
+                .remove(&story_id)
+                .ok_or_else(|| anyhow!("Invalid Story ID!"))?;
+            for subtask_id in story.subtasks {
+                db_state.subtasks.remove(&subtask_id);
+            }
+            let remaining_stories = Vec::from_iter(
+                db_state
+                    .epics
+                    .get_mut(&epic_id)
+                    .ok_or_else(|| anyhow!("Invalid Epic Id!"))?
+                    .stories
+                    .iter()
+                    .filter(|&&x| x != story_id)
+                    .map(|y| *y),
+            );
             db_state
                 .epics
                 .get_mut(&epic_id)
                 .ok_or_else(|| anyhow!("Invalid Epic Id!"))?
-                .stories
-                .iter()
-                .filter(|&&x| x != story_id)
-                .map(|y| *y),
-        );
-        db_state
-            .epics
-            .get_mut(&epic_id)
-            .ok_or_else(|| anyhow!("Invalid Epic Id!"))?
-            .stories = remaining_stories;
-        db_state.last_item_id = *max(
-            db_state
-                .stories
-                .keys()
-                .sorted_by(|a, b| a.partial_cmp(b).unwrap())
-                .last()
-                .unwrap_or(&(0 as usize)),
-            db_state
-                .epics
-                .keys()
-                .sorted_by(|a, b| a.partial_cmp(b).unwrap())
-                .last()
-                .unwrap_or(&(0 as usize)),
-        );
-        return Ok(self.database.write_db(&db_state)?);
-    }
+                .stories = remaining_stories;
+            db_state.remove_dangling_references();
+            db_state.update_epic_status(epic_id, &self.config.epic_rollup);
 
-    pub fn update_epic_status(&self, epic_id: usize) -> Result<()> {
-        let mut db_state = self.read_db()?;
-        db_state.update_epic_status(epic_id);
-        return Ok(self.database.write_db(&db_state)?);
+            Ok(())
+        })
     }
 
-    pub fn update_story_status(&self, story_id: usize, status: Status) -> Result<()> {
-        let mut db_state = self.database.read_db()?;
+    pub fn update_epic(&self, epic_id: usize, name: String, description: String) -> Result<()> {
+        self.modify(|db_state| {
+            let epic = db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or_else(|| anyhow!("Invalid Epic ID!"))?;
+            epic.name = name;
+            epic.description = description;
 
-        db_state
-            .stories
-            .get_mut(&story_id)
-            .ok_or_else(|| anyhow!("Invalid Story ID!"))?
-            .status = status;
+            Ok(())
+        })
+    }
 
-        let mut epic_id: usize = 0;
-        for (id, contents) in &db_state.epics {
-            if contents.stories.contains(&story_id) {
-                epic_id = *id;
-                break;
-            }
-        }
+    pub fn update_story(&self, story_id: usize, name: String, description: String) -> Result<()> {
+        self.modify(|db_state| {
+            let story = db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or_else(|| anyhow!("Invalid Story ID!"))?;
+            story.name = name;
+            story.description = description;
 
-        self.database.write_db(&db_state)?;
-        return self.update_epic_status(epic_id);
+            Ok(())
+        })
     }
 
-    pub fn close_epic(&self, epic_id: usize) -> Result<()> {
-        let mut db_state = self.database.read_db()?;
-
-        db_state
-            .epics
-            .get_mut(&epic_id)
-            .ok_or_else(|| anyhow!("Invalid Epic ID!"))?
-            .status = Status::Closed;
+    pub fn update_story_points(&self, story_id: usize, points: Option<u32>) -> Result<()> {
+        self.config.check_points(points)?;
+        self.modify(|db_state| {
+            db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or_else(|| anyhow!("Invalid Story ID!"))?
+                .points = points;
 
-        return Ok(self.database.write_db(&db_state)?);
+            Ok(())
+        })
     }
-}
-
-pub trait Database {
-    fn read_db(&self) -> Result<DBState>;
-    fn write_db(&self, db_state: &DBState) -> Result<()>;
-}
 
-pub struct JSONFileDatabase {
-    pub file_path: String,
-}
+    pub fn update_epic_priority(&self, epic_id: usize, priority: Priority) -> Result<()> {
+        self.modify(|db_state| {
+            db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or_else(|| anyhow!("Invalid Epic ID!"))?
+                .priority = priority;
 
-impl JSONFileDatabase {
-    pub fn new(file_path: String) -> Self {
-        Self { file_path }
+            Ok(())
+        })
     }
-}
 
-impl Database for JSONFileDatabase {
-    fn read_db(&self) -> Result<DBState> {
-        let data = fs::read_to_string(&self.file_path)?;
-        let db_state: DBState = serde_json::from_str(&data)?;
-        return Ok(db_state);
-    }
+    pub fn update_story_priority(&self, story_id: usize, priority: Priority) -> Result<()> {
+        self.modify(|db_state| {
+            db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or_else(|| anyhow!("Invalid Story ID!"))?
+                .priority = priority;
 
-    fn write_db(&self, db_state: &DBState) -> Result<()> {
-        let json_db = serde_json::to_string(db_state)?;
-        Ok(fs::write(&self.file_path, json_db)?)
+            Ok(())
+        })
     }
-}
-
-pub mod test_utils {
-    use std::{cell::RefCell, collections::HashMap};
 
-    use super::*;
+    pub fn update_story_type(&self, story_id: usize, issue_type: IssueType) -> Result<()> {
+        self.modify(|db_state| {
+            db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or_else(|| anyhow!("Invalid Story ID!"))?
+                .set_issue_type(issue_type);
 
-    pub struct MockDB {
-        pub last_written_state: RefCell<DBState>,
+            Ok(())
+        })
     }
 
-    impl MockDB {
-        pub fn new() -> Self {
-            Self {
-                last_written_state: RefCell::new(DBState {
-                    last_item_id: 0,
-                    epics: HashMap::new(),
-                    stories: HashMap::new(),
-                }),
+    pub fn update_bug_report(&self, story_id: usize, bug: BugReport) -> Result<()> {
+        self.modify(|db_state| {
+            let story = db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or_else(|| anyhow!("Invalid Story ID!"))?;
+            if story.issue_type != IssueType::Bug {
+                return Err(anyhow!("Only bugs have a severity and steps to reproduce!"));
             }
-        }
+            story.bug = Some(bug);
+
+            Ok(())
+        })
     }
 
-    impl Database for MockDB {
-        fn read_db(&self) -> Result<DBState> {
-            // TODO: fix this error by deriving the appropriate traits for Story
-            let state = self.last_written_state.borrow().clone();
-            Ok(state)
-        }
+    pub fn update_epic_due_date(&self, epic_id: usize, due: Option<NaiveDate>) -> Result<()> {
+        self.modify(|db_state| {
+            db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or_else(|| anyhow!("Invalid Epic ID!"))?
+                .due = due;
 
-        fn write_db(&self, db_state: &DBState) -> Result<()> {
-            let latest_state = &self.last_written_state;
-            // TODO: fix this error by deriving the appropriate traits for DBState
-            *latest_state.borrow_mut() = db_state.clone();
             Ok(())
-        }
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::test_utils::MockDB;
-    use super::*;
+    pub fn update_story_due_date(&self, story_id: usize, due: Option<NaiveDate>) -> Result<()> {
+        self.modify(|db_state| {
+            db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or_else(|| anyhow!("Invalid Story ID!"))?
+                .due = due;
 
-    #[test]
-    fn create_epic_should_work() {
-        let db = JiraDatabase {
-            database: Box::new(MockDB::new()),
-        };
-        let epic = Epic::new("".to_owned(), "".to_owned());
+            Ok(())
+        })
+    }
 
-        // TODO: fix this error by deriving the appropriate traits for Epic
-        let result = db.create_epic(epic.clone());
-        println!("{:?}", result.as_ref().unwrap());
+    pub fn add_user(&self, username: String, name: String) -> Result<()> {
+        if username.is_empty() || username.contains(char::is_whitespace) {
+            return Err(anyhow!("Invalid username '{}'!", username));
+        }
+        self.modify(|db_state| {
+            if db_state.users.contains_key(&username) {
+                return Err(anyhow!("User '{}' already exists!", username));
+            }
+            db_state.users.insert(username, User { name });
 
-        assert_eq!(result.is_ok(), true);
+            Ok(())
+        })
+    }
 
-        let id = result.unwrap();
-        let db_state = db.read_db().unwrap();
+    // removing a user unassigns everything they own or work on, reporters are kept as history
+    pub fn remove_user(&self, username: &str) -> Result<()> {
+        self.modify(|db_state| {
+            db_state
+                .users
+                .remove(username)
+                .ok_or_else(|| anyhow!("Unknown user '{}'!", username))?;
+            for epic in db_state.epics.values_mut() {
+                if epic.owner.as_deref() == Some(username) {
+                    epic.owner = None;
+                }
+            }
+            for story in db_state.stories.values_mut() {
+                if story.assignee.as_deref() == Some(username) {
+                    story.assignee = None;
+                }
+            }
 
-        let expected_id = 1;
+            Ok(())
+        })
+    }
 
-        assert_eq!(id, expected_id);
-        assert_eq!(db_state.last_item_id, expected_id);
-        assert_eq!(db_state.epics.get(&id), Some(&epic));
+    pub fn assign_epic(&self, epic_id: usize, owner: Option<String>) -> Result<()> {
+        self.modify(|db_state| {
+            check_user(db_state, owner.as_deref())?;
+            db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or_else(|| anyhow!("Invalid Epic ID!"))?
+                .owner = owner;
+
+            Ok(())
+        })
     }
 
-    #[test]
-    fn create_story_should_error_if_invalid_epic_id() {
-        let db = JiraDatabase {
-            database: Box::new(MockDB::new()),
-        };
-        let story = Story::new("".to_owned(), "".to_owned());
+    pub fn assign_story(&self, story_id: usize, assignee: Option<String>) -> Result<()> {
+        self.modify(|db_state| {
+            check_user(db_state, assignee.as_deref())?;
+            db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or_else(|| anyhow!("Invalid Story ID!"))?
+                .assignee = assignee;
 
-        let non_existent_epic_id = 999;
+            Ok(())
+        })
+    }
 
-        let result = db.create_story(story, non_existent_epic_id);
-        assert_eq!(result.is_err(), true);
+    pub fn add_label(&self, item_id: usize, label: &str) -> Result<()> {
+        let label = label.trim();
+        if label.is_empty() || label.contains(char::is_whitespace) {
+            return Err(anyhow!("Invalid label '{}'!", label));
+        }
+        self.modify(|db_state| {
+            item_labels(db_state, item_id)?.insert(label.to_owned());
+
+            Ok(())
+        })
     }
 
-    #[test]
-    fn create_story_should_work() {
-        let db = JiraDatabase {
-            database: Box::new(MockDB::new()),
+    pub fn add_comment(&self, item_id: usize, body: String) -> Result<()> {
+        if body.trim().is_empty() {
+            return Err(anyhow!("Comments can't be empty!"));
+        }
+        let comment = Comment {
+            author: self.config.current_user(),
+            timestamp: Utc::now(),
+            body,
         };
-        let epic = Epic::new("".to_owned(), "".to_owned());
-        let story = Story::new("".to_owned(), "".to_owned());
+        self.modify(|db_state| {
+            item_comments(db_state, item_id)?.push(comment);
 
-        let result = db.create_epic(epic);
-        assert_eq!(result.is_ok(), true);
+            Ok(())
+        })
+    }
 
-        let epic_id = result.unwrap();
+    pub fn remove_label(&self, item_id: usize, label: &str) -> Result<()> {
+        self.modify(|db_state| {
+            if !item_labels(db_state, item_id)?.remove(label.trim()) {
+                return Err(anyhow!("Item has no label '{}'!", label.trim()));
+            }
 
-        // TODO: fix this error by deriving the appropriate traits for Story
-        let result = db.create_story(story.clone(), epic_id);
-        assert_eq!(result.is_ok(), true);
+            Ok(())
+        })
+    }
 
-        let id = result.unwrap();
-        let db_state = db.read_db().unwrap();
+    // the epics owned by and the stories assigned to a user, most urgent first
+    pub fn work_rows(&self, username: &str) -> Result<Vec<ItemRow>> {
+        let db_state = self.read_db()?;
+        let epic_ids = db_state.epics.keys().copied().sorted().collect_vec();
+        let epic_rows = epic_ids.iter().map(|id| self.epic_row(&db_state, *id));
+        let story_rows = epic_ids
+            .iter()
+            .flat_map(|id| &db_state.epics[id].stories)
+            .map(|id| self.story_row(&db_state, *id));
+        let rows = epic_rows
+            .chain(story_rows)
+            .filter(|row| row.assignee.as_deref() == Some(username))
+            .collect();
+        Ok(sort_rows(rows, SortOrder::Priority))
+    }
 
-        let expected_id = 2;
+    // moves a story to the first workflow state of the status. Only the transitions of the
+    // workflow are allowed, and resolving or closing a story that open stories still block is
+    // refused with a BlockedError.
+    pub fn update_story_status(&self, story_id: usize, status: Status) -> Result<()> {
+        self.set_story_state(story_id, &self.status_state(status)?, true)
+    }
 
-        assert_eq!(id, expected_id);
-        assert_eq!(db_state.last_item_id, expected_id);
-        assert_eq!(
-            db_state.epics.get(&epic_id).unwrap().stories.contains(&id),
-            true
-        );
-        assert_eq!(db_state.stories.get(&id), Some(&story));
+    // like update_story_status, but resolves blocked stories anyway
+    pub fn force_story_status(&self, story_id: usize, status: Status) -> Result<()> {
+        self.set_story_state(story_id, &self.status_state(status)?, false)
     }
 
-    #[test]
-    fn delete_epic_should_error_if_invalid_epic_id() {
-        let db = JiraDatabase {
-            database: Box::new(MockDB::new()),
-        };
+    // like update_story_status, for any state of the workflow
+    pub fn update_story_state(&self, story_id: usize, state: &str) -> Result<()> {
+        self.set_story_state(story_id, state, true)
+    }
 
-        let non_existent_epic_id = 999;
+    pub fn force_story_state(&self, story_id: usize, state: &str) -> Result<()> {
+        self.set_story_state(story_id, state, false)
+    }
 
-        let result = db.delete_epic(non_existent_epic_id);
-        assert_eq!(result.is_err(), true);
+    fn status_state(&self, status: Status) -> Result<String> {
+        self.config
+            .workflow
+            .state_for_status(status)
+            .map(|state| state.name.clone())
+            .ok_or_else(|| anyhow!("The workflow has no state for {} stories!", status))
     }
 
-    #[test]
-    fn delete_epic_should_work() {
-        let db = JiraDatabase {
-            database: Box::new(MockDB::new()),
-        };
-        let epic = Epic::new("".to_owned(), "".to_owned());
-        let story = Story::new("".to_owned(), "".to_owned());
+    fn set_story_state(&self, story_id: usize, state: &str, check_blockers: bool) -> Result<()> {
+        let workflow = &self.config.workflow;
+        let target = workflow
+            .state(state)
+            .ok_or_else(|| anyhow!("Unknown workflow state '{}'!", state))?;
 
-        let result = db.create_epic(epic);
-        assert_eq!(result.is_ok(), true);
+        self.modify(|db_state| {
+            let epic_id = db_state
+                .epics
+                .iter()
+                .find(|(_, epic)| epic.stories.contains(&story_id))
+                .map(|(id, _)| *id);
+
+            if let Some(epic_id) = epic_id {
+                if db_state.epics[&epic_id].status == Status::Closed {
+                    return Err(anyhow!(
+                        "Cannot change the status of a Story from a closed Epic!"
+                    ));
+                }
+            }
 
-        let epic_id = result.unwrap();
+            self.check_story_move(db_state, story_id, target, check_blockers)?;
 
-        let result = db.create_story(story, epic_id);
-        assert_eq!(result.is_ok(), true);
+            let story = db_state.stories.get_mut(&story_id).unwrap();
+            story.status = target.category;
+            story.state = Some(target.name.clone());
 
-        let story_id = result.unwrap();
+            if let Some(epic_id) = epic_id {
+                db_state.update_epic_status(epic_id, &self.config.epic_rollup);
+            }
 
-        let result = db.delete_epic(epic_id);
-        assert_eq!(result.is_ok(), true);
+            Ok(())
+        })
+    }
 
-        let db_state = db.read_db().unwrap();
+    // refuses moves the workflow has no transition for, and resolving or closing a story that
+    // open stories still block when asked to check them
+    fn check_story_move(
+        &self,
+        db_state: &DBState,
+        story_id: usize,
+        target: &WorkflowState,
+        check_blockers: bool,
+    ) -> Result<()> {
+        let workflow = &self.config.workflow;
+        let story = db_state
+            .stories
+            .get(&story_id)
+            .ok_or_else(|| anyhow!("Invalid Story ID!"))?;
+        // staying in the current state is always allowed
+        let current = workflow.story_state(story);
+        let next_states = workflow.next_states(&current);
+        if !current.eq_ignore_ascii_case(&target.name)
+            && !next_states
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&target.name))
+        {
+            return Err(anyhow!(
+                "{} cannot move from {} to {}! Allowed: {}",
+                self.config.issue_key(story_id),
+                current,
+                target.name,
+                match next_states.is_empty() {
+                    true => "none".to_owned(),
+                    false => next_states.join(", "),
+                }
+            ));
+        }
 
-        let expected_last_id = 0;
+        let blockers = db_state.open_blockers(story_id);
+        let resolving = matches!(target.category, Status::Resolved | Status::Closed);
+        if check_blockers && resolving && !blockers.is_empty() {
+            return Err(BlockedError {
+                story: self.config.issue_key(story_id),
+                blockers: blockers
+                    .into_iter()
+                    .map(|id| self.config.issue_key(id))
+                    .collect(),
+            }
+            .into());
+        }
 
-        assert_eq!(db_state.last_item_id, expected_last_id);
-        assert_eq!(db_state.epics.get(&epic_id), None);
-        assert_eq!(db_state.stories.get(&story_id), None);
+        Ok(())
     }
 
-    #[test]
-    fn delete_story_should_error_if_invalid_epic_id() {
-        let db = JiraDatabase {
-            database: Box::new(MockDB::new()),
+    // moves a story to the end of another epic and rolls both epics' statuses up again
+    pub fn move_story(&self, story_id: usize, epic_id: usize) -> Result<()> {
+        self.modify(|db_state| {
+            let from_epic_id = db_state
+                .story_epic(story_id)
+                .ok_or_else(|| anyhow!("Invalid Story ID!"))?;
+            let to_epic = db_state
+                .epics
+                .get(&epic_id)
+                .ok_or_else(|| anyhow!("Invalid Epic ID!"))?;
+            if from_epic_id == epic_id {
+                return Err(anyhow!("Story already belongs to this Epic!"));
+            }
+            if to_epic.status == Status::Closed
+                || db_state.epics[&from_epic_id].status == Status::Closed
+            {
+                return Err(anyhow!("Cannot move a Story from or to a closed Epic!"));
+            }
+
+            db_state
+                .epics
+                .get_mut(&from_epic_id)
+                .unwrap()
+                .stories
+                .retain(|id| *id != story_id);
+            db_state
+                .epics
+                .get_mut(&epic_id)
+                .unwrap()
+                .stories
+                .push(story_id);
+            db_state.update_epic_status(from_epic_id, &self.config.epic_rollup);
+            db_state.update_epic_status(epic_id, &self.config.epic_rollup);
+
+            Ok(())
+        })
+    }
+
+    pub fn create_sprint(&self, sprint: Sprint) -> Result<usize> {
+        if let (Some(start), Some(end)) = (sprint.start, sprint.end) {
+            if end < start {
+                return Err(anyhow!("A sprint cannot end before it starts!"));
+            }
+        }
+        self.modify(|db_state| {
+            let new_id = db_state.last_sprint_id + 1;
+            db_state.sprints.insert(new_id, sprint);
+            db_state.last_sprint_id = new_id;
+
+            Ok(new_id)
+        })
+    }
+
+    // pulls a story from any epic into a planned or active sprint
+    pub fn add_story_to_sprint(&self, sprint_id: usize, story_id: usize) -> Result<()> {
+        self.modify(|db_state| {
+            if !db_state.stories.contains_key(&story_id) {
+                return Err(anyhow!("Invalid Story ID!"));
+            }
+            if let Some(current_sprint_id) = db_state.story_sprint(story_id) {
+                return Err(anyhow!(
+                    "Story is already in sprint {}!",
+                    db_state.sprints[&current_sprint_id].name
+                ));
+            }
+            let sprint = db_state
+                .sprints
+                .get_mut(&sprint_id)
+                .ok_or_else(|| anyhow!("Invalid Sprint ID!"))?;
+            if sprint.state == SprintState::Completed {
+                return Err(anyhow!("Cannot change a completed sprint!"));
+            }
+            sprint.stories.push(story_id);
+
+            Ok(())
+        })
+    }
+
+    // puts a story of a planned or active sprint back into the backlog
+    pub fn remove_story_from_sprint(&self, sprint_id: usize, story_id: usize) -> Result<()> {
+        self.modify(|db_state| {
+            let sprint = db_state
+                .sprints
+                .get_mut(&sprint_id)
+                .ok_or_else(|| anyhow!("Invalid Sprint ID!"))?;
+            if sprint.state == SprintState::Completed {
+                return Err(anyhow!("Cannot change a completed sprint!"));
+            }
+            if !sprint.stories.contains(&story_id) {
+                return Err(anyhow!("Story is not in this sprint!"));
+            }
+            sprint.stories.retain(|id| *id != story_id);
+
+            Ok(())
+        })
+    }
+
+    // only one sprint runs at a time. Sprints without a start date start today.
+    pub fn start_sprint(&self, sprint_id: usize) -> Result<()> {
+        self.modify(|db_state| {
+            if let Some(active_sprint_id) = db_state.active_sprint() {
+                return Err(anyhow!(
+                    "Sprint {} is still active!",
+                    db_state.sprints[&active_sprint_id].name
+                ));
+            }
+            let sprint = db_state
+                .sprints
+                .get_mut(&sprint_id)
+                .ok_or_else(|| anyhow!("Invalid Sprint ID!"))?;
+            if sprint.state != SprintState::Planned {
+                return Err(anyhow!("Only planned sprints can be started!"));
+            }
+            sprint.state = SprintState::Active;
+            sprint
+                .start
+                .get_or_insert_with(|| Local::now().date_naive());
+
+            Ok(())
+        })
+    }
+
+    // completes the active sprint, moving its unfinished stories into the next sprint or back
+    // to the backlog when there is none
+    pub fn complete_sprint(&self, sprint_id: usize, next_sprint_id: Option<usize>) -> Result<()> {
+        self.modify(|db_state| {
+            let sprint = db_state
+                .sprints
+                .get(&sprint_id)
+                .ok_or_else(|| anyhow!("Invalid Sprint ID!"))?;
+            if sprint.state != SprintState::Active {
+                return Err(anyhow!("Only the active sprint can be completed!"));
+            }
+            let (finished, unfinished): (Vec<usize>, Vec<usize>) =
+                sprint.stories.iter().partition(|id| {
+                    matches!(
+                        db_state.stories[id].status,
+                        Status::Resolved | Status::Closed
+                    )
+                });
+
+            if let Some(next_sprint_id) = next_sprint_id {
+                let next_sprint = db_state
+                    .sprints
+                    .get_mut(&next_sprint_id)
+                    .ok_or_else(|| anyhow!("Invalid Sprint ID!"))?;
+                if next_sprint.state != SprintState::Planned {
+                    return Err(anyhow!(
+                        "Unfinished stories can only move into a planned sprint!"
+                    ));
+                }
+                next_sprint.stories.extend(unfinished);
+            }
+
+            let sprint = db_state.sprints.get_mut(&sprint_id).unwrap();
+            sprint.stories = finished;
+            sprint.state = SprintState::Completed;
+            sprint.end.get_or_insert_with(|| Local::now().date_naive());
+
+            Ok(())
+        })
+    }
+
+    // links two stories, which may belong to different epics
+    pub fn link_stories(&self, source_id: usize, kind: LinkKind, target_id: usize) -> Result<()> {
+        self.modify(|db_state| {
+            if !db_state.stories.contains_key(&target_id) {
+                return Err(anyhow!("Invalid Story ID!"));
+            }
+            if source_id == target_id {
+                return Err(anyhow!("A story cannot be linked to itself!"));
+            }
+            let source = db_state
+                .stories
+                .get(&source_id)
+                .ok_or_else(|| anyhow!("Invalid Story ID!"))?;
+            let link = Link {
+                kind,
+                target: target_id,
+            };
+            if source.links.contains(&link) {
+                return Err(anyhow!("Stories are already linked!"));
+            }
+            // the new link would close a loop if the target already blocks the source
+            if kind == LinkKind::Blocks && db_state.blocks_transitively(target_id, source_id) {
+                return Err(anyhow!(
+                    "{} already blocks {}, linking them would create a cycle!",
+                    self.config.issue_key(target_id),
+                    self.config.issue_key(source_id)
+                ));
+            }
+
+            db_state
+                .stories
+                .get_mut(&source_id)
+                .unwrap()
+                .links
+                .push(link);
+
+            Ok(())
+        })
+    }
+
+    // removes every link between the two stories, whichever of them it starts from
+    pub fn unlink_stories(&self, story_id: usize, other_id: usize) -> Result<()> {
+        self.modify(|db_state| {
+            let mut removed = false;
+            for (source_id, target_id) in [(story_id, other_id), (other_id, story_id)] {
+                let source = db_state
+                    .stories
+                    .get_mut(&source_id)
+                    .ok_or_else(|| anyhow!("Invalid Story ID!"))?;
+                let count = source.links.len();
+                source.links.retain(|link| link.target != target_id);
+                removed |= source.links.len() != count;
+            }
+            if !removed {
+                return Err(anyhow!("Stories are not linked!"));
+            }
+
+            Ok(())
+        })
+    }
+
+    pub fn create_subtask(&self, subtask: Subtask, story_id: usize) -> Result<usize> {
+        self.modify(|db_state| {
+            check_subtask_epic_open(db_state, story_id)?;
+            let new_id = db_state.last_item_id + 1;
+            db_state.subtasks.insert(new_id, subtask);
+            db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or_else(|| anyhow!("Invalid Story ID!"))?
+                .subtasks
+                .push(new_id);
+            db_state.last_item_id = new_id;
+            self.roll_up_subtasks(db_state, story_id);
+
+            Ok(new_id)
+        })
+    }
+
+    pub fn delete_subtask(&self, story_id: usize, subtask_id: usize) -> Result<()> {
+        self.modify(|db_state| {
+            check_subtask_epic_open(db_state, story_id)?;
+            let story = db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or_else(|| anyhow!("Invalid Story ID!"))?;
+            if !story.subtasks.contains(&subtask_id) {
+                return Err(anyhow!("Invalid Sub-task ID!"));
+            }
+            story.subtasks.retain(|id| *id != subtask_id);
+            db_state.subtasks.remove(&subtask_id);
+            self.roll_up_subtasks(db_state, story_id);
+
+            Ok(())
+        })
+    }
+
+    pub fn update_subtask_status(&self, subtask_id: usize, status: Status) -> Result<()> {
+        self.modify(|db_state| {
+            let story_id = db_state
+                .subtask_story(subtask_id)
+                .ok_or_else(|| anyhow!("Invalid Sub-task ID!"))?;
+            check_subtask_epic_open(db_state, story_id)?;
+
+            db_state
+                .subtasks
+                .get_mut(&subtask_id)
+                .ok_or_else(|| anyhow!("Invalid Sub-task ID!"))?
+                .status = status;
+            self.roll_up_subtasks(db_state, story_id);
+
+            Ok(())
+        })
+    }
+
+    // keeps the story, and in turn its epic, in step with the sub-tasks when configured to.
+    // The story moves like set_story_state would move it; where the workflow or open blockers
+    // refuse that, the story stays where it is and only the sub-task changes.
+    fn roll_up_subtasks(&self, db_state: &mut DBState, story_id: usize) {
+        if !self.config.rollup_subtasks {
+            return;
+        }
+        let Some(target) = db_state
+            .subtask_rollup_status(story_id)
+            .filter(|status| *status != db_state.stories[&story_id].status)
+            .and_then(|status| self.config.workflow.state_for_status(status))
+        else {
+            return;
+        };
+        if self
+            .check_story_move(db_state, story_id, target, true)
+            .is_err()
+        {
+            return;
+        }
+
+        let story = db_state.stories.get_mut(&story_id).unwrap();
+        story.status = target.category;
+        story.state = Some(target.name.clone());
+        if let Some(epic_id) = db_state.story_epic(story_id) {
+            db_state.update_epic_status(epic_id, &self.config.epic_rollup);
+        }
+    }
+
+    // reopening resets a closed epic to the status its stories roll up to. A finished epic
+    // comes back resolved even where the roll-up closes epics by itself.
+    pub fn reopen_epic(&self, epic_id: usize) -> Result<()> {
+        self.modify(|db_state| {
+            let epic = db_state
+                .epics
+                .get(&epic_id)
+                .ok_or_else(|| anyhow!("Invalid Epic ID!"))?;
+            if epic.status != Status::Closed {
+                return Err(anyhow!("Only closed epics can be reopened!"));
+            }
+            let status = match self
+                .config
+                .epic_rollup
+                .status(&db_state.epic_story_statuses(epic_id))
+            {
+                Status::Closed => Status::Resolved,
+                status => status,
+            };
+            db_state.epics.get_mut(&epic_id).unwrap().status = status;
+
+            Ok(())
+        })
+    }
+
+    pub fn close_epic(&self, epic_id: usize) -> Result<()> {
+        self.modify(|db_state| {
+            db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or_else(|| anyhow!("Invalid Epic ID!"))?
+                .status = Status::Closed;
+
+            Ok(())
+        })
+    }
+
+    // replaces the tracker contents with an earlier snapshot. Ids handed out since then stay
+    // used, so restoring never causes an id to be reused.
+    pub fn restore(&self, snapshot: DBState) -> Result<()> {
+        self.modify(|db_state| {
+            *db_state = DBState {
+                schema_version: db_state.schema_version,
+                revision: db_state.revision,
+                last_item_id: db_state.last_item_id.max(snapshot.last_item_id),
+                last_sprint_id: db_state.last_sprint_id.max(snapshot.last_sprint_id),
+                ..snapshot
+            };
+
+            Ok(())
+        })
+    }
+}
+
+fn check_user(db_state: &DBState, username: Option<&str>) -> Result<()> {
+    match username {
+        Some(username) if !db_state.users.contains_key(username) => {
+            Err(anyhow!("Unknown user '{}'!", username))
+        }
+        _ => Ok(()),
+    }
+}
+
+// sub-tasks of a closed epic's stories are frozen along with the epic
+fn check_subtask_epic_open(db_state: &DBState, story_id: usize) -> Result<()> {
+    match db_state.story_epic(story_id) {
+        Some(epic_id) if db_state.epics[&epic_id].status == Status::Closed => Err(anyhow!(
+            "Cannot change the Sub-tasks of a Story from a closed Epic!"
+        )),
+        _ => Ok(()),
+    }
+}
+
+// epics and stories share ids, so labels can be edited without knowing which one an id is
+fn item_labels(db_state: &mut DBState, item_id: usize) -> Result<&mut BTreeSet<String>> {
+    if let Some(epic) = db_state.epics.get_mut(&item_id) {
+        return Ok(&mut epic.labels);
+    }
+    db_state
+        .stories
+        .get_mut(&item_id)
+        .map(|story| &mut story.labels)
+        .ok_or_else(|| anyhow!("Invalid Item ID!"))
+}
+
+fn item_comments(db_state: &mut DBState, item_id: usize) -> Result<&mut Vec<Comment>> {
+    if let Some(epic) = db_state.epics.get_mut(&item_id) {
+        return Ok(&mut epic.comments);
+    }
+    db_state
+        .stories
+        .get_mut(&item_id)
+        .map(|story| &mut story.comments)
+        .ok_or_else(|| anyhow!("Invalid Item ID!"))
+}
+
+// keeps only the rows carrying the label, or every row when there is no label to filter by
+pub fn filter_rows(rows: Vec<ItemRow>, label: Option<&str>) -> Vec<ItemRow> {
+    match label {
+        Some(label) => rows
+            .into_iter()
+            .filter(|row| row.labels.split_whitespace().any(|l| l == label))
+            .collect(),
+        None => rows,
+    }
+}
+
+// keeps only the rows of the issue type, or every row when there is no type to filter by
+pub fn filter_rows_by_type(rows: Vec<ItemRow>, issue_type: Option<IssueType>) -> Vec<ItemRow> {
+    match issue_type {
+        Some(issue_type) => rows
+            .into_iter()
+            .filter(|row| row.issue_type == Some(issue_type))
+            .collect(),
+        None => rows,
+    }
+}
+
+// sorting is stable, so rows of equal priority keep their order
+fn sort_rows(mut rows: Vec<ItemRow>, sort_order: SortOrder) -> Vec<ItemRow> {
+    if sort_order == SortOrder::Priority {
+        rows.sort_by_key(|row| row.priority);
+    }
+    rows
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConflictError {
+    pub expected_revision: u64,
+    pub actual_revision: u64,
+}
+
+impl Display for ConflictError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the database was changed by someone else (expected revision {}, found {})",
+            self.expected_revision, self.actual_revision
+        )
+    }
+}
+
+impl error::Error for ConflictError {}
+
+// returned when resolving or closing a story that open stories still block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockedError {
+    pub story: String,
+    pub blockers: Vec<String>,
+}
+
+impl Display for BlockedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} is still blocked by {}",
+            self.story,
+            self.blockers.join(", ")
+        )
+    }
+}
+
+impl error::Error for BlockedError {}
+
+// write_db implementations must refuse to write a state whose revision doesn't match the one
+// currently stored, and store the state with its revision bumped by one otherwise
+fn check_revision(db_state: &DBState, actual_revision: u64) -> Result<DBState> {
+    if db_state.revision != actual_revision {
+        return Err(ConflictError {
+            expected_revision: db_state.revision,
+            actual_revision,
+        }
+        .into());
+    }
+
+    let mut next_state = db_state.clone();
+    next_state.revision += 1;
+    Ok(next_state)
+}
+
+pub trait Database {
+    fn read_db(&self) -> Result<DBState>;
+    fn write_db(&self, db_state: &DBState) -> Result<()>;
+
+    // the audit log is append-only and kept apart from DBState, so restoring an older state
+    // never rewrites history
+    fn append_event(&self, event: &Event) -> Result<()>;
+    fn read_events(&self) -> Result<Vec<Event>>;
+
+    // backends that can be shared between processes should return a lock that excludes every
+    // other holder until it is dropped
+    fn lock(&self) -> Result<DatabaseLock> {
+        Ok(DatabaseLock { file: None })
+    }
+}
+
+// an advisory lock on a `<database>.lock` file next to the database, released on drop
+pub struct DatabaseLock {
+    file: Option<File>,
+}
+
+impl DatabaseLock {
+    pub fn acquire(database_path: &str) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(format!("{}.lock", database_path))?;
+        file.lock()?;
+
+        Ok(Self { file: Some(file) })
+    }
+}
+
+impl Drop for DatabaseLock {
+    fn drop(&mut self) {
+        if let Some(file) = &self.file {
+            let _ = file.unlock();
+        }
+    }
+}
+
+pub struct JSONFileDatabase {
+    pub file_path: String,
+}
+
+impl JSONFileDatabase {
+    pub fn new(file_path: String) -> Self {
+        Self { file_path }
+    }
+}
+
+type Migration = fn(&mut Map<String, Value>) -> Result<()>;
+
+// MIGRATIONS[n] upgrades a version n document to version n + 1. Documents written before
+// schema_version existed are version 0.
+const MIGRATIONS: [Migration; CURRENT_SCHEMA_VERSION as usize] = [
+    migrate_v0_to_v1,
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+    migrate_v4_to_v5,
+    migrate_v5_to_v6,
+    migrate_v6_to_v7,
+    migrate_v7_to_v8,
+    migrate_v8_to_v9,
+    migrate_v9_to_v10,
+    migrate_v10_to_v11,
+    migrate_v11_to_v12,
+];
+
+fn migrate_v0_to_v1(document: &mut Map<String, Value>) -> Result<()> {
+    document.entry("revision").or_insert(json!(0));
+    Ok(())
+}
+
+fn migrate_v1_to_v2(document: &mut Map<String, Value>) -> Result<()> {
+    let stories = document
+        .get_mut("stories")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| anyhow!("Database file does not contain any stories!"))?;
+    for story in stories.values_mut() {
+        if let Some(story) = story.as_object_mut() {
+            story.entry("points").or_insert(Value::Null);
+        }
+    }
+    Ok(())
+}
+
+fn migrate_v2_to_v3(document: &mut Map<String, Value>) -> Result<()> {
+    for items in ["epics", "stories"] {
+        let items = document
+            .get_mut(items)
+            .and_then(Value::as_object_mut)
+            .ok_or_else(|| anyhow!("Database file does not contain any {}!", items))?;
+        for item in items.values_mut() {
+            if let Some(item) = item.as_object_mut() {
+                item.entry("priority").or_insert(json!("Major"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn migrate_v3_to_v4(document: &mut Map<String, Value>) -> Result<()> {
+    document.entry("users").or_insert(json!({}));
+    for (items, fields) in [
+        ("epics", &["owner"][..]),
+        ("stories", &["assignee", "reporter"]),
+    ] {
+        let items = document
+            .get_mut(items)
+            .and_then(Value::as_object_mut)
+            .ok_or_else(|| anyhow!("Database file does not contain any {}!", items))?;
+        for item in items.values_mut().filter_map(Value::as_object_mut) {
+            for field in fields {
+                item.entry(*field).or_insert(Value::Null);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn migrate_v4_to_v5(document: &mut Map<String, Value>) -> Result<()> {
+    for items in ["epics", "stories"] {
+        let items = document
+            .get_mut(items)
+            .and_then(Value::as_object_mut)
+            .ok_or_else(|| anyhow!("Database file does not contain any {}!", items))?;
+        for item in items.values_mut().filter_map(Value::as_object_mut) {
+            item.entry("labels").or_insert(json!([]));
+        }
+    }
+    Ok(())
+}
+
+fn migrate_v5_to_v6(document: &mut Map<String, Value>) -> Result<()> {
+    for items in ["epics", "stories"] {
+        let items = document
+            .get_mut(items)
+            .and_then(Value::as_object_mut)
+            .ok_or_else(|| anyhow!("Database file does not contain any {}!", items))?;
+        for item in items.values_mut().filter_map(Value::as_object_mut) {
+            for field in ["created_at", "updated_at", "due"] {
+                item.entry(field).or_insert(Value::Null);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn migrate_v6_to_v7(document: &mut Map<String, Value>) -> Result<()> {
+    for items in ["epics", "stories"] {
+        let items = document
+            .get_mut(items)
+            .and_then(Value::as_object_mut)
+            .ok_or_else(|| anyhow!("Database file does not contain any {}!", items))?;
+        for item in items.values_mut().filter_map(Value::as_object_mut) {
+            item.entry("comments").or_insert(json!([]));
+        }
+    }
+    Ok(())
+}
+
+fn migrate_v7_to_v8(document: &mut Map<String, Value>) -> Result<()> {
+    document.entry("subtasks").or_insert(json!({}));
+    let stories = document
+        .get_mut("stories")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| anyhow!("Database file does not contain any stories!"))?;
+    for story in stories.values_mut().filter_map(Value::as_object_mut) {
+        story.entry("subtasks").or_insert(json!([]));
+    }
+    Ok(())
+}
+
+fn migrate_v8_to_v9(document: &mut Map<String, Value>) -> Result<()> {
+    let stories = document
+        .get_mut("stories")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| anyhow!("Database file does not contain any stories!"))?;
+    for story in stories.values_mut().filter_map(Value::as_object_mut) {
+        story.entry("issue_type").or_insert(json!("Story"));
+        story.entry("bug").or_insert(Value::Null);
+    }
+    Ok(())
+}
+
+fn migrate_v9_to_v10(document: &mut Map<String, Value>) -> Result<()> {
+    let stories = document
+        .get_mut("stories")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| anyhow!("Database file does not contain any stories!"))?;
+    for story in stories.values_mut().filter_map(Value::as_object_mut) {
+        story.entry("links").or_insert(json!([]));
+    }
+    Ok(())
+}
+
+fn migrate_v10_to_v11(document: &mut Map<String, Value>) -> Result<()> {
+    document.entry("sprints").or_insert(json!({}));
+    document.entry("last_sprint_id").or_insert(json!(0));
+    Ok(())
+}
+
+fn migrate_v11_to_v12(document: &mut Map<String, Value>) -> Result<()> {
+    let stories = document
+        .get_mut("stories")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| anyhow!("Database file does not contain any stories!"))?;
+    for story in stories.values_mut().filter_map(Value::as_object_mut) {
+        story.entry("state").or_insert(Value::Null);
+    }
+    Ok(())
+}
+
+// upgrades the document in place and returns the version it was originally stored with
+fn migrate_document(document: &mut Value) -> Result<u32> {
+    let document = document
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("Database file does not contain a JSON object!"))?;
+
+    let original_version = match document.get("schema_version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| anyhow!("Invalid schema version: {}", version))?,
+    };
+
+    if original_version > CURRENT_SCHEMA_VERSION {
+        return Err(anyhow!(
+            "Database schema version {} is newer than the supported version {}!",
+            original_version,
+            CURRENT_SCHEMA_VERSION
+        ));
+    }
+
+    for version in original_version..CURRENT_SCHEMA_VERSION {
+        MIGRATIONS[version as usize](document)?;
+        document.insert("schema_version".to_owned(), json!(version + 1));
+    }
+
+    Ok(original_version)
+}
+
+impl JSONFileDatabase {
+    // reads and migrates the document on disk, returning the state and the schema version the
+    // file is currently stored with
+    fn read_document(&self) -> Result<(DBState, u32)> {
+        let data = fs::read_to_string(&self.file_path)?;
+        let mut document: Value = serde_json::from_str(&data)?;
+        let stored_version = migrate_document(&mut document)?;
+        let db_state: DBState = serde_json::from_value(document)?;
+        Ok((db_state, stored_version))
+    }
+}
+
+impl Database for JSONFileDatabase {
+    fn read_db(&self) -> Result<DBState> {
+        let (db_state, _) = self.read_document()?;
+        Ok(db_state)
+    }
+
+    fn write_db(&self, db_state: &DBState) -> Result<()> {
+        let (actual_revision, stored_version) = match self.read_document() {
+            Ok((current_state, stored_version)) => (current_state.revision, stored_version),
+            Err(error) => match error.downcast_ref::<std::io::Error>() {
+                Some(io_error) if io_error.kind() == ErrorKind::NotFound => {
+                    (0, CURRENT_SCHEMA_VERSION)
+                }
+                _ => return Err(error),
+            },
+        };
+        let db_state = check_revision(db_state, actual_revision)?;
+
+        // keep a copy of the file as it was before its first write in the current schema
+        if stored_version < CURRENT_SCHEMA_VERSION {
+            let backup_path = format!("{}.v{}.bak", self.file_path, stored_version);
+            if !Path::new(&backup_path).exists() {
+                fs::copy(&self.file_path, backup_path)?;
+            }
+        }
+
+        let json_db = serde_json::to_string(&db_state)?;
+
+        // write the new state next to the database and rename it over the old one, so a crash
+        // mid-write leaves either the old or the new file behind but never a truncated one
+        let tmp_path = format!("{}.tmp", self.file_path);
+        let mut tmp_file = File::create(&tmp_path)?;
+        tmp_file.write_all(json_db.as_bytes())?;
+        tmp_file.sync_all()?;
+        fs::rename(&tmp_path, &self.file_path)?;
+
+        // persist the rename itself
+        let parent = match Path::new(&self.file_path).parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
+
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<DatabaseLock> {
+        DatabaseLock::acquire(&self.file_path)
+    }
+
+    fn append_event(&self, event: &Event) -> Result<()> {
+        let mut events_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.events_path())?;
+        writeln!(events_file, "{}", serde_json::to_string(event)?)?;
+        Ok(events_file.sync_all()?)
+    }
+
+    fn read_events(&self) -> Result<Vec<Event>> {
+        let data = match fs::read_to_string(self.events_path()) {
+            Ok(data) => data,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(error) => return Err(error.into()),
+        };
+
+        data.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+}
+
+impl JSONFileDatabase {
+    // the audit log lives next to the database, e.g. data/db.events.jsonl for data/db.json
+    fn events_path(&self) -> PathBuf {
+        Path::new(&self.file_path).with_extension("events.jsonl")
+    }
+}
+
+pub struct SqliteDatabase {
+    pub file_path: String,
+}
+
+// SQLITE_MIGRATIONS[n] upgrades a database at user_version n to n + 1. Files created before
+// user_version was tracked already have the version 1 tables, hence IF NOT EXISTS.
+const SQLITE_MIGRATIONS: [&str; CURRENT_SCHEMA_VERSION as usize] = [
+    "CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS epics (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        status TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS stories (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        status TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS epic_stories (
+        epic_id INTEGER NOT NULL REFERENCES epics(id) ON DELETE CASCADE,
+        story_id INTEGER NOT NULL UNIQUE REFERENCES stories(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (epic_id, story_id)
+    );
+    CREATE TABLE IF NOT EXISTS events (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp TEXT NOT NULL,
+        actor TEXT NOT NULL,
+        item_id INTEGER NOT NULL,
+        kind TEXT NOT NULL
+    );",
+    "ALTER TABLE stories ADD COLUMN points INTEGER;",
+    "ALTER TABLE epics ADD COLUMN priority TEXT NOT NULL DEFAULT 'Major';
+    ALTER TABLE stories ADD COLUMN priority TEXT NOT NULL DEFAULT 'Major';",
+    "CREATE TABLE users (
+        username TEXT PRIMARY KEY,
+        name TEXT NOT NULL
+    );
+    ALTER TABLE epics ADD COLUMN owner TEXT;
+    ALTER TABLE stories ADD COLUMN assignee TEXT;
+    ALTER TABLE stories ADD COLUMN reporter TEXT;",
+    "CREATE TABLE labels (
+        item_id INTEGER NOT NULL,
+        label TEXT NOT NULL,
+        PRIMARY KEY (item_id, label)
+    );",
+    "ALTER TABLE epics ADD COLUMN created_at TEXT;
+    ALTER TABLE epics ADD COLUMN updated_at TEXT;
+    ALTER TABLE epics ADD COLUMN due TEXT;
+    ALTER TABLE stories ADD COLUMN created_at TEXT;
+    ALTER TABLE stories ADD COLUMN updated_at TEXT;
+    ALTER TABLE stories ADD COLUMN due TEXT;",
+    "CREATE TABLE comments (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        item_id INTEGER NOT NULL,
+        author TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        body TEXT NOT NULL
+    );",
+    "CREATE TABLE subtasks (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        status TEXT NOT NULL
+    );
+    CREATE TABLE story_subtasks (
+        story_id INTEGER NOT NULL REFERENCES stories(id) ON DELETE CASCADE,
+        subtask_id INTEGER NOT NULL UNIQUE REFERENCES subtasks(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (story_id, subtask_id)
+    );",
+    "ALTER TABLE stories ADD COLUMN issue_type TEXT NOT NULL DEFAULT 'Story';
+    ALTER TABLE stories ADD COLUMN severity TEXT;
+    ALTER TABLE stories ADD COLUMN steps_to_reproduce TEXT;",
+    "CREATE TABLE story_links (
+        source_id INTEGER NOT NULL REFERENCES stories(id) ON DELETE CASCADE,
+        target_id INTEGER NOT NULL REFERENCES stories(id) ON DELETE CASCADE,
+        kind TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (source_id, target_id, kind)
+    );",
+    "CREATE TABLE sprints (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        goal TEXT NOT NULL,
+        start_date TEXT,
+        end_date TEXT,
+        state TEXT NOT NULL
+    );
+    CREATE TABLE sprint_stories (
+        sprint_id INTEGER NOT NULL REFERENCES sprints(id) ON DELETE CASCADE,
+        story_id INTEGER NOT NULL REFERENCES stories(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (sprint_id, story_id)
+    );",
+    "ALTER TABLE stories ADD COLUMN state TEXT;",
+];
+
+impl SqliteDatabase {
+    pub fn new(file_path: String) -> Self {
+        Self { file_path }
+    }
+
+    fn connect(&self) -> Result<Connection> {
+        let mut connection = Connection::open(&self.file_path)?;
+        connection.execute_batch("PRAGMA foreign_keys = ON;")?;
+
+        if Self::schema_version(&connection)? == CURRENT_SCHEMA_VERSION {
+            return Ok(connection);
+        }
+
+        // only upgrades take the write lock. The immediate transaction keeps two processes from
+        // upgrading the same file at once, so the version is checked again once it is held.
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        for version in Self::schema_version(&transaction)?..CURRENT_SCHEMA_VERSION {
+            transaction.execute_batch(SQLITE_MIGRATIONS[version as usize])?;
+            transaction.execute_batch(&format!("PRAGMA user_version = {}", version + 1))?;
+        }
+        transaction.commit()?;
+
+        Ok(connection)
+    }
+
+    fn schema_version(connection: &Connection) -> Result<u32> {
+        let version: u32 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version > CURRENT_SCHEMA_VERSION {
+            return Err(anyhow!(
+                "Database schema version {} is newer than supported version {}!",
+                version,
+                CURRENT_SCHEMA_VERSION
+            ));
+        }
+        Ok(version)
+    }
+
+    fn read_state(connection: &Connection) -> Result<DBState> {
+        let mut db_state = DBState::new();
+
+        db_state.last_item_id = connection
+            .query_row(
+                "SELECT value FROM meta WHERE key = 'last_item_id'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?
+            .unwrap_or(0);
+        db_state.last_sprint_id = connection
+            .query_row(
+                "SELECT value FROM meta WHERE key = 'last_sprint_id'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?
+            .unwrap_or(0);
+        db_state.revision = connection
+            .query_row("SELECT value FROM meta WHERE key = 'revision'", [], |row| {
+                row.get(0)
+            })
+            .optional()?
+            .unwrap_or(0);
+
+        // enums are stored by variant name and parsed once the row has been read
+        let mut statement = connection.prepare(
+            "SELECT id, name, description, status, priority, owner, created_at, updated_at, due
+            FROM epics",
+        )?;
+        let epics = statement.query_map([], |row| {
+            let mut epic = Epic::new(row.get(1)?, row.get(2)?);
+            epic.owner = row.get(5)?;
+            Ok((
+                row.get::<_, usize>(0)?,
+                epic,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                [row.get(6)?, row.get(7)?, row.get(8)?],
+            ))
+        })?;
+        for epic in epics {
+            let (id, mut epic, status, priority, [created_at, updated_at, due]) = epic?;
+            epic.status = status_from_sql(&status)?;
+            epic.priority = priority_from_sql(&priority)?;
+            epic.created_at = timestamp_from_sql(created_at)?;
+            epic.updated_at = timestamp_from_sql(updated_at)?;
+            epic.due = date_from_sql(due)?;
+            db_state.epics.insert(id, epic);
+        }
+
+        let mut statement = connection.prepare(
+            "SELECT id, name, description, status, points, priority, assignee, reporter,
+                created_at, updated_at, due, issue_type, severity, steps_to_reproduce, state
+            FROM stories",
+        )?;
+        let stories = statement.query_map([], |row| {
+            let mut story = Story::new(row.get(1)?, row.get(2)?);
+            story.points = row.get(4)?;
+            story.assignee = row.get(6)?;
+            story.reporter = row.get(7)?;
+            story.state = row.get(14)?;
+            Ok((
+                row.get::<_, usize>(0)?,
+                story,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(5)?,
+                [row.get(8)?, row.get(9)?, row.get(10)?],
+                (
+                    row.get::<_, String>(11)?,
+                    row.get::<_, Option<String>>(12)?,
+                    row.get::<_, Option<String>>(13)?,
+                ),
+            ))
+        })?;
+        for story in stories {
+            let (
+                id,
+                mut story,
+                status,
+                priority,
+                [created_at, updated_at, due],
+                (issue_type, severity, steps_to_reproduce),
+            ) = story?;
+            story.issue_type = issue_type_from_sql(&issue_type)?;
+            // only bugs have a severity stored
+            story.bug = match severity {
+                Some(severity) => Some(BugReport {
+                    severity: severity_from_sql(&severity)?,
+                    steps_to_reproduce: steps_to_reproduce.unwrap_or_default(),
+                }),
+                None => None,
+            };
+            story.status = status_from_sql(&status)?;
+            story.priority = priority_from_sql(&priority)?;
+            story.created_at = timestamp_from_sql(created_at)?;
+            story.updated_at = timestamp_from_sql(updated_at)?;
+            story.due = date_from_sql(due)?;
+            db_state.stories.insert(id, story);
+        }
+
+        let mut statement =
+            connection.prepare("SELECT id, name, description, status FROM subtasks")?;
+        let subtasks = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, usize>(0)?,
+                Subtask::new(row.get(1)?, row.get(2)?),
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+        for subtask in subtasks {
+            let (id, mut subtask, status) = subtask?;
+            subtask.status = status_from_sql(&status)?;
+            db_state.subtasks.insert(id, subtask);
+        }
+
+        let mut statement = connection.prepare(
+            "SELECT story_id, subtask_id FROM story_subtasks ORDER BY story_id, position",
+        )?;
+        let relations = statement.query_map([], |row| {
+            Ok((row.get::<_, usize>(0)?, row.get::<_, usize>(1)?))
+        })?;
+        for relation in relations {
+            let (story_id, subtask_id) = relation?;
+            db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Sub-task {} belongs to missing story {}!",
+                        subtask_id,
+                        story_id
+                    )
+                })?
+                .subtasks
+                .push(subtask_id);
+        }
+
+        let mut statement = connection.prepare(
+            "SELECT source_id, target_id, kind FROM story_links ORDER BY source_id, position",
+        )?;
+        let links = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, usize>(0)?,
+                row.get::<_, usize>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+        for link in links {
+            let (source_id, target_id, kind) = link?;
+            db_state
+                .stories
+                .get_mut(&source_id)
+                .ok_or_else(|| anyhow!("Link starts from missing story {}!", source_id))?
+                .links
+                .push(Link {
+                    kind: link_kind_from_sql(&kind)?,
+                    target: target_id,
+                });
+        }
+
+        let mut statement = connection
+            .prepare("SELECT id, name, goal, start_date, end_date, state FROM sprints")?;
+        let sprints = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, usize>(0)?,
+                Sprint::new(row.get(1)?, row.get(2)?),
+                [row.get(3)?, row.get(4)?],
+                row.get::<_, String>(5)?,
+            ))
+        })?;
+        for sprint in sprints {
+            let (id, mut sprint, [start, end], state) = sprint?;
+            sprint.start = date_from_sql(start)?;
+            sprint.end = date_from_sql(end)?;
+            sprint.state = sprint_state_from_sql(&state)?;
+            db_state.sprints.insert(id, sprint);
+        }
+
+        let mut statement = connection.prepare(
+            "SELECT sprint_id, story_id FROM sprint_stories ORDER BY sprint_id, position",
+        )?;
+        let relations = statement.query_map([], |row| {
+            Ok((row.get::<_, usize>(0)?, row.get::<_, usize>(1)?))
+        })?;
+        for relation in relations {
+            let (sprint_id, story_id) = relation?;
+            db_state
+                .sprints
+                .get_mut(&sprint_id)
+                .ok_or_else(|| anyhow!("Story {} is in missing sprint {}!", story_id, sprint_id))?
+                .stories
+                .push(story_id);
+        }
+
+        let mut statement = connection.prepare("SELECT username, name FROM users")?;
+        let users = statement.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, User { name: row.get(1)? }))
+        })?;
+        for user in users {
+            let (username, user) = user?;
+            db_state.users.insert(username, user);
+        }
+
+        let mut statement = connection.prepare("SELECT item_id, label FROM labels")?;
+        let labels = statement.query_map([], |row| {
+            Ok((row.get::<_, usize>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for label in labels {
+            let (item_id, label) = label?;
+            item_labels(&mut db_state, item_id)
+                .map_err(|_| anyhow!("Label '{}' belongs to missing item {}!", label, item_id))?
+                .insert(label);
+        }
+
+        let mut statement = connection
+            .prepare("SELECT item_id, author, timestamp, body FROM comments ORDER BY id")?;
+        let comments = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, usize>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+        for comment in comments {
+            let (item_id, author, timestamp, body) = comment?;
+            item_comments(&mut db_state, item_id)
+                .map_err(|_| anyhow!("Comment belongs to missing item {}!", item_id))?
+                .push(Comment {
+                    author,
+                    timestamp: DateTime::parse_from_rfc3339(&timestamp)?.with_timezone(&Utc),
+                    body,
+                });
+        }
+
+        let mut statement = connection
+            .prepare("SELECT epic_id, story_id FROM epic_stories ORDER BY epic_id, position")?;
+        let relations = statement.query_map([], |row| {
+            Ok((row.get::<_, usize>(0)?, row.get::<_, usize>(1)?))
+        })?;
+        for relation in relations {
+            let (epic_id, story_id) = relation?;
+            db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or_else(|| anyhow!("Story {} belongs to missing epic {}!", story_id, epic_id))?
+                .stories
+                .push(story_id);
+        }
+
+        Ok(db_state)
+    }
+}
+
+impl Database for SqliteDatabase {
+    fn read_db(&self) -> Result<DBState> {
+        let mut connection = self.connect()?;
+        // a single read transaction, so a write from another process can't land between the
+        // queries of read_state
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Deferred)?;
+        let db_state = SqliteDatabase::read_state(&transaction)?;
+        transaction.commit()?;
+        Ok(db_state)
+    }
+
+    fn write_db(&self, db_state: &DBState) -> Result<()> {
+        let mut connection = self.connect()?;
+        let transaction = connection.transaction()?;
+        let current_state = SqliteDatabase::read_state(&transaction)?;
+        let db_state = &check_revision(db_state, current_state.revision)?;
+
+        // only touch the rows that actually changed since the last write
+        for id in current_state.epics.keys() {
+            if !db_state.epics.contains_key(id) {
+                transaction.execute("DELETE FROM epics WHERE id = ?1", [id])?;
+                transaction.execute("DELETE FROM labels WHERE item_id = ?1", [id])?;
+                transaction.execute("DELETE FROM comments WHERE item_id = ?1", [id])?;
+            }
+        }
+        for id in current_state.stories.keys() {
+            if !db_state.stories.contains_key(id) {
+                transaction.execute("DELETE FROM stories WHERE id = ?1", [id])?;
+                transaction.execute("DELETE FROM labels WHERE item_id = ?1", [id])?;
+                transaction.execute("DELETE FROM comments WHERE item_id = ?1", [id])?;
+            }
+        }
+
+        for id in current_state.subtasks.keys() {
+            if !db_state.subtasks.contains_key(id) {
+                transaction.execute("DELETE FROM subtasks WHERE id = ?1", [id])?;
+            }
+        }
+        for (id, subtask) in &db_state.subtasks {
+            if current_state.subtasks.get(id) == Some(subtask) {
+                continue;
+            }
+            transaction.execute(
+                "INSERT INTO subtasks (id, name, description, status) VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    description = excluded.description,
+                    status = excluded.status",
+                params![
+                    id,
+                    subtask.name,
+                    subtask.description,
+                    status_to_sql(&subtask.status)
+                ],
+            )?;
+        }
+
+        for username in current_state.users.keys() {
+            if !db_state.users.contains_key(username) {
+                transaction.execute("DELETE FROM users WHERE username = ?1", [username])?;
+            }
+        }
+        for (username, user) in &db_state.users {
+            if current_state.users.get(username) == Some(user) {
+                continue;
+            }
+            transaction.execute(
+                "INSERT INTO users (username, name) VALUES (?1, ?2)
+                ON CONFLICT(username) DO UPDATE SET name = excluded.name",
+                params![username, user.name],
+            )?;
+        }
+
+        for (id, story) in &db_state.stories {
+            if current_state.stories.get(id) == Some(story) {
+                continue;
+            }
+            transaction.execute(
+                "INSERT INTO stories (id, name, description, status, points, priority, assignee,
+                    reporter, created_at, updated_at, due, issue_type, severity,
+                    steps_to_reproduce, state)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+                ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    description = excluded.description,
+                    status = excluded.status,
+                    points = excluded.points,
+                    priority = excluded.priority,
+                    assignee = excluded.assignee,
+                    reporter = excluded.reporter,
+                    created_at = excluded.created_at,
+                    updated_at = excluded.updated_at,
+                    due = excluded.due,
+                    issue_type = excluded.issue_type,
+                    severity = excluded.severity,
+                    steps_to_reproduce = excluded.steps_to_reproduce,
+                    state = excluded.state",
+                params![
+                    id,
+                    story.name,
+                    story.description,
+                    status_to_sql(&story.status),
+                    story.points,
+                    priority_to_sql(&story.priority),
+                    story.assignee,
+                    story.reporter,
+                    story.created_at.map(|timestamp| timestamp.to_rfc3339()),
+                    story.updated_at.map(|timestamp| timestamp.to_rfc3339()),
+                    story.due.map(|due| due.to_string()),
+                    issue_type_to_sql(&story.issue_type),
+                    story.bug.as_ref().map(|bug| severity_to_sql(&bug.severity)),
+                    story
+                        .bug
+                        .as_ref()
+                        .map(|bug| bug.steps_to_reproduce.as_str()),
+                    story.state
+                ],
+            )?;
+            write_labels(&transaction, *id, &story.labels)?;
+            write_comments(&transaction, *id, &story.comments)?;
+            transaction.execute("DELETE FROM story_subtasks WHERE story_id = ?1", [id])?;
+            transaction.execute("DELETE FROM story_links WHERE source_id = ?1", [id])?;
+        }
+
+        for (id, epic) in &db_state.epics {
+            if current_state.epics.get(id) == Some(epic) {
+                continue;
+            }
+            transaction.execute(
+                "INSERT INTO epics (id, name, description, status, priority, owner, created_at,
+                    updated_at, due)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    description = excluded.description,
+                    status = excluded.status,
+                    priority = excluded.priority,
+                    owner = excluded.owner,
+                    created_at = excluded.created_at,
+                    updated_at = excluded.updated_at,
+                    due = excluded.due",
+                params![
+                    id,
+                    epic.name,
+                    epic.description,
+                    status_to_sql(&epic.status),
+                    priority_to_sql(&epic.priority),
+                    epic.owner,
+                    epic.created_at.map(|timestamp| timestamp.to_rfc3339()),
+                    epic.updated_at.map(|timestamp| timestamp.to_rfc3339()),
+                    epic.due.map(|due| due.to_string())
+                ],
+            )?;
+            write_labels(&transaction, *id, &epic.labels)?;
+            write_comments(&transaction, *id, &epic.comments)?;
+            transaction.execute("DELETE FROM epic_stories WHERE epic_id = ?1", [id])?;
+        }
+
+        // relations are re-inserted once every changed epic has released its old rows, since a
+        // story can only ever belong to a single epic
+        for (id, epic) in &db_state.epics {
+            if current_state.epics.get(id) == Some(epic) {
+                continue;
+            }
+            for (position, story_id) in epic.stories.iter().enumerate() {
+                transaction.execute(
+                    "INSERT INTO epic_stories (epic_id, story_id, position) VALUES (?1, ?2, ?3)",
+                    params![id, story_id, position],
+                )?;
+            }
+        }
+
+        for (id, story) in &db_state.stories {
+            if current_state.stories.get(id) == Some(story) {
+                continue;
+            }
+            for (position, subtask_id) in story.subtasks.iter().enumerate() {
+                transaction.execute(
+                    "INSERT INTO story_subtasks (story_id, subtask_id, position)
+                    VALUES (?1, ?2, ?3)",
+                    params![id, subtask_id, position],
+                )?;
+            }
+            // links may point at stories inserted later in the loop above, so they come last
+            for (position, link) in story.links.iter().enumerate() {
+                transaction.execute(
+                    "INSERT INTO story_links (source_id, target_id, kind, position)
+                    VALUES (?1, ?2, ?3, ?4)",
+                    params![id, link.target, link_kind_to_sql(&link.kind), position],
+                )?;
+            }
+        }
+
+        // sprints refer to stories, so they are written once every story is in place
+        for id in current_state.sprints.keys() {
+            if !db_state.sprints.contains_key(id) {
+                transaction.execute("DELETE FROM sprints WHERE id = ?1", [id])?;
+            }
+        }
+        for (id, sprint) in &db_state.sprints {
+            if current_state.sprints.get(id) == Some(sprint) {
+                continue;
+            }
+            transaction.execute(
+                "INSERT INTO sprints (id, name, goal, start_date, end_date, state)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    goal = excluded.goal,
+                    start_date = excluded.start_date,
+                    end_date = excluded.end_date,
+                    state = excluded.state",
+                params![
+                    id,
+                    sprint.name,
+                    sprint.goal,
+                    sprint.start.map(|start| start.to_string()),
+                    sprint.end.map(|end| end.to_string()),
+                    sprint_state_to_sql(&sprint.state)
+                ],
+            )?;
+            transaction.execute("DELETE FROM sprint_stories WHERE sprint_id = ?1", [id])?;
+            for (position, story_id) in sprint.stories.iter().enumerate() {
+                transaction.execute(
+                    "INSERT INTO sprint_stories (sprint_id, story_id, position)
+                    VALUES (?1, ?2, ?3)",
+                    params![id, story_id, position],
+                )?;
+            }
+        }
+
+        transaction.execute(
+            "INSERT INTO meta (key, value) VALUES ('last_item_id', ?1)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            [db_state.last_item_id],
+        )?;
+        transaction.execute(
+            "INSERT INTO meta (key, value) VALUES ('last_sprint_id', ?1)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            [db_state.last_sprint_id],
+        )?;
+        transaction.execute(
+            "INSERT INTO meta (key, value) VALUES ('revision', ?1)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            [db_state.revision],
+        )?;
+
+        Ok(transaction.commit()?)
+    }
+
+    fn lock(&self) -> Result<DatabaseLock> {
+        DatabaseLock::acquire(&self.file_path)
+    }
+
+    fn append_event(&self, event: &Event) -> Result<()> {
+        let connection = self.connect()?;
+        connection.execute(
+            "INSERT INTO events (timestamp, actor, item_id, kind) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                event.timestamp.to_rfc3339(),
+                event.actor,
+                event.item_id,
+                serde_json::to_string(&event.kind)?
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn read_events(&self) -> Result<Vec<Event>> {
+        let connection = self.connect()?;
+        let mut statement =
+            connection.prepare("SELECT timestamp, actor, item_id, kind FROM events ORDER BY id")?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, usize>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+
+        let mut events = vec![];
+        for row in rows {
+            let (timestamp, actor, item_id, kind) = row?;
+            events.push(Event {
+                timestamp: DateTime::parse_from_rfc3339(&timestamp)?.with_timezone(&Utc),
+                actor,
+                item_id,
+                kind: serde_json::from_str(&kind)?,
+            });
+        }
+        Ok(events)
+    }
+}
+
+fn status_to_sql(status: &Status) -> &'static str {
+    match status {
+        Status::Open => "Open",
+        Status::InProgress => "InProgress",
+        Status::Resolved => "Resolved",
+        Status::Closed => "Closed",
+    }
+}
+
+fn status_from_sql(status: &str) -> Result<Status> {
+    match status {
+        "Open" => Ok(Status::Open),
+        "InProgress" => Ok(Status::InProgress),
+        "Resolved" => Ok(Status::Resolved),
+        "Closed" => Ok(Status::Closed),
+        other => Err(anyhow!("Unknown status '{}' in database!", other)),
+    }
+}
+
+fn priority_to_sql(priority: &Priority) -> &'static str {
+    match priority {
+        Priority::Blocker => "Blocker",
+        Priority::Critical => "Critical",
+        Priority::Major => "Major",
+        Priority::Minor => "Minor",
+        Priority::Trivial => "Trivial",
+    }
+}
+
+fn priority_from_sql(priority: &str) -> Result<Priority> {
+    match priority {
+        "Blocker" => Ok(Priority::Blocker),
+        "Critical" => Ok(Priority::Critical),
+        "Major" => Ok(Priority::Major),
+        "Minor" => Ok(Priority::Minor),
+        "Trivial" => Ok(Priority::Trivial),
+        other => Err(anyhow!("Unknown priority '{}' in database!", other)),
+    }
+}
+
+fn issue_type_to_sql(issue_type: &IssueType) -> &'static str {
+    match issue_type {
+        IssueType::Bug => "Bug",
+        IssueType::Task => "Task",
+        IssueType::Story => "Story",
+        IssueType::Spike => "Spike",
+    }
+}
+
+fn issue_type_from_sql(issue_type: &str) -> Result<IssueType> {
+    match issue_type {
+        "Bug" => Ok(IssueType::Bug),
+        "Task" => Ok(IssueType::Task),
+        "Story" => Ok(IssueType::Story),
+        "Spike" => Ok(IssueType::Spike),
+        other => Err(anyhow!("Unknown issue type '{}' in database!", other)),
+    }
+}
+
+fn sprint_state_to_sql(state: &SprintState) -> &'static str {
+    match state {
+        SprintState::Planned => "Planned",
+        SprintState::Active => "Active",
+        SprintState::Completed => "Completed",
+    }
+}
+
+fn sprint_state_from_sql(state: &str) -> Result<SprintState> {
+    match state {
+        "Planned" => Ok(SprintState::Planned),
+        "Active" => Ok(SprintState::Active),
+        "Completed" => Ok(SprintState::Completed),
+        other => Err(anyhow!("Unknown sprint state '{}' in database!", other)),
+    }
+}
+
+fn link_kind_to_sql(kind: &LinkKind) -> &'static str {
+    match kind {
+        LinkKind::Blocks => "Blocks",
+        LinkKind::RelatesTo => "RelatesTo",
+    }
+}
+
+fn link_kind_from_sql(kind: &str) -> Result<LinkKind> {
+    match kind {
+        "Blocks" => Ok(LinkKind::Blocks),
+        "RelatesTo" => Ok(LinkKind::RelatesTo),
+        other => Err(anyhow!("Unknown link kind '{}' in database!", other)),
+    }
+}
+
+fn severity_to_sql(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "Critical",
+        Severity::Major => "Major",
+        Severity::Minor => "Minor",
+        Severity::Cosmetic => "Cosmetic",
+    }
+}
+
+fn severity_from_sql(severity: &str) -> Result<Severity> {
+    match severity {
+        "Critical" => Ok(Severity::Critical),
+        "Major" => Ok(Severity::Major),
+        "Minor" => Ok(Severity::Minor),
+        "Cosmetic" => Ok(Severity::Cosmetic),
+        other => Err(anyhow!("Unknown severity '{}' in database!", other)),
+    }
+}
+
+// timestamps are stored as rfc3339 text and due dates as YYYY-MM-DD
+fn timestamp_from_sql(timestamp: Option<String>) -> Result<Option<DateTime<Utc>>> {
+    match timestamp {
+        Some(timestamp) => Ok(Some(
+            DateTime::parse_from_rfc3339(&timestamp)?.with_timezone(&Utc),
+        )),
+        None => Ok(None),
+    }
+}
+
+fn date_from_sql(date: Option<String>) -> Result<Option<NaiveDate>> {
+    match date {
+        Some(date) => Ok(Some(date.parse()?)),
+        None => Ok(None),
+    }
+}
+
+// replaces the stored labels of an epic or story
+fn write_labels(connection: &Connection, item_id: usize, labels: &BTreeSet<String>) -> Result<()> {
+    connection.execute("DELETE FROM labels WHERE item_id = ?1", [item_id])?;
+    for label in labels {
+        connection.execute(
+            "INSERT INTO labels (item_id, label) VALUES (?1, ?2)",
+            params![item_id, label],
+        )?;
+    }
+    Ok(())
+}
+
+// replaces the stored comment thread of an epic or story, keeping its order
+fn write_comments(connection: &Connection, item_id: usize, comments: &[Comment]) -> Result<()> {
+    connection.execute("DELETE FROM comments WHERE item_id = ?1", [item_id])?;
+    for comment in comments {
+        connection.execute(
+            "INSERT INTO comments (item_id, author, timestamp, body) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                item_id,
+                comment.author,
+                comment.timestamp.to_rfc3339(),
+                comment.body
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+pub mod test_utils {
+    use std::{cell::RefCell, collections::HashMap};
+
+    use super::*;
+
+    pub struct MockDB {
+        pub last_written_state: RefCell<DBState>,
+        pub events: RefCell<Vec<Event>>,
+    }
+
+    impl MockDB {
+        pub fn new() -> Self {
+            Self {
+                last_written_state: RefCell::new(DBState {
+                    schema_version: CURRENT_SCHEMA_VERSION,
+                    last_item_id: 0,
+                    last_sprint_id: 0,
+                    revision: 0,
+                    epics: HashMap::new(),
+                    stories: HashMap::new(),
+                    users: HashMap::new(),
+                    subtasks: HashMap::new(),
+                    sprints: HashMap::new(),
+                }),
+                events: RefCell::new(vec![]),
+            }
+        }
+    }
+
+    impl Database for MockDB {
+        fn read_db(&self) -> Result<DBState> {
+            // TODO: fix this error by deriving the appropriate traits for Story
+            let state = self.last_written_state.borrow().clone();
+            Ok(state)
+        }
+
+        fn write_db(&self, db_state: &DBState) -> Result<()> {
+            let latest_state = &self.last_written_state;
+            let db_state = check_revision(db_state, latest_state.borrow().revision)?;
+            // TODO: fix this error by deriving the appropriate traits for DBState
+            *latest_state.borrow_mut() = db_state;
+            Ok(())
+        }
+
+        fn append_event(&self, event: &Event) -> Result<()> {
+            self.events.borrow_mut().push(event.clone());
+            Ok(())
+        }
+
+        fn read_events(&self) -> Result<Vec<Event>> {
+            Ok(self.events.borrow().clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_utils::MockDB;
+    use super::*;
+    use crate::config::{Workflow, WorkflowState};
+    use crate::models::EventKind;
+
+    // every JiraDatabase test below is run once per storage backend
+    macro_rules! jira_database_tests {
+        ($($test:ident),* $(,)?) => {
+            jira_database_tests!(@backend mock, mock_database; $($test),*);
+            jira_database_tests!(@backend json_file, json_file_database; $($test),*);
+            jira_database_tests!(@backend sqlite, sqlite_database; $($test),*);
+        };
+        (@backend $backend:ident, $new_db:ident; $($test:ident),*) => {
+            mod $backend {
+                $(
+                    #[test]
+                    fn $test() {
+                        let (db, _tmp_dir) = super::$new_db();
+                        super::$test(db);
+                    }
+                )*
+            }
+        };
+    }
+
+    jira_database_tests!(
+        create_epic_should_work,
+        create_story_should_error_if_invalid_epic_id,
+        create_story_should_work,
+        delete_epic_should_error_if_invalid_epic_id,
+        delete_epic_should_work,
+        delete_story_should_error_if_invalid_epic_id,
+        delete_story_should_error_if_story_not_found_in_epic,
+        delete_story_should_work,
+        close_epic_should_work,
+        update_story_status_should_error_if_invalid_story_id,
+        update_story_status_should_error_if_epic_is_closed,
+        update_story_status_should_work,
+        modify_should_fail_with_conflict_if_changed_since_read,
+        deleted_ids_should_never_be_reused,
+        restore_should_work,
+        mutations_should_be_recorded_in_the_audit_log,
+        item_rows_should_work,
+        create_story_should_error_if_points_are_not_on_the_scale,
+        update_story_points_should_work,
+        story_points_should_roll_up_to_the_epic,
+        rows_should_sort_by_priority,
+        update_epic_should_error_if_invalid_epic_id,
+        update_epic_should_work,
+        update_story_should_error_if_invalid_story_id,
+        update_story_should_work,
+        add_user_should_error_if_user_is_invalid_or_exists,
+        remove_user_should_unassign_their_work,
+        assign_should_error_if_user_is_unknown,
+        create_story_should_default_reporter_to_current_user,
+        work_rows_should_list_items_assigned_to_user,
+        add_label_should_work,
+        remove_label_should_error_if_label_is_missing,
+        rows_should_filter_by_label,
+        mutations_should_record_timestamps,
+        update_due_date_should_work,
+        add_comment_should_work,
+        create_subtask_should_work,
+        delete_subtask_should_work,
+        delete_story_should_remove_subtasks,
+        update_subtask_status_should_not_roll_up_by_default,
+        update_subtask_status_should_roll_up_when_configured,
+        update_subtask_status_should_error_if_epic_is_closed,
+        subtasks_should_not_change_if_epic_is_closed,
+        subtask_rollup_should_follow_the_workflow,
+        subtask_rollup_should_not_resolve_blocked_stories,
+        update_story_type_should_work,
+        update_bug_report_should_only_accept_bugs,
+        rows_should_filter_by_type,
+        move_story_should_work,
+        move_story_should_error_for_invalid_moves,
+        link_stories_should_work,
+        link_stories_should_reject_cycles,
+        unlink_stories_should_work,
+        delete_story_should_remove_links_to_it,
+        update_story_status_should_refuse_to_resolve_blocked_stories,
+        create_sprint_should_work,
+        add_story_to_sprint_should_work,
+        add_story_to_sprint_should_reject_stories_in_another_open_sprint,
+        start_sprint_should_only_allow_one_active_sprint,
+        complete_sprint_should_move_unfinished_stories_to_the_backlog,
+        complete_sprint_should_move_unfinished_stories_to_the_next_sprint,
+        delete_story_should_remove_it_from_sprints,
+        board_rows_should_show_one_or_every_epic,
+        update_story_state_should_follow_the_workflow,
+        update_story_status_should_use_the_first_state_of_the_status,
+        epic_status_should_roll_up_from_its_stories,
+        epic_rollup_should_leave_closed_epics_alone,
+        epic_rollup_should_close_epics_when_configured,
+        reopen_epic_should_reset_to_the_rolled_up_status,
+    );
+
+    fn mock_database() -> (JiraDatabase, Option<tempfile::TempDir>) {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        (db, None)
+    }
+
+    fn json_file_database() -> (JiraDatabase, Option<tempfile::TempDir>) {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let file_path = tmp_dir.path().join("db.json");
+        fs::write(
+            &file_path,
+            r#"{ "last_item_id": 0, "epics": {}, "stories": {} }"#,
+        )
+        .unwrap();
+
+        let db = JiraDatabase::new(file_path.to_str().unwrap().to_owned());
+        (db, Some(tmp_dir))
+    }
+
+    fn sqlite_database() -> (JiraDatabase, Option<tempfile::TempDir>) {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let file_path = tmp_dir.path().join("db.sqlite");
+
+        let db = JiraDatabase::new(file_path.to_str().unwrap().to_owned());
+        (db, Some(tmp_dir))
+    }
+
+    fn create_epic_should_work(db: JiraDatabase) {
+        let epic = Epic::new("".to_owned(), "".to_owned());
+
+        // TODO: fix this error by deriving the appropriate traits for Epic
+        let result = db.create_epic(epic.clone());
+        println!("{:?}", result.as_ref().unwrap());
+
+        assert_eq!(result.is_ok(), true);
+
+        let id = result.unwrap();
+        let db_state = db.read_db().unwrap();
+
+        let expected_id = 1;
+
+        assert_eq!(id, expected_id);
+        assert_eq!(db_state.last_item_id, expected_id);
+        // creation times are recorded by the database
+        let stored_epic = &db_state.epics[&id];
+        assert!(stored_epic.created_at.is_some());
+        assert_eq!(
+            stored_epic,
+            &Epic {
+                created_at: stored_epic.created_at,
+                updated_at: stored_epic.created_at,
+                ..epic
+            }
+        );
+    }
+
+    fn create_story_should_error_if_invalid_epic_id(db: JiraDatabase) {
+        let story = Story::new("".to_owned(), "".to_owned());
+
+        let non_existent_epic_id = 999;
+
+        let result = db.create_story(story, non_existent_epic_id);
+        assert_eq!(result.is_err(), true);
+    }
+
+    fn create_story_should_work(db: JiraDatabase) {
+        let epic = Epic::new("".to_owned(), "".to_owned());
+        let mut story = Story::new("".to_owned(), "".to_owned());
+        story.reporter = Some(db.config.current_user());
+
+        let result = db.create_epic(epic);
+        assert_eq!(result.is_ok(), true);
+
+        let epic_id = result.unwrap();
+
+        // TODO: fix this error by deriving the appropriate traits for Story
+        let result = db.create_story(story.clone(), epic_id);
+        assert_eq!(result.is_ok(), true);
+
+        let id = result.unwrap();
+        let db_state = db.read_db().unwrap();
+
+        let expected_id = 2;
+
+        assert_eq!(id, expected_id);
+        assert_eq!(db_state.last_item_id, expected_id);
+        assert_eq!(
+            db_state.epics.get(&epic_id).unwrap().stories.contains(&id),
+            true
+        );
+        // creation times are recorded by the database
+        let stored_story = &db_state.stories[&id];
+        assert!(stored_story.created_at.is_some());
+        assert_eq!(
+            stored_story,
+            &Story {
+                created_at: stored_story.created_at,
+                updated_at: stored_story.created_at,
+                ..story
+            }
+        );
+    }
+
+    fn delete_epic_should_error_if_invalid_epic_id(db: JiraDatabase) {
+        let non_existent_epic_id = 999;
+
+        let result = db.delete_epic(non_existent_epic_id);
+        assert_eq!(result.is_err(), true);
+    }
+
+    fn delete_epic_should_work(db: JiraDatabase) {
+        let epic = Epic::new("".to_owned(), "".to_owned());
+        let story = Story::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic);
+        assert_eq!(result.is_ok(), true);
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+        assert_eq!(result.is_ok(), true);
+
+        let story_id = result.unwrap();
+
+        let result = db.delete_epic(epic_id);
+        assert_eq!(result.is_ok(), true);
+
+        let db_state = db.read_db().unwrap();
+
+        let expected_last_id = 2;
+
+        assert_eq!(db_state.last_item_id, expected_last_id);
+        assert_eq!(db_state.epics.get(&epic_id), None);
+        assert_eq!(db_state.stories.get(&story_id), None);
+    }
+
+    fn delete_story_should_error_if_invalid_epic_id(db: JiraDatabase) {
+        let epic = Epic::new("".to_owned(), "".to_owned());
+        let story = Story::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic);
+        assert_eq!(result.is_ok(), true);
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+        assert_eq!(result.is_ok(), true);
+
+        let story_id = result.unwrap();
+
+        let non_existent_epic_id = 999;
+
+        let result = db.delete_story(non_existent_epic_id, story_id);
+        assert_eq!(result.is_err(), true);
+    }
+
+    fn delete_story_should_error_if_story_not_found_in_epic(db: JiraDatabase) {
+        let epic = Epic::new("".to_owned(), "".to_owned());
+        let story = Story::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic);
+        assert_eq!(result.is_ok(), true);
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+        assert_eq!(result.is_ok(), true);
+
+        let non_existent_story_id = 999;
+
+        let result = db.delete_story(epic_id, non_existent_story_id);
+        assert_eq!(result.is_err(), true);
+    }
+
+    fn delete_story_should_work(db: JiraDatabase) {
+        let epic = Epic::new("".to_owned(), "".to_owned());
+        let story = Story::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic);
+        assert_eq!(result.is_ok(), true);
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+        assert_eq!(result.is_ok(), true);
+
+        let story_id = result.unwrap();
+
+        let result = db.delete_story(epic_id, story_id);
+        assert_eq!(result.is_ok(), true);
+
+        let db_state = db.read_db().unwrap();
+
+        let expected_last_id = 2;
+
+        assert_eq!(db_state.last_item_id, expected_last_id);
+        assert_eq!(
+            db_state
+                .epics
+                .get(&epic_id)
+                .unwrap()
+                .stories
+                .contains(&story_id),
+            false
+        );
+        assert_eq!(db_state.stories.get(&story_id), None);
+    }
+
+    fn close_epic_should_work(db: JiraDatabase) {
+        let epic = Epic::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic);
+
+        assert_eq!(result.is_ok(), true);
+
+        let epic_id = result.unwrap();
+
+        let result = db.close_epic(epic_id);
+
+        assert_eq!(result.is_ok(), true);
+
+        let db_state = db.read_db().unwrap();
+
+        assert_eq!(db_state.epics.get(&epic_id).unwrap().status, Status::Closed);
+    }
+
+    fn update_story_status_should_error_if_invalid_story_id(db: JiraDatabase) {
+        let non_existent_story_id = 999;
+
+        let result = db.update_story_status(non_existent_story_id, Status::Closed);
+        assert_eq!(result.is_err(), true);
+    }
+
+    fn update_story_status_should_error_if_epic_is_closed(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.close_epic(epic_id).unwrap();
+
+        let result = db.update_story_status(story_id, Status::Resolved);
+        assert!(result.is_err());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories[&story_id].status, Status::Open);
+    }
+
+    fn update_story_status_should_work(db: JiraDatabase) {
+        let epic = Epic::new("".to_owned(), "".to_owned());
+        let story = Story::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic);
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+
+        let story_id = result.unwrap();
+
+        let result = db.update_story_status(story_id, Status::Closed);
+
+        assert_eq!(result.is_ok(), true);
+
+        let db_state = db.read_db().unwrap();
+
+        assert_eq!(
+            db_state.stories.get(&story_id).unwrap().status,
+            Status::Closed
+        );
+    }
+
+    fn mutations_should_be_recorded_in_the_audit_log(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.update_story_status(story_id, Status::InProgress)
+            .unwrap();
+        db.close_epic(epic_id).unwrap();
+        db.reopen_epic(epic_id).unwrap();
+        db.delete_epic(epic_id).unwrap();
+
+        let story_events = db
+            .read_events(story_id)
+            .unwrap()
+            .into_iter()
+            .map(|event| event.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            story_events,
+            vec![
+                EventKind::Created,
+                EventKind::StatusChanged {
+                    from: Status::Open,
+                    to: Status::InProgress
+                },
+                EventKind::Deleted,
+            ]
+        );
+
+        let epic_events = db
+            .read_events(epic_id)
+            .unwrap()
+            .into_iter()
+            .map(|event| event.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            epic_events,
+            vec![
+                EventKind::Created,
+                EventKind::StatusChanged {
+                    from: Status::Open,
+                    to: Status::InProgress
+                },
+                EventKind::Closed,
+                EventKind::Reopened,
+                EventKind::Deleted,
+            ]
+        );
+
+        let events = db.read_events(epic_id).unwrap();
+        assert_eq!(events[0].actor, db.config.current_user());
+        assert!(events
+            .windows(2)
+            .all(|pair| pair[0].timestamp <= pair[1].timestamp));
+    }
+
+    fn item_rows_should_work(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("epic".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("story".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.create_epic(Epic::new("other".to_owned(), "".to_owned()))
+            .unwrap();
+
+        let rows = db.epic_rows(SortOrder::Id).unwrap();
+        assert_eq!(
+            rows.iter().map(|row| row.key.as_str()).collect::<Vec<_>>(),
+            vec!["PROJ-1", "PROJ-3"]
+        );
+
+        let rows = db.story_rows(epic_id, SortOrder::Id).unwrap();
+        assert_eq!(
+            rows,
+            vec![ItemRow {
+                key: "PROJ-2".to_owned(),
+                id: story_id,
+                name: "story".to_owned(),
+                description: "".to_owned(),
+                status: Status::Open,
+                points: None,
+                priority: Priority::Major,
+                assignee: None,
+                labels: "".to_owned(),
+                due: None,
+                issue_type: Some(IssueType::Story),
+            }]
+        );
+
+        assert!(db.story_rows(999, SortOrder::Id).is_err());
+    }
+
+    fn create_story_should_error_if_points_are_not_on_the_scale(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let mut story = Story::new("".to_owned(), "".to_owned());
+        story.points = Some(4);
+
+        let result = db.create_story(story, epic_id);
+        assert!(result.is_err());
+        assert!(db.read_db().unwrap().stories.is_empty());
+    }
+
+    fn update_story_points_should_work(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        assert!(db.update_story_points(story_id, Some(4)).is_err());
+        assert!(db.update_story_points(999, Some(5)).is_err());
+
+        db.update_story_points(story_id, Some(5)).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].points, Some(5));
+
+        db.update_story_points(story_id, None).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].points, None);
+    }
+
+    fn story_points_should_roll_up_to_the_epic(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        assert_eq!(db.epic_rows(SortOrder::Id).unwrap()[0].points, None);
+
+        for (points, status) in [
+            (Some(5), Status::Resolved),
+            (Some(3), Status::InProgress),
+            (None, Status::Closed),
+        ] {
+            let mut story = Story::new("".to_owned(), "".to_owned());
+            story.points = points;
+            let story_id = db.create_story(story, epic_id).unwrap();
+            db.update_story_status(story_id, status).unwrap();
+        }
+
+        assert_eq!(db.epic_rows(SortOrder::Id).unwrap()[0].points, Some(8));
+        assert_eq!(db.read_db().unwrap().epic_points(epic_id), (8, 5));
+    }
+
+    fn rows_should_sort_by_priority(db: JiraDatabase) {
+        let mut ids = vec![];
+        for priority in [Priority::Minor, Priority::Blocker, Priority::Minor] {
+            let mut epic = Epic::new("".to_owned(), "".to_owned());
+            epic.priority = priority;
+            ids.push(db.create_epic(epic).unwrap());
+        }
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), ids[0])
+            .unwrap();
+        db.update_story_priority(story_id, Priority::Trivial)
+            .unwrap();
+        db.update_epic_priority(ids[2], Priority::Critical).unwrap();
+
+        let rows = db.epic_rows(SortOrder::Priority).unwrap();
+        assert_eq!(
+            rows.iter().map(|row| row.id).collect::<Vec<_>>(),
+            vec![ids[1], ids[2], ids[0]]
+        );
+
+        let rows = db.story_rows(ids[0], SortOrder::Priority).unwrap();
+        assert_eq!(rows[0].priority, Priority::Trivial);
+
+        assert!(db.update_epic_priority(999, Priority::Major).is_err());
+        assert!(db.update_story_priority(999, Priority::Major).is_err());
+    }
+
+    fn add_user_should_error_if_user_is_invalid_or_exists(db: JiraDatabase) {
+        assert!(db.add_user("".to_owned(), "".to_owned()).is_err());
+        assert!(db.add_user("al ice".to_owned(), "".to_owned()).is_err());
+        assert!(db.add_user("alice".to_owned(), "Alice".to_owned()).is_ok());
+        assert!(db.add_user("alice".to_owned(), "Alice".to_owned()).is_err());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.users["alice"].name, "Alice");
+    }
+
+    fn remove_user_should_unassign_their_work(db: JiraDatabase) {
+        db.add_user("alice".to_owned(), "Alice".to_owned()).unwrap();
+        let mut epic = Epic::new("".to_owned(), "".to_owned());
+        epic.owner = Some("alice".to_owned());
+        let epic_id = db.create_epic(epic).unwrap();
+        let mut story = Story::new("".to_owned(), "".to_owned());
+        story.assignee = Some("alice".to_owned());
+        story.reporter = Some("alice".to_owned());
+        let story_id = db.create_story(story, epic_id).unwrap();
+
+        assert!(db.remove_user("alice").is_ok());
+        assert!(db.remove_user("alice").is_err());
+
+        let db_state = db.read_db().unwrap();
+        assert!(db_state.users.is_empty());
+        assert_eq!(db_state.epics[&epic_id].owner, None);
+        assert_eq!(db_state.stories[&story_id].assignee, None);
+        assert_eq!(
+            db_state.stories[&story_id].reporter,
+            Some("alice".to_owned())
+        );
+    }
+
+    fn assign_should_error_if_user_is_unknown(db: JiraDatabase) {
+        db.add_user("alice".to_owned(), "Alice".to_owned()).unwrap();
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        assert!(db.assign_epic(epic_id, Some("bob".to_owned())).is_err());
+        assert!(db.assign_story(story_id, Some("bob".to_owned())).is_err());
+        assert!(db.assign_epic(999, Some("alice".to_owned())).is_err());
+        assert!(db.assign_story(999, Some("alice".to_owned())).is_err());
+
+        assert!(db.assign_epic(epic_id, Some("alice".to_owned())).is_ok());
+        assert!(db.assign_story(story_id, Some("alice".to_owned())).is_ok());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics[&epic_id].owner, Some("alice".to_owned()));
+        assert_eq!(
+            db_state.stories[&story_id].assignee,
+            Some("alice".to_owned())
+        );
+
+        assert!(db.assign_story(story_id, None).is_ok());
+        assert_eq!(db.read_db().unwrap().stories[&story_id].assignee, None);
+
+        let mut story = Story::new("".to_owned(), "".to_owned());
+        story.assignee = Some("bob".to_owned());
+        assert!(db.create_story(story, epic_id).is_err());
+    }
+
+    fn create_story_should_default_reporter_to_current_user(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(
+            db_state.stories[&story_id].reporter,
+            Some(db.config.current_user())
+        );
+    }
+
+    fn work_rows_should_list_items_assigned_to_user(db: JiraDatabase) {
+        db.add_user("alice".to_owned(), "Alice".to_owned()).unwrap();
+        db.add_user("bob".to_owned(), "Bob".to_owned()).unwrap();
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let minor_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let blocker_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let other_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.assign_epic(epic_id, Some("alice".to_owned())).unwrap();
+        db.assign_story(minor_id, Some("alice".to_owned())).unwrap();
+        db.assign_story(blocker_id, Some("alice".to_owned()))
+            .unwrap();
+        db.assign_story(other_id, Some("bob".to_owned())).unwrap();
+        db.update_story_priority(minor_id, Priority::Minor).unwrap();
+        db.update_story_priority(blocker_id, Priority::Blocker)
+            .unwrap();
+
+        let rows = db.work_rows("alice").unwrap();
+        assert_eq!(
+            rows.iter().map(|row| row.id).collect::<Vec<_>>(),
+            vec![blocker_id, epic_id, minor_id]
+        );
+        assert!(db.work_rows("carol").unwrap().is_empty());
+    }
+
+    fn add_label_should_work(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        assert!(db.add_label(epic_id, " frontend ").is_ok());
+        assert!(db.add_label(epic_id, "frontend").is_ok());
+        assert!(db.add_label(story_id, "tech-debt").is_ok());
+        assert!(db.add_label(story_id, "").is_err());
+        assert!(db.add_label(story_id, "tech debt").is_err());
+        assert!(db.add_label(999, "frontend").is_err());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(
+            db_state.epics[&epic_id].labels,
+            BTreeSet::from(["frontend".to_owned()])
+        );
+        assert_eq!(
+            db_state.stories[&story_id].labels,
+            BTreeSet::from(["tech-debt".to_owned()])
+        );
+    }
+
+    fn remove_label_should_error_if_label_is_missing(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        db.add_label(epic_id, "frontend").unwrap();
+
+        assert!(db.remove_label(epic_id, "backend").is_err());
+        assert!(db.remove_label(999, "frontend").is_err());
+        assert!(db.remove_label(epic_id, "frontend").is_ok());
+        assert!(db.read_db().unwrap().epics[&epic_id].labels.is_empty());
+    }
+
+    fn mutations_should_record_timestamps(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let other_epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let created = db.read_db().unwrap();
+        let created_at = created.epics[&epic_id].created_at;
+        assert!(created_at.is_some());
+        assert_eq!(created.epics[&epic_id].updated_at, created_at);
+
+        db.update_epic(epic_id, "name".to_owned(), "".to_owned())
+            .unwrap();
+
+        let updated = db.read_db().unwrap();
+        assert_eq!(updated.epics[&epic_id].created_at, created_at);
+        assert!(updated.epics[&epic_id].updated_at > created_at);
+        // untouched items keep their timestamps
+        assert_eq!(updated.epics[&other_epic_id], created.epics[&other_epic_id]);
+    }
+
+    fn update_due_date_should_work(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let due = NaiveDate::from_ymd_opt(2030, 1, 31);
+
+        assert!(db.update_epic_due_date(epic_id, due).is_ok());
+        assert!(db.update_story_due_date(story_id, due).is_ok());
+        assert!(db.update_epic_due_date(story_id, due).is_err());
+        assert!(db.update_story_due_date(epic_id, due).is_err());
+
+        let rows = db.story_rows(epic_id, SortOrder::Id).unwrap();
+        assert_eq!(rows[0].due, due);
+        assert!(rows[0].is_overdue(NaiveDate::from_ymd_opt(2030, 2, 1).unwrap()));
+        assert!(!rows[0].is_overdue(NaiveDate::from_ymd_opt(2030, 1, 31).unwrap()));
+
+        db.update_story_status(story_id, Status::Resolved).unwrap();
+        let rows = db.story_rows(epic_id, SortOrder::Id).unwrap();
+        assert!(!rows[0].is_overdue(NaiveDate::from_ymd_opt(2030, 2, 1).unwrap()));
+
+        db.update_epic_due_date(epic_id, None).unwrap();
+        assert_eq!(db.read_db().unwrap().epics[&epic_id].due, None);
+    }
+
+    fn add_comment_should_work(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        assert!(db.add_comment(story_id, "first".to_owned()).is_ok());
+        assert!(db.add_comment(story_id, "second".to_owned()).is_ok());
+        assert!(db.add_comment(epic_id, "on the epic".to_owned()).is_ok());
+        assert!(db.add_comment(story_id, " \n".to_owned()).is_err());
+        assert!(db.add_comment(999, "lost".to_owned()).is_err());
+
+        let db_state = db.read_db().unwrap();
+        let comments = &db_state.stories[&story_id].comments;
+        assert_eq!(
+            comments
+                .iter()
+                .map(|comment| comment.body.as_str())
+                .collect::<Vec<_>>(),
+            vec!["first", "second"]
+        );
+        assert_eq!(comments[0].author, db.config.current_user());
+        assert!(comments[0].timestamp <= comments[1].timestamp);
+        assert_eq!(db_state.comments(epic_id).unwrap().len(), 1);
+    }
+
+    fn create_subtask_should_work(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        let subtask = Subtask::new("subtask".to_owned(), "".to_owned());
+        let subtask_id = db.create_subtask(subtask.clone(), story_id).unwrap();
+        assert!(db.create_subtask(subtask.clone(), 999).is_err());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.last_item_id, subtask_id);
+        assert_eq!(db_state.subtasks[&subtask_id], subtask);
+        assert_eq!(db_state.stories[&story_id].subtasks, vec![subtask_id]);
+        assert_eq!(db_state.subtask_story(subtask_id), Some(story_id));
+    }
+
+    fn delete_subtask_should_work(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let subtask_id = db
+            .create_subtask(Subtask::new("".to_owned(), "".to_owned()), story_id)
+            .unwrap();
+
+        assert!(db.delete_subtask(story_id, 999).is_err());
+        assert!(db.delete_subtask(story_id, subtask_id).is_ok());
+
+        let db_state = db.read_db().unwrap();
+        assert!(db_state.subtasks.is_empty());
+        assert!(db_state.stories[&story_id].subtasks.is_empty());
+    }
+
+    fn delete_story_should_remove_subtasks(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.create_subtask(Subtask::new("".to_owned(), "".to_owned()), story_id)
+            .unwrap();
+
+        db.delete_story(epic_id, story_id).unwrap();
+
+        assert!(db.read_db().unwrap().subtasks.is_empty());
+    }
+
+    fn update_subtask_status_should_not_roll_up_by_default(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let subtask_id = db
+            .create_subtask(Subtask::new("".to_owned(), "".to_owned()), story_id)
+            .unwrap();
+
+        assert!(db.update_subtask_status(subtask_id, Status::Closed).is_ok());
+        assert!(db.update_subtask_status(999, Status::Closed).is_err());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.subtasks[&subtask_id].status, Status::Closed);
+        assert_eq!(db_state.stories[&story_id].status, Status::Open);
+    }
+
+    fn update_subtask_status_should_roll_up_when_configured(mut db: JiraDatabase) {
+        db.config.rollup_subtasks = true;
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let first = db
+            .create_subtask(Subtask::new("".to_owned(), "".to_owned()), story_id)
+            .unwrap();
+        let second = db
+            .create_subtask(Subtask::new("".to_owned(), "".to_owned()), story_id)
+            .unwrap();
+
+        db.update_subtask_status(first, Status::Resolved).unwrap();
+        assert_eq!(
+            db.read_db().unwrap().stories[&story_id].status,
+            Status::InProgress
+        );
+
+        db.update_subtask_status(second, Status::Closed).unwrap();
+        assert_eq!(
+            db.read_db().unwrap().stories[&story_id].status,
+            Status::Resolved
+        );
+
+        db.update_subtask_status(first, Status::Closed).unwrap();
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories[&story_id].status, Status::Closed);
+        assert_eq!(db_state.epics[&epic_id].status, Status::Resolved);
+    }
+
+    fn update_subtask_status_should_error_if_epic_is_closed(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let subtask_id = db
+            .create_subtask(Subtask::new("".to_owned(), "".to_owned()), story_id)
+            .unwrap();
+        db.close_epic(epic_id).unwrap();
+
+        assert!(db
+            .update_subtask_status(subtask_id, Status::Resolved)
+            .is_err());
+        assert_eq!(
+            db.read_db().unwrap().subtasks[&subtask_id].status,
+            Status::Open
+        );
+    }
+
+    fn subtasks_should_not_change_if_epic_is_closed(mut db: JiraDatabase) {
+        db.config.rollup_subtasks = true;
+        let story_id = create_stories(&db, 1)[0];
+        let epic_id = db.read_db().unwrap().story_epic(story_id).unwrap();
+        let subtask_id = db
+            .create_subtask(Subtask::new("".to_owned(), "".to_owned()), story_id)
+            .unwrap();
+        db.close_epic(epic_id).unwrap();
+
+        assert!(db
+            .create_subtask(Subtask::new("".to_owned(), "".to_owned()), story_id)
+            .is_err());
+        assert!(db.delete_subtask(story_id, subtask_id).is_err());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories[&story_id].subtasks, vec![subtask_id]);
+        assert_eq!(db_state.subtasks.len(), 1);
+    }
+
+    fn subtask_rollup_should_follow_the_workflow(mut db: JiraDatabase) {
+        db.config.rollup_subtasks = true;
+        db.config.workflow = review_workflow();
+        let story_id = create_stories(&db, 1)[0];
+        let subtask_id = db
+            .create_subtask(Subtask::new("".to_owned(), "".to_owned()), story_id)
+            .unwrap();
+        let story = || db.read_db().unwrap().stories[&story_id].clone();
+
+        // To Do cannot move to Done directly, so the story stays
+        db.update_subtask_status(subtask_id, Status::Closed)
+            .unwrap();
+        assert_eq!(story().status, Status::Open);
+
+        db.update_subtask_status(subtask_id, Status::InProgress)
+            .unwrap();
+        assert_eq!(story().status, Status::InProgress);
+        assert_eq!(story().state.as_deref(), Some("In Progress"));
+
+        // In Progress cannot move to Done either
+        db.update_subtask_status(subtask_id, Status::Closed)
+            .unwrap();
+        assert_eq!(story().status, Status::InProgress);
+
+        db.update_story_state(story_id, "In Review").unwrap();
+        db.update_subtask_status(subtask_id, Status::InProgress)
+            .unwrap();
+        db.update_subtask_status(subtask_id, Status::Closed)
+            .unwrap();
+        assert_eq!(story().status, Status::Closed);
+        assert_eq!(story().state.as_deref(), Some("Done"));
+    }
+
+    fn subtask_rollup_should_not_resolve_blocked_stories(mut db: JiraDatabase) {
+        db.config.rollup_subtasks = true;
+        let stories = create_stories(&db, 2);
+        db.link_stories(stories[0], LinkKind::Blocks, stories[1])
+            .unwrap();
+        let subtask_id = db
+            .create_subtask(Subtask::new("".to_owned(), "".to_owned()), stories[1])
+            .unwrap();
+        let status = || db.read_db().unwrap().stories[&stories[1]].status;
+
+        db.update_subtask_status(subtask_id, Status::InProgress)
+            .unwrap();
+        assert_eq!(status(), Status::InProgress);
+
+        // the sub-task still changes, the blocked story doesn't
+        db.update_subtask_status(subtask_id, Status::Resolved)
+            .unwrap();
+        assert_eq!(
+            db.read_db().unwrap().subtasks[&subtask_id].status,
+            Status::Resolved
+        );
+        assert_eq!(status(), Status::InProgress);
+
+        db.update_story_status(stories[0], Status::Resolved)
+            .unwrap();
+        db.update_subtask_status(subtask_id, Status::Closed)
+            .unwrap();
+        assert_eq!(status(), Status::Closed);
+    }
+
+    fn update_story_type_should_work(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let mut story = Story::new("".to_owned(), "".to_owned());
+        story.issue_type = IssueType::Bug;
+        let story_id = db.create_story(story, epic_id).unwrap();
+
+        // bugs are created with an empty report when none is given
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories[&story_id].bug, Some(BugReport::default()));
+
+        db.update_story_type(story_id, IssueType::Spike).unwrap();
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories[&story_id].issue_type, IssueType::Spike);
+        assert_eq!(db_state.stories[&story_id].bug, None);
+
+        assert!(db.update_story_type(999, IssueType::Task).is_err());
+    }
+
+    fn update_bug_report_should_only_accept_bugs(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let bug = BugReport {
+            severity: Severity::Critical,
+            steps_to_reproduce: "click save twice".to_owned(),
         };
-        let epic = Epic::new("".to_owned(), "".to_owned());
-        let story = Story::new("".to_owned(), "".to_owned());
 
-        let result = db.create_epic(epic);
-        assert_eq!(result.is_ok(), true);
+        assert!(db.update_bug_report(story_id, bug.clone()).is_err());
+
+        db.update_story_type(story_id, IssueType::Bug).unwrap();
+        assert!(db.update_bug_report(story_id, bug.clone()).is_ok());
+        assert_eq!(db.read_db().unwrap().stories[&story_id].bug, Some(bug));
+    }
+
+    fn rows_should_filter_by_type(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        for issue_type in [IssueType::Bug, IssueType::Task, IssueType::Bug] {
+            let mut story = Story::new("".to_owned(), "".to_owned());
+            story.issue_type = issue_type;
+            db.create_story(story, epic_id).unwrap();
+        }
+
+        let rows = db.story_rows(epic_id, SortOrder::Id).unwrap();
+        assert_eq!(filter_rows_by_type(rows.clone(), None).len(), 3);
+        assert_eq!(
+            filter_rows_by_type(rows.clone(), Some(IssueType::Bug))
+                .iter()
+                .map(|row| row.id)
+                .collect::<Vec<_>>(),
+            vec![2, 4]
+        );
+        assert!(filter_rows_by_type(rows, Some(IssueType::Spike)).is_empty());
+    }
+
+    // creates an epic with the given number of stories and returns the story ids
+    fn create_stories(db: &JiraDatabase, count: usize) -> Vec<usize> {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        (0..count)
+            .map(|_| {
+                db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    fn move_story_should_work(db: JiraDatabase) {
+        let stories = create_stories(&db, 2);
+        let from_epic_id = db.read_db().unwrap().story_epic(stories[0]).unwrap();
+        let to_epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let other_story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), to_epic_id)
+            .unwrap();
+        db.update_story_status(stories[0], Status::Resolved)
+            .unwrap();
+
+        assert!(db.move_story(stories[1], to_epic_id).is_ok());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics[&from_epic_id].stories, vec![stories[0]]);
+        assert_eq!(
+            db_state.epics[&to_epic_id].stories,
+            vec![other_story_id, stories[1]]
+        );
+        // only the resolved story is left behind
+        assert_eq!(db_state.epics[&from_epic_id].status, Status::Resolved);
+        assert_eq!(db_state.epics[&to_epic_id].status, Status::Open);
+    }
+
+    fn move_story_should_error_for_invalid_moves(db: JiraDatabase) {
+        let stories = create_stories(&db, 1);
+        let epic_id = db.read_db().unwrap().story_epic(stories[0]).unwrap();
+        let closed_epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        db.close_epic(closed_epic_id).unwrap();
+
+        assert!(db.move_story(stories[0], epic_id).is_err());
+        assert!(db.move_story(stories[0], closed_epic_id).is_err());
+        assert!(db.move_story(stories[0], 999).is_err());
+        assert!(db.move_story(999, epic_id).is_err());
+        assert_eq!(db.read_db().unwrap().epics[&epic_id].stories, stories);
+    }
+
+    fn create_sprint(db: &JiraDatabase, story_ids: &[usize]) -> usize {
+        let sprint_id = db
+            .create_sprint(Sprint::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        for story_id in story_ids {
+            db.add_story_to_sprint(sprint_id, *story_id).unwrap();
+        }
+        sprint_id
+    }
+
+    fn create_sprint_should_work(db: JiraDatabase) {
+        let mut sprint = Sprint::new("sprint".to_owned(), "goal".to_owned());
+        sprint.start = NaiveDate::from_ymd_opt(2030, 1, 1);
+        sprint.end = NaiveDate::from_ymd_opt(2030, 1, 14);
+
+        let sprint_id = db.create_sprint(sprint.clone()).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        // sprints don't use up issue ids
+        assert_eq!(db_state.last_item_id, 0);
+        assert_eq!(db_state.last_sprint_id, sprint_id);
+        assert_eq!(sprint_id, 1);
+        assert_eq!(db_state.sprints[&sprint_id], sprint);
+
+        sprint.end = NaiveDate::from_ymd_opt(2029, 12, 31);
+        assert!(db.create_sprint(sprint).is_err());
+    }
+
+    fn add_story_to_sprint_should_work(db: JiraDatabase) {
+        // stories are pulled from any epic
+        let first_epic_story = create_stories(&db, 1)[0];
+        let second_epic_story = create_stories(&db, 1)[0];
+        let sprint_id = create_sprint(&db, &[second_epic_story, first_epic_story]);
+
+        assert_eq!(
+            db.read_db().unwrap().sprints[&sprint_id].stories,
+            vec![second_epic_story, first_epic_story]
+        );
+        assert!(db.backlog_rows().unwrap().is_empty());
+
+        assert!(db
+            .remove_story_from_sprint(sprint_id, second_epic_story)
+            .is_ok());
+        assert!(db
+            .remove_story_from_sprint(sprint_id, second_epic_story)
+            .is_err());
+        let backlog = db.backlog_rows().unwrap();
+        assert_eq!(backlog.len(), 1);
+        assert_eq!(backlog[0].id, second_epic_story);
+
+        assert!(db.add_story_to_sprint(sprint_id, 999).is_err());
+        assert!(db.add_story_to_sprint(999, second_epic_story).is_err());
+    }
+
+    fn add_story_to_sprint_should_reject_stories_in_another_open_sprint(db: JiraDatabase) {
+        let stories = create_stories(&db, 1);
+        create_sprint(&db, &stories);
+        let other_sprint_id = create_sprint(&db, &[]);
+
+        assert!(db.add_story_to_sprint(other_sprint_id, stories[0]).is_err());
+    }
+
+    fn start_sprint_should_only_allow_one_active_sprint(db: JiraDatabase) {
+        let sprint_id = create_sprint(&db, &[]);
+        let other_sprint_id = create_sprint(&db, &[]);
+
+        assert!(db.start_sprint(sprint_id).is_ok());
+        assert!(db.start_sprint(sprint_id).is_err());
+        assert!(db.start_sprint(other_sprint_id).is_err());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.active_sprint(), Some(sprint_id));
+        assert_eq!(
+            db_state.sprints[&sprint_id].start,
+            Some(Local::now().date_naive())
+        );
+    }
+
+    fn complete_sprint_should_move_unfinished_stories_to_the_backlog(db: JiraDatabase) {
+        let stories = create_stories(&db, 3);
+        let sprint_id = create_sprint(&db, &stories);
+        db.start_sprint(sprint_id).unwrap();
+        db.update_story_status(stories[0], Status::Resolved)
+            .unwrap();
+        db.update_story_status(stories[1], Status::InProgress)
+            .unwrap();
+
+        // only the active sprint can be completed
+        assert!(db.complete_sprint(create_sprint(&db, &[]), None).is_err());
+        assert!(db.complete_sprint(sprint_id, None).is_ok());
+
+        let db_state = db.read_db().unwrap();
+        let sprint = &db_state.sprints[&sprint_id];
+        assert_eq!(sprint.state, SprintState::Completed);
+        assert_eq!(sprint.stories, vec![stories[0]]);
+        assert_eq!(db_state.active_sprint(), None);
+        let backlog = db.backlog_rows().unwrap();
+        assert_eq!(backlog.len(), 2);
+
+        assert!(db.add_story_to_sprint(sprint_id, stories[1]).is_err());
+    }
+
+    fn complete_sprint_should_move_unfinished_stories_to_the_next_sprint(db: JiraDatabase) {
+        let stories = create_stories(&db, 3);
+        let sprint_id = create_sprint(&db, &stories[..2]);
+        let next_sprint_id = create_sprint(&db, &stories[2..]);
+        db.start_sprint(sprint_id).unwrap();
+        db.update_story_status(stories[1], Status::Closed).unwrap();
+
+        assert!(db.complete_sprint(sprint_id, Some(sprint_id)).is_err());
+        assert!(db.complete_sprint(sprint_id, Some(next_sprint_id)).is_ok());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.sprints[&sprint_id].stories, vec![stories[1]]);
+        assert_eq!(
+            db_state.sprints[&next_sprint_id].stories,
+            vec![stories[2], stories[0]]
+        );
+    }
+
+    fn delete_story_should_remove_it_from_sprints(db: JiraDatabase) {
+        let stories = create_stories(&db, 2);
+        let sprint_id = create_sprint(&db, &stories);
+        let epic_id = db.read_db().unwrap().story_epic(stories[0]).unwrap();
+
+        db.delete_story(epic_id, stories[0]).unwrap();
+
+        assert_eq!(
+            db.read_db().unwrap().sprints[&sprint_id].stories,
+            vec![stories[1]]
+        );
+    }
+
+    fn board_rows_should_show_one_or_every_epic(db: JiraDatabase) {
+        let first_epic_stories = create_stories(&db, 2);
+        let second_epic_story = create_stories(&db, 1)[0];
+        let epic_id = db.read_db().unwrap().story_epic(second_epic_story).unwrap();
+        db.update_story_priority(first_epic_stories[1], Priority::Blocker)
+            .unwrap();
+
+        let ids = |rows: Vec<ItemRow>| rows.into_iter().map(|row| row.id).collect_vec();
+        assert_eq!(
+            ids(db.board_rows(None).unwrap()),
+            vec![
+                first_epic_stories[1],
+                first_epic_stories[0],
+                second_epic_story
+            ]
+        );
+        assert_eq!(
+            ids(db.board_rows(Some(epic_id)).unwrap()),
+            vec![second_epic_story]
+        );
+        assert!(db.board_rows(Some(999)).is_err());
+    }
+
+    // To Do -> In Progress <-> In Review -> Done
+    fn review_workflow() -> Workflow {
+        let state = |name: &str, category| WorkflowState {
+            name: name.to_owned(),
+            category,
+        };
+        let transitions = [
+            ("To Do", vec!["In Progress"]),
+            ("In Progress", vec!["In Review"]),
+            ("In Review", vec!["In Progress", "Done"]),
+        ];
+        Workflow {
+            states: vec![
+                state("To Do", Status::Open),
+                state("In Progress", Status::InProgress),
+                state("In Review", Status::InProgress),
+                state("Done", Status::Closed),
+            ],
+            transitions: transitions
+                .into_iter()
+                .map(|(from, to)| (from.to_owned(), to.into_iter().map(str::to_owned).collect()))
+                .collect(),
+        }
+    }
+
+    fn update_story_state_should_follow_the_workflow(mut db: JiraDatabase) {
+        db.config.workflow = review_workflow();
+        let story_id = create_stories(&db, 1)[0];
+
+        assert!(db.update_story_state(story_id, "Done").is_err());
+        assert!(db.update_story_state(story_id, "Nowhere").is_err());
+        assert!(db.update_story_state(story_id, "in progress").is_ok());
+        assert!(db.update_story_state(story_id, "In Review").is_ok());
+
+        let db_state = db.read_db().unwrap();
+        let story = &db_state.stories[&story_id];
+        assert_eq!(story.status, Status::InProgress);
+        assert_eq!(story.state.as_deref(), Some("In Review"));
+
+        assert!(db.update_story_state(story_id, "Done").is_ok());
+        assert_eq!(
+            db.read_db().unwrap().stories[&story_id].status,
+            Status::Closed
+        );
+
+        // Done is final
+        assert!(db.update_story_state(story_id, "In Review").is_err());
+        assert!(db.update_story_state(story_id, "Done").is_ok());
+    }
+
+    fn update_story_status_should_use_the_first_state_of_the_status(mut db: JiraDatabase) {
+        db.config.workflow = review_workflow();
+        let story_id = create_stories(&db, 1)[0];
+
+        assert!(db.update_story_status(story_id, Status::Closed).is_err());
+        assert!(db.update_story_status(story_id, Status::InProgress).is_ok());
+        assert_eq!(
+            db.read_db().unwrap().stories[&story_id].state.as_deref(),
+            Some("In Progress")
+        );
+        // the workflow has no Resolved state
+        assert!(db.update_story_status(story_id, Status::Resolved).is_err());
+    }
+
+    fn epic_status_should_roll_up_from_its_stories(db: JiraDatabase) {
+        let stories = create_stories(&db, 2);
+        let epic_id = db.read_db().unwrap().story_epic(stories[0]).unwrap();
+        let epic_status = || db.read_db().unwrap().epics[&epic_id].status;
+
+        assert_eq!(epic_status(), Status::Open);
+        db.update_story_status(stories[0], Status::InProgress)
+            .unwrap();
+        assert_eq!(epic_status(), Status::InProgress);
+        // back to open once nothing has started
+        db.update_story_status(stories[0], Status::Open).unwrap();
+        assert_eq!(epic_status(), Status::Open);
+        db.update_story_status(stories[0], Status::Closed).unwrap();
+        assert_eq!(epic_status(), Status::InProgress);
+        db.update_story_status(stories[1], Status::Resolved)
+            .unwrap();
+        assert_eq!(epic_status(), Status::Resolved);
+        db.update_story_status(stories[1], Status::Closed).unwrap();
+        assert_eq!(epic_status(), Status::Resolved);
+
+        // new work reopens the finished epic
+        let new_story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        assert_eq!(epic_status(), Status::InProgress);
+        db.delete_story(epic_id, new_story_id).unwrap();
+        assert_eq!(epic_status(), Status::Resolved);
+
+        // an epic left without stories starts over
+        db.delete_story(epic_id, stories[0]).unwrap();
+        db.delete_story(epic_id, stories[1]).unwrap();
+        assert_eq!(epic_status(), Status::Open);
+    }
+
+    fn epic_rollup_should_leave_closed_epics_alone(db: JiraDatabase) {
+        let stories = create_stories(&db, 2);
+        let epic_id = db.read_db().unwrap().story_epic(stories[0]).unwrap();
+        db.close_epic(epic_id).unwrap();
+
+        db.delete_story(epic_id, stories[0]).unwrap();
+
+        assert_eq!(db.read_db().unwrap().epics[&epic_id].status, Status::Closed);
+    }
+
+    fn epic_rollup_should_close_epics_when_configured(mut db: JiraDatabase) {
+        db.config.epic_rollup.close_when_all_closed = true;
+        let stories = create_stories(&db, 2);
+        let epic_id = db.read_db().unwrap().story_epic(stories[0]).unwrap();
+
+        db.update_story_status(stories[0], Status::Closed).unwrap();
+        db.update_story_status(stories[1], Status::Resolved)
+            .unwrap();
+        assert_eq!(
+            db.read_db().unwrap().epics[&epic_id].status,
+            Status::Resolved
+        );
+        db.update_story_status(stories[1], Status::Closed).unwrap();
+        assert_eq!(db.read_db().unwrap().epics[&epic_id].status, Status::Closed);
+
+        // reopening a finished epic never closes it again straight away
+        db.reopen_epic(epic_id).unwrap();
+        assert_eq!(
+            db.read_db().unwrap().epics[&epic_id].status,
+            Status::Resolved
+        );
+    }
+
+    fn reopen_epic_should_reset_to_the_rolled_up_status(db: JiraDatabase) {
+        let stories = create_stories(&db, 2);
+        let epic_id = db.read_db().unwrap().story_epic(stories[0]).unwrap();
+        db.update_story_status(stories[0], Status::InProgress)
+            .unwrap();
+        db.close_epic(epic_id).unwrap();
+
+        assert!(db.reopen_epic(epic_id).is_ok());
+        assert_eq!(
+            db.read_db().unwrap().epics[&epic_id].status,
+            Status::InProgress
+        );
 
-        let epic_id = result.unwrap();
+        // only closed epics can be reopened
+        assert!(db.reopen_epic(epic_id).is_err());
+        assert!(db.reopen_epic(999).is_err());
 
-        let result = db.create_story(story, epic_id);
-        assert_eq!(result.is_ok(), true);
+        let empty_epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        db.close_epic(empty_epic_id).unwrap();
+        db.reopen_epic(empty_epic_id).unwrap();
+        assert_eq!(
+            db.read_db().unwrap().epics[&empty_epic_id].status,
+            Status::Open
+        );
+    }
 
-        let story_id = result.unwrap();
+    fn link_stories_should_work(db: JiraDatabase) {
+        let stories = create_stories(&db, 2);
+        // links may cross epics
+        let other_epic_story = create_stories(&db, 1)[0];
+
+        assert!(db
+            .link_stories(stories[0], LinkKind::Blocks, stories[1])
+            .is_ok());
+        assert!(db
+            .link_stories(other_epic_story, LinkKind::RelatesTo, stories[1])
+            .is_ok());
+        assert!(db
+            .link_stories(stories[0], LinkKind::Blocks, stories[1])
+            .is_err());
+        assert!(db
+            .link_stories(stories[0], LinkKind::RelatesTo, stories[0])
+            .is_err());
+        assert!(db.link_stories(stories[0], LinkKind::Blocks, 999).is_err());
+        assert!(db.link_stories(999, LinkKind::Blocks, stories[0]).is_err());
 
-        let non_existent_epic_id = 999;
+        let db_state = db.read_db().unwrap();
+        assert_eq!(
+            db_state.stories[&stories[0]].links,
+            vec![Link {
+                kind: LinkKind::Blocks,
+                target: stories[1],
+            }]
+        );
+        assert_eq!(
+            db_state.inbound_links(stories[1]),
+            vec![
+                (stories[0], LinkKind::Blocks),
+                (other_epic_story, LinkKind::RelatesTo)
+            ]
+        );
+        assert_eq!(db_state.open_blockers(stories[1]), vec![stories[0]]);
+    }
 
-        let result = db.delete_story(non_existent_epic_id, story_id);
-        assert_eq!(result.is_err(), true);
+    fn link_stories_should_reject_cycles(db: JiraDatabase) {
+        let stories = create_stories(&db, 3);
+        db.link_stories(stories[0], LinkKind::Blocks, stories[1])
+            .unwrap();
+        db.link_stories(stories[1], LinkKind::Blocks, stories[2])
+            .unwrap();
+
+        assert!(db
+            .link_stories(stories[1], LinkKind::Blocks, stories[0])
+            .is_err());
+        assert!(db
+            .link_stories(stories[2], LinkKind::Blocks, stories[0])
+            .is_err());
+        // only "blocks" links can form a cycle
+        assert!(db
+            .link_stories(stories[2], LinkKind::RelatesTo, stories[0])
+            .is_ok());
+        assert!(db
+            .link_stories(stories[0], LinkKind::Blocks, stories[2])
+            .is_ok());
     }
 
-    #[test]
-    fn delete_story_should_error_if_story_not_found_in_epic() {
-        let db = JiraDatabase {
-            database: Box::new(MockDB::new()),
-        };
-        let epic = Epic::new("".to_owned(), "".to_owned());
-        let story = Story::new("".to_owned(), "".to_owned());
+    fn unlink_stories_should_work(db: JiraDatabase) {
+        let stories = create_stories(&db, 2);
+        db.link_stories(stories[0], LinkKind::Blocks, stories[1])
+            .unwrap();
+        db.link_stories(stories[1], LinkKind::RelatesTo, stories[0])
+            .unwrap();
 
-        let result = db.create_epic(epic);
-        assert_eq!(result.is_ok(), true);
+        assert!(db.unlink_stories(stories[1], stories[0]).is_ok());
+        assert!(db.unlink_stories(stories[1], stories[0]).is_err());
+        assert!(db.unlink_stories(stories[1], 999).is_err());
 
-        let epic_id = result.unwrap();
+        let db_state = db.read_db().unwrap();
+        assert!(db_state.stories[&stories[0]].links.is_empty());
+        assert!(db_state.stories[&stories[1]].links.is_empty());
+    }
 
-        let result = db.create_story(story, epic_id);
-        assert_eq!(result.is_ok(), true);
+    fn delete_story_should_remove_links_to_it(db: JiraDatabase) {
+        let stories = create_stories(&db, 2);
+        db.link_stories(stories[0], LinkKind::Blocks, stories[1])
+            .unwrap();
+        let epic_id = db.read_db().unwrap().story_epic(stories[1]).unwrap();
 
-        let non_existent_story_id = 999;
+        db.delete_story(epic_id, stories[1]).unwrap();
 
-        let result = db.delete_story(epic_id, non_existent_story_id);
-        assert_eq!(result.is_err(), true);
+        assert!(db.read_db().unwrap().stories[&stories[0]].links.is_empty());
     }
 
-    #[test]
-    fn delete_story_should_work() {
-        let db = JiraDatabase {
-            database: Box::new(MockDB::new()),
-        };
-        let epic = Epic::new("".to_owned(), "".to_owned());
-        let story = Story::new("".to_owned(), "".to_owned());
+    fn update_story_status_should_refuse_to_resolve_blocked_stories(db: JiraDatabase) {
+        let stories = create_stories(&db, 3);
+        db.link_stories(stories[0], LinkKind::Blocks, stories[2])
+            .unwrap();
+        db.link_stories(stories[1], LinkKind::Blocks, stories[2])
+            .unwrap();
+        db.update_story_status(stories[1], Status::Resolved)
+            .unwrap();
+
+        let error = db
+            .update_story_status(stories[2], Status::Resolved)
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<BlockedError>(),
+            Some(&BlockedError {
+                story: db.config.issue_key(stories[2]),
+                blockers: vec![db.config.issue_key(stories[0])],
+            })
+        );
+        // blocked stories can still be worked on
+        assert!(db
+            .update_story_status(stories[2], Status::InProgress)
+            .is_ok());
 
-        let result = db.create_epic(epic);
-        assert_eq!(result.is_ok(), true);
+        assert!(db.force_story_status(stories[2], Status::Closed).is_ok());
+        assert_eq!(
+            db.read_db().unwrap().stories[&stories[2]].status,
+            Status::Closed
+        );
+    }
 
-        let epic_id = result.unwrap();
+    fn rows_should_filter_by_label(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let frontend_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let backend_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.add_label(frontend_id, "frontend").unwrap();
+        db.add_label(frontend_id, "tech-debt").unwrap();
+        db.add_label(backend_id, "backend").unwrap();
+
+        let rows = db.story_rows(epic_id, SortOrder::Id).unwrap();
+        assert_eq!(rows[0].labels, "frontend tech-debt");
+
+        let filtered = filter_rows(rows.clone(), Some("tech-debt"));
+        assert_eq!(
+            filtered.iter().map(|row| row.id).collect::<Vec<_>>(),
+            vec![frontend_id]
+        );
+        assert!(filter_rows(rows.clone(), Some("tech")).is_empty());
+        assert_eq!(filter_rows(rows, None).len(), 2);
+    }
 
-        let result = db.create_story(story, epic_id);
-        assert_eq!(result.is_ok(), true);
+    fn update_epic_should_error_if_invalid_epic_id(db: JiraDatabase) {
+        let result = db.update_epic(999, "".to_owned(), "".to_owned());
+        assert!(result.is_err());
+    }
 
-        let story_id = result.unwrap();
+    fn update_epic_should_work(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("nmae".to_owned(), "".to_owned()))
+            .unwrap();
 
-        let result = db.delete_story(epic_id, story_id);
-        assert_eq!(result.is_ok(), true);
+        let result = db.update_epic(epic_id, "name".to_owned(), "description".to_owned());
+        assert!(result.is_ok());
 
         let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics[&epic_id].name, "name");
+        assert_eq!(db_state.epics[&epic_id].description, "description");
 
-        let expected_last_id = 1;
-
-        assert_eq!(db_state.last_item_id, expected_last_id);
+        let events = db.read_events(epic_id).unwrap();
         assert_eq!(
-            db_state
-                .epics
-                .get(&epic_id)
-                .unwrap()
-                .stories
-                .contains(&story_id),
-            false
+            events.last().unwrap().kind,
+            EventKind::FieldChanged {
+                field: "description".to_owned(),
+                from: "".to_owned(),
+                to: "description".to_owned()
+            }
         );
-        assert_eq!(db_state.stories.get(&story_id), None);
     }
 
-    #[test]
-    fn close_epic_should_work() {
-        let db = JiraDatabase {
-            database: Box::new(MockDB::new()),
-        };
-        let epic = Epic::new("".to_owned(), "".to_owned());
+    fn update_story_should_error_if_invalid_story_id(db: JiraDatabase) {
+        let result = db.update_story(999, "".to_owned(), "".to_owned());
+        assert!(result.is_err());
+    }
 
-        let result = db.create_epic(epic);
+    fn update_story_should_work(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
 
-        assert_eq!(result.is_ok(), true);
+        let result = db.update_story(story_id, "name".to_owned(), "description".to_owned());
+        assert!(result.is_ok());
 
-        let epic_id = result.unwrap();
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories[&story_id].name, "name");
+        assert_eq!(db_state.stories[&story_id].description, "description");
+        assert_eq!(db_state.stories[&story_id].status, Status::Open);
+    }
 
-        let result = db.close_epic(epic_id);
+    fn restore_should_work(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let snapshot = db.read_db().unwrap();
 
-        assert_eq!(result.is_ok(), true);
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        let result = db.restore(snapshot);
+        assert!(result.is_ok());
 
         let db_state = db.read_db().unwrap();
 
-        assert_eq!(db_state.epics.get(&epic_id).unwrap().status, Status::Closed);
+        assert_eq!(db_state.stories.get(&story_id), None);
+        assert!(db_state.epics[&epic_id].stories.is_empty());
+        assert_eq!(db_state.last_item_id, story_id);
     }
 
-    #[test]
-    fn update_story_status_should_error_if_invalid_story_id() {
-        let db = JiraDatabase {
-            database: Box::new(MockDB::new()),
-        };
-
-        let non_existent_story_id = 999;
+    fn deleted_ids_should_never_be_reused(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
 
-        let result = db.update_story_status(non_existent_story_id, Status::Closed);
-        assert_eq!(result.is_err(), true);
-    }
+        let result = db.delete_story(epic_id, story_id);
+        assert!(result.is_ok());
 
-    #[test]
-    fn update_story_status_should_work() {
-        let db = JiraDatabase {
-            database: Box::new(MockDB::new()),
-        };
-        let epic = Epic::new("".to_owned(), "".to_owned());
-        let story = Story::new("".to_owned(), "".to_owned());
+        let new_story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        assert_eq!(new_story_id, story_id + 1);
 
-        let result = db.create_epic(epic);
+        let result = db.delete_epic(epic_id);
+        assert!(result.is_ok());
 
-        let epic_id = result.unwrap();
+        let new_epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        assert_eq!(new_epic_id, new_story_id + 1);
+    }
 
-        let result = db.create_story(story, epic_id);
+    fn modify_should_fail_with_conflict_if_changed_since_read(db: JiraDatabase) {
+        let result = db.create_epic(Epic::new("".to_owned(), "".to_owned()));
+        assert!(result.is_ok());
 
-        let story_id = result.unwrap();
+        let db_state = db.view_db().unwrap();
 
-        let result = db.update_story_status(story_id, Status::Closed);
+        // someone else writes after this session read the state
+        db.database.write_db(&db_state).unwrap();
 
-        assert_eq!(result.is_ok(), true);
+        let result = db.create_epic(Epic::new("".to_owned(), "".to_owned()));
+        assert!(result.unwrap_err().is::<ConflictError>());
 
-        let db_state = db.read_db().unwrap();
+        let result = db.view_db();
+        assert_eq!(result.unwrap().epics.len(), 1);
 
-        assert_eq!(
-            db_state.stories.get(&story_id).unwrap().status,
-            Status::Closed
-        );
+        let result = db.create_epic(Epic::new("".to_owned(), "".to_owned()));
+        assert!(result.is_ok());
     }
 
     mod database {
@@ -559,12 +4142,40 @@ mod tests {
                 name: "epic 1".to_owned(),
                 description: "epic 1".to_owned(),
                 status: Status::Open,
+                points: Some(3),
+                priority: Priority::Minor,
+                assignee: Some("alice".to_owned()),
+                reporter: None,
+                labels: BTreeSet::from(["frontend".to_owned()]),
+                created_at: Some(Utc::now()),
+                updated_at: Some(Utc::now()),
+                due: None,
+                comments: vec![Comment {
+                    author: "alice".to_owned(),
+                    timestamp: Utc::now(),
+                    body: "comment".to_owned(),
+                }],
+                subtasks: vec![3],
+                issue_type: IssueType::Bug,
+                bug: Some(BugReport {
+                    severity: Severity::Critical,
+                    steps_to_reproduce: "open the app".to_owned(),
+                }),
+                links: vec![],
+                state: Some("In Review".to_owned()),
             };
             let epic = Epic {
                 name: "epic 1".to_owned(),
                 description: "epic 1".to_owned(),
                 status: Status::Open,
                 stories: vec![2],
+                priority: Priority::Critical,
+                owner: None,
+                labels: BTreeSet::new(),
+                created_at: None,
+                updated_at: None,
+                due: "2030-01-31".parse().ok(),
+                comments: vec![],
             };
 
             let mut stories = HashMap::new();
@@ -573,17 +4184,542 @@ mod tests {
             let mut epics = HashMap::new();
             epics.insert(1, epic);
 
+            let mut users = HashMap::new();
+            users.insert(
+                "alice".to_owned(),
+                User {
+                    name: "Alice".to_owned(),
+                },
+            );
+
+            let mut subtasks = HashMap::new();
+            subtasks.insert(3, Subtask::new("subtask 3".to_owned(), "".to_owned()));
+
             let state = DBState {
-                last_item_id: 2,
+                schema_version: CURRENT_SCHEMA_VERSION,
+                last_item_id: 3,
+                last_sprint_id: 0,
+                revision: 0,
                 epics,
                 stories,
+                users,
+                subtasks,
+                sprints: HashMap::new(),
             };
 
             let write_result = db.write_db(&state);
             let read_result = db.read_db().unwrap();
 
             assert_eq!(write_result.is_ok(), true);
-            assert_eq!(read_result, state);
+            assert_eq!(
+                read_result,
+                DBState {
+                    revision: 1,
+                    ..state
+                }
+            );
+        }
+
+        #[test]
+        fn sqlite_read_db_should_return_empty_state_for_new_file() {
+            let tmp_dir = tempfile::tempdir().unwrap();
+
+            let db = SqliteDatabase {
+                file_path: tmp_dir
+                    .path()
+                    .join("db.sqlite")
+                    .to_str()
+                    .expect("failed to convert tmp_dir path to str")
+                    .to_string(),
+            };
+
+            let result = db.read_db();
+
+            assert_eq!(result.unwrap(), DBState::new());
+        }
+
+        #[test]
+        fn sqlite_read_db_should_upgrade_files_without_user_version() {
+            let tmp_dir = tempfile::tempdir().unwrap();
+            let file_path = tmp_dir.path().join("db.sqlite");
+
+            // the version 1 layout, written before user_version was tracked
+            let connection = Connection::open(&file_path).unwrap();
+            connection.execute_batch(SQLITE_MIGRATIONS[0]).unwrap();
+            connection
+                .execute(
+                    "INSERT INTO stories (id, name, description, status) VALUES (1, 'story', '', 'Open')",
+                    [],
+                )
+                .unwrap();
+            drop(connection);
+
+            let db = SqliteDatabase {
+                file_path: file_path.to_str().unwrap().to_string(),
+            };
+
+            let db_state = db.read_db().unwrap();
+            assert_eq!(db_state.stories[&1].name, "story");
+            assert_eq!(db_state.stories[&1].points, None);
+
+            let mut story = db_state.stories[&1].clone();
+            story.points = Some(5);
+            let mut new_state = db_state.clone();
+            new_state.stories.insert(1, story);
+            db.write_db(&new_state).unwrap();
+
+            assert_eq!(db.read_db().unwrap().stories[&1].points, Some(5));
+        }
+
+        #[test]
+        fn sqlite_read_db_should_not_wait_for_writers() {
+            let tmp_dir = tempfile::tempdir().unwrap();
+            let file_path = tmp_dir.path().join("db.sqlite");
+            let db = SqliteDatabase {
+                file_path: file_path.to_str().unwrap().to_string(),
+            };
+            db.read_db().unwrap();
+
+            // another process is in the middle of a write
+            let mut connection = Connection::open(&file_path).unwrap();
+            let transaction = connection
+                .transaction_with_behavior(TransactionBehavior::Immediate)
+                .unwrap();
+
+            assert_eq!(db.read_db().unwrap(), DBState::new());
+            drop(transaction);
+        }
+
+        #[test]
+        fn sqlite_write_db_should_work() {
+            let tmp_dir = tempfile::tempdir().unwrap();
+
+            let db = SqliteDatabase {
+                file_path: tmp_dir
+                    .path()
+                    .join("db.sqlite")
+                    .to_str()
+                    .expect("failed to convert tmp_dir path to str")
+                    .to_string(),
+            };
+
+            let mut state = DBState::new();
+            state.last_item_id = 3;
+            state
+                .stories
+                .insert(2, Story::new("story 2".to_owned(), "story 2".to_owned()));
+            state
+                .stories
+                .insert(3, Story::new("story 3".to_owned(), "story 3".to_owned()));
+            let mut epic = Epic::new("epic 1".to_owned(), "epic 1".to_owned());
+            epic.stories = vec![3, 2];
+            epic.owner = Some("alice".to_owned());
+            state.epics.insert(1, epic);
+            state.users.insert(
+                "alice".to_owned(),
+                User {
+                    name: "Alice".to_owned(),
+                },
+            );
+            state.stories.get_mut(&2).unwrap().reporter = Some("alice".to_owned());
+            state.stories.get_mut(&3).unwrap().labels = BTreeSet::from(["frontend".to_owned()]);
+            state.epics.get_mut(&1).unwrap().labels =
+                BTreeSet::from(["frontend".to_owned(), "tech-debt".to_owned()]);
+            state.epics.get_mut(&1).unwrap().created_at = Some(Utc::now());
+            state.stories.get_mut(&2).unwrap().due = NaiveDate::from_ymd_opt(2030, 1, 31);
+            for (item_id, body) in [(3, "first"), (1, "second"), (3, "third")] {
+                item_comments(&mut state, item_id).unwrap().push(Comment {
+                    author: "alice".to_owned(),
+                    timestamp: Utc::now(),
+                    body: body.to_owned(),
+                });
+            }
+            state.last_item_id = 5;
+            state
+                .subtasks
+                .insert(4, Subtask::new("subtask 4".to_owned(), "".to_owned()));
+            state
+                .subtasks
+                .insert(5, Subtask::new("subtask 5".to_owned(), "".to_owned()));
+            state.stories.get_mut(&2).unwrap().subtasks = vec![5, 4];
+            state
+                .stories
+                .get_mut(&3)
+                .unwrap()
+                .set_issue_type(IssueType::Bug);
+            state.stories.get_mut(&2).unwrap().issue_type = IssueType::Spike;
+            // story 2 links to story 3, which is only upserted after it on some runs
+            state.stories.get_mut(&2).unwrap().links = vec![
+                Link {
+                    kind: LinkKind::Blocks,
+                    target: 3,
+                },
+                Link {
+                    kind: LinkKind::RelatesTo,
+                    target: 3,
+                },
+            ];
+            state.stories.get_mut(&3).unwrap().links = vec![Link {
+                kind: LinkKind::RelatesTo,
+                target: 2,
+            }];
+            state.last_item_id = 7;
+            let mut sprint = Sprint::new("sprint 6".to_owned(), "ship it".to_owned());
+            sprint.start = NaiveDate::from_ymd_opt(2030, 1, 1);
+            sprint.end = NaiveDate::from_ymd_opt(2030, 1, 14);
+            sprint.state = SprintState::Active;
+            sprint.stories = vec![3, 2];
+            state.sprints.insert(6, sprint);
+            state
+                .sprints
+                .insert(7, Sprint::new("sprint 7".to_owned(), "".to_owned()));
+
+            let write_result = db.write_db(&state);
+            state.revision += 1;
+            assert!(write_result.is_ok());
+            assert_eq!(db.read_db().unwrap(), state);
+
+            state.stories.remove(&3);
+            state.remove_dangling_references();
+            state.epics.get_mut(&1).unwrap().stories = vec![2];
+            state.stories.get_mut(&2).unwrap().status = Status::Resolved;
+            state.epics.get_mut(&1).unwrap().owner = None;
+            state.epics.get_mut(&1).unwrap().labels.remove("frontend");
+            state.users.clear();
+            state.subtasks.remove(&5);
+            state.stories.get_mut(&2).unwrap().bug = Some(BugReport {
+                severity: Severity::Cosmetic,
+                steps_to_reproduce: "resize the window".to_owned(),
+            });
+            state.stories.get_mut(&2).unwrap().issue_type = IssueType::Bug;
+            state.subtasks.get_mut(&4).unwrap().status = Status::Closed;
+            state.stories.get_mut(&2).unwrap().subtasks = vec![4];
+            state.sprints.remove(&7);
+            state.stories.get_mut(&2).unwrap().state = Some("In Review".to_owned());
+            state.sprints.get_mut(&6).unwrap().state = SprintState::Completed;
+
+            let write_result = db.write_db(&state);
+            state.revision += 1;
+            assert!(write_result.is_ok());
+            assert_eq!(db.read_db().unwrap(), state);
+        }
+
+        #[test]
+        fn write_db_should_replace_file_atomically() {
+            let tmp_dir = tempfile::tempdir().unwrap();
+            let file_path = tmp_dir
+                .path()
+                .join("db.json")
+                .to_str()
+                .expect("failed to convert tmp_dir path to str")
+                .to_string();
+
+            let db = JSONFileDatabase {
+                file_path: file_path.clone(),
+            };
+
+            let write_result = db.write_db(&DBState::new());
+
+            assert!(write_result.is_ok());
+            assert_eq!(db.read_db().unwrap().revision, 1);
+            assert!(!Path::new(&format!("{}.tmp", file_path)).exists());
+        }
+
+        #[test]
+        fn write_db_should_fail_with_conflict_if_revision_changed() {
+            let tmp_dir = tempfile::tempdir().unwrap();
+
+            for file_name in ["db.json", "db.sqlite"] {
+                let db = JiraDatabase::new(
+                    tmp_dir
+                        .path()
+                        .join(file_name)
+                        .to_str()
+                        .expect("failed to convert tmp_dir path to str")
+                        .to_string(),
+                );
+                let stale_state = DBState::new();
+                db.database.write_db(&stale_state).unwrap();
+
+                let result = db.database.write_db(&stale_state);
+
+                assert_eq!(
+                    result.unwrap_err().downcast_ref::<ConflictError>(),
+                    Some(&ConflictError {
+                        expected_revision: 0,
+                        actual_revision: 1
+                    })
+                );
+            }
+        }
+
+        #[test]
+        fn lock_should_serialize_concurrent_writers() {
+            for file_name in ["db.json", "db.sqlite"] {
+                let tmp_dir = tempfile::tempdir().unwrap();
+                let file_path = tmp_dir
+                    .path()
+                    .join(file_name)
+                    .to_str()
+                    .expect("failed to convert tmp_dir path to str")
+                    .to_string();
+                JiraDatabase::new(file_path.clone())
+                    .database
+                    .write_db(&DBState::new())
+                    .unwrap();
+
+                let writers = (0..4)
+                    .map(|_| {
+                        let file_path = file_path.clone();
+                        std::thread::spawn(move || {
+                            for _ in 0..10 {
+                                JiraDatabase::new(file_path.clone())
+                                    .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                                    .unwrap();
+                            }
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                for writer in writers {
+                    writer.join().unwrap();
+                }
+
+                let db_state = JiraDatabase::new(file_path).read_db().unwrap();
+
+                assert_eq!(db_state.epics.len(), 40);
+                assert_eq!(db_state.last_item_id, 40);
+            }
+        }
+
+        // copies tests/fixtures/db.v<version>.json into a temporary directory
+        fn fixture_database(version: u32) -> (JSONFileDatabase, tempfile::TempDir) {
+            let tmp_dir = tempfile::tempdir().unwrap();
+            let file_path = tmp_dir.path().join("db.json");
+            fs::copy(
+                format!(
+                    "{}/tests/fixtures/db.v{}.json",
+                    env!("CARGO_MANIFEST_DIR"),
+                    version
+                ),
+                &file_path,
+            )
+            .expect("missing fixture for schema version");
+
+            let db = JSONFileDatabase {
+                file_path: file_path
+                    .to_str()
+                    .expect("failed to convert tmp_dir path to str")
+                    .to_string(),
+            };
+            (db, tmp_dir)
+        }
+
+        #[test]
+        fn read_db_should_migrate_every_schema_version() {
+            for version in 0..=CURRENT_SCHEMA_VERSION {
+                let (db, _tmp_dir) = fixture_database(version);
+
+                let db_state = db.read_db().unwrap();
+
+                assert_eq!(db_state.schema_version, CURRENT_SCHEMA_VERSION);
+                assert_eq!(db_state.last_item_id, 3);
+                assert_eq!(db_state.epics[&1].name, "Epic - Project 1");
+                assert_eq!(db_state.epics[&1].stories, vec![2, 3]);
+                assert_eq!(db_state.epics[&1].status, Status::InProgress);
+                assert_eq!(db_state.stories[&2].status, Status::InProgress);
+                assert_eq!(db_state.stories[&3].name, "Story - Project 1 README");
+                assert_eq!(db_state.stories[&3].status, Status::Open);
+            }
+        }
+
+        #[test]
+        fn read_db_should_migrate_v0_to_v1() {
+            let (db, _tmp_dir) = fixture_database(0);
+
+            let db_state = db.read_db().unwrap();
+
+            assert_eq!(db_state.revision, 0);
+        }
+
+        #[test]
+        fn read_db_should_migrate_v1_to_v2() {
+            let (db, _tmp_dir) = fixture_database(1);
+
+            let db_state = db.read_db().unwrap();
+
+            assert_eq!(db_state.stories[&2].points, None);
+            assert_eq!(db_state.stories[&3].points, None);
+        }
+
+        #[test]
+        fn read_db_should_migrate_v2_to_v3() {
+            let (db, _tmp_dir) = fixture_database(2);
+
+            let db_state = db.read_db().unwrap();
+
+            assert_eq!(db_state.epics[&1].priority, Priority::Major);
+            assert_eq!(db_state.stories[&2].priority, Priority::Major);
+            assert_eq!(db_state.stories[&2].points, Some(3));
+        }
+
+        #[test]
+        fn read_db_should_migrate_v3_to_v4() {
+            let (db, _tmp_dir) = fixture_database(3);
+
+            let db_state = db.read_db().unwrap();
+
+            assert!(db_state.users.is_empty());
+            assert_eq!(db_state.epics[&1].owner, None);
+            assert_eq!(db_state.stories[&2].assignee, None);
+            assert_eq!(db_state.stories[&2].reporter, None);
+            assert_eq!(db_state.stories[&2].priority, Priority::Blocker);
+        }
+
+        #[test]
+        fn read_db_should_migrate_v4_to_v5() {
+            let (db, _tmp_dir) = fixture_database(4);
+
+            let db_state = db.read_db().unwrap();
+
+            assert!(db_state.epics[&1].labels.is_empty());
+            assert!(db_state.stories[&2].labels.is_empty());
+            assert_eq!(db_state.stories[&2].assignee, Some("alice".to_owned()));
+        }
+
+        #[test]
+        fn read_db_should_migrate_v5_to_v6() {
+            let (db, _tmp_dir) = fixture_database(5);
+
+            let db_state = db.read_db().unwrap();
+
+            assert_eq!(db_state.epics[&1].created_at, None);
+            assert_eq!(db_state.stories[&2].updated_at, None);
+            assert_eq!(db_state.stories[&3].due, None);
+            assert_eq!(
+                db_state.epics[&1].labels,
+                BTreeSet::from(["frontend".to_owned()])
+            );
+        }
+
+        #[test]
+        fn read_db_should_migrate_v6_to_v7() {
+            let (db, _tmp_dir) = fixture_database(6);
+
+            let db_state = db.read_db().unwrap();
+
+            assert!(db_state.epics[&1].comments.is_empty());
+            assert!(db_state.stories[&2].comments.is_empty());
+            assert_eq!(db_state.stories[&3].due, "2024-04-15".parse().ok());
+        }
+
+        #[test]
+        fn read_db_should_migrate_v7_to_v8() {
+            let (db, _tmp_dir) = fixture_database(7);
+
+            let db_state = db.read_db().unwrap();
+
+            assert!(db_state.subtasks.is_empty());
+            assert!(db_state.stories[&2].subtasks.is_empty());
+            assert_eq!(db_state.stories[&2].comments.len(), 1);
+        }
+
+        #[test]
+        fn read_db_should_migrate_v8_to_v9() {
+            let (db, _tmp_dir) = fixture_database(8);
+
+            let db_state = db.read_db().unwrap();
+
+            assert_eq!(db_state.stories[&2].issue_type, IssueType::Story);
+            assert_eq!(db_state.stories[&3].bug, None);
+        }
+
+        #[test]
+        fn read_db_should_migrate_v9_to_v10() {
+            let (db, _tmp_dir) = fixture_database(9);
+
+            let db_state = db.read_db().unwrap();
+
+            assert!(db_state.stories[&2].links.is_empty());
+            assert_eq!(db_state.stories[&3].issue_type, IssueType::Bug);
+        }
+
+        #[test]
+        fn read_db_should_migrate_v10_to_v11() {
+            let (db, _tmp_dir) = fixture_database(10);
+
+            let db_state = db.read_db().unwrap();
+
+            assert!(db_state.sprints.is_empty());
+            assert_eq!(db_state.last_sprint_id, 0);
+            assert_eq!(db_state.stories[&3].links.len(), 1);
+        }
+
+        #[test]
+        fn read_db_should_migrate_v11_to_v12() {
+            let (db, _tmp_dir) = fixture_database(11);
+
+            let db_state = db.read_db().unwrap();
+
+            assert_eq!(db_state.stories[&2].state, None);
+            assert_eq!(db_state.stories[&3].state, None);
+        }
+
+        #[test]
+        fn read_db_should_not_modify_older_documents() {
+            let (db, _tmp_dir) = fixture_database(0);
+            let original = fs::read_to_string(&db.file_path).unwrap();
+
+            let result = db.read_db();
+
+            assert!(result.is_ok());
+            assert_eq!(fs::read_to_string(&db.file_path).unwrap(), original);
+        }
+
+        #[test]
+        fn read_db_should_fail_for_newer_schema_version() {
+            let (db, _tmp_dir) = fixture_database(CURRENT_SCHEMA_VERSION);
+            let mut document: Value =
+                serde_json::from_str(&fs::read_to_string(&db.file_path).unwrap()).unwrap();
+            document["schema_version"] = json!(CURRENT_SCHEMA_VERSION + 1);
+            fs::write(&db.file_path, document.to_string()).unwrap();
+
+            let result = db.read_db();
+
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn write_db_should_back_up_documents_from_older_schema() {
+            let (db, _tmp_dir) = fixture_database(0);
+            let original = fs::read_to_string(&db.file_path).unwrap();
+            let backup_path = format!("{}.v0.bak", db.file_path);
+
+            let db_state = db.read_db().unwrap();
+            let write_result = db.write_db(&db_state);
+
+            assert!(write_result.is_ok());
+            assert_eq!(fs::read_to_string(&backup_path).unwrap(), original);
+
+            let document: Value =
+                serde_json::from_str(&fs::read_to_string(&db.file_path).unwrap()).unwrap();
+            assert_eq!(document["schema_version"], json!(CURRENT_SCHEMA_VERSION));
+
+            let db_state = db.read_db().unwrap();
+            let write_result = db.write_db(&db_state);
+
+            assert!(write_result.is_ok());
+            assert_eq!(fs::read_to_string(&backup_path).unwrap(), original);
+        }
+
+        #[test]
+        fn write_db_should_not_back_up_current_documents() {
+            let (db, tmp_dir) = fixture_database(CURRENT_SCHEMA_VERSION);
+
+            let db_state = db.read_db().unwrap();
+            let write_result = db.write_db(&db_state);
+
+            assert!(write_result.is_ok());
+            assert_eq!(fs::read_dir(tmp_dir.path()).unwrap().count(), 1);
         }
     }
 }
@@ -1,5 +1,10 @@
 #![allow(unused)]
-use std::io;
+use anyhow::{anyhow, bail, Result};
+use std::{
+    env, fs,
+    io::{self, Write},
+    process::Command,
+};
 
 pub fn get_user_input() -> String {
     let mut user_input = String::new();
@@ -12,3 +17,59 @@ pub fn get_user_input() -> String {
 pub fn wait_for_key_press() {
     io::stdin().read_line(&mut String::new()).unwrap();
 }
+
+// opens $VISUAL or $EDITOR on a temporary file holding `initial` and returns the saved text
+pub fn compose_in_editor(initial: &str) -> Result<String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .map_err(|_| anyhow!("Set $VISUAL or $EDITOR to compose text in an editor!"))?;
+
+    compose_with(&editor, initial)
+}
+
+fn compose_with(editor: &str, initial: &str) -> Result<String> {
+    let mut file = tempfile::Builder::new().suffix(".txt").tempfile()?;
+    file.write_all(initial.as_bytes())?;
+    file.flush()?;
+
+    // editors are often configured with arguments, e.g. "code --wait"
+    let mut command = editor.split_whitespace();
+    let program = command
+        .next()
+        .ok_or_else(|| anyhow!("Set $VISUAL or $EDITOR to compose text in an editor!"))?;
+
+    let status = Command::new(program)
+        .args(command)
+        .arg(file.path())
+        .status()?;
+    if !status.success() {
+        bail!("{} exited with {}", editor, status);
+    }
+
+    Ok(fs::read_to_string(file.path())?.trim_end().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compose_with_should_return_the_edited_text() {
+        // a stand-in editor that rewrites the file it is given
+        let mut editor = tempfile::Builder::new().suffix(".sh").tempfile().unwrap();
+        write!(editor, "printf 'final\\n- first criterion\\n' > \"$1\"").unwrap();
+        editor.flush().unwrap();
+
+        let text = compose_with(
+            &format!("sh {}", editor.path().display()),
+            "draft\n- first criterion\n",
+        )
+        .unwrap();
+        assert_eq!(text, "final\n- first criterion");
+    }
+
+    #[test]
+    fn compose_with_should_error_if_the_editor_fails() {
+        assert!(compose_with("false", "").is_err());
+    }
+}
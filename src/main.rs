@@ -1,4 +1,12 @@
-use std::rc::Rc;
+use std::{io, process, rc::Rc};
+
+use clap::Parser;
+
+mod cli;
+use cli::Cli;
+
+mod config;
+use config::Config;
 
 mod models;
 
@@ -14,8 +22,29 @@ mod navigator;
 use navigator::*;
 
 fn main() {
-    // TODO: create database and navigator
-    let db = Rc::new(JiraDatabase::new("data/db.json".to_owned()));
+    let cli = Cli::parse();
+
+    let mut db = JiraDatabase::new(cli.db.clone());
+    db.config = match Config::load(&cli.config_path()) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("Error loading config: {}", error);
+            process::exit(1);
+        }
+    };
+
+    match cli.command {
+        Some(command) => {
+            if let Err(error) = cli::run(command, cli.format, &db, &mut io::stdout()) {
+                eprintln!("Error: {}", error);
+                process::exit(1);
+            }
+        }
+        None => run_pages(Rc::new(db)),
+    }
+}
+
+fn run_pages(db: Rc<JiraDatabase>) {
     let mut nav = Navigator::new(Rc::clone(&db));
 
     loop {
@@ -1,16 +1,56 @@
 #![allow(dead_code, unused_imports)]
+use chrono::{DateTime, NaiveDate, Utc};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt::Display,
+};
 
-#[derive(Debug, PartialEq, Eq)]
+use crate::config::EpicRollup;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Action {
     NavigateToEpicDetail { epic_id: usize },
     NavigateToStoryDetail { epic_id: usize, story_id: usize },
+    NavigateToSubtaskDetail { story_id: usize, subtask_id: usize },
     NavigateToPreviousPage,
     GetEpicName { epic_id: usize },
     GetEpicDescription { epic_id: usize },
     GetStoryName { story_id: usize },
     GetStoryDescription { story_id: usize },
+    EditEpicName { epic_id: usize },
+    EditEpicDescription { epic_id: usize },
+    EditStoryName { story_id: usize },
+    EditStoryDescription { story_id: usize },
+    EditStoryPoints { story_id: usize },
+    EditEpicPriority { epic_id: usize },
+    EditStoryPriority { story_id: usize },
+    EditStoryType { story_id: usize },
+    EditBugReport { story_id: usize },
+    LinkStory { story_id: usize },
+    UnlinkStory { story_id: usize },
+    MoveStory { story_id: usize },
+    NavigateToSprints,
+    NavigateToSprintPlanning { sprint_id: usize },
+    NavigateToActiveSprint,
+    CreateSprint,
+    AddStoryToSprint { sprint_id: usize, story_id: usize },
+    RemoveStoryFromSprint { sprint_id: usize, story_id: usize },
+    StartSprint { sprint_id: usize },
+    CompleteSprint { sprint_id: usize },
+    // the board of one epic, or of the whole project without one
+    NavigateToBoard { epic_id: Option<usize> },
+    MoveCard { story_id: usize, state: String },
+    AssignEpic { epic_id: usize },
+    AssignStory { story_id: usize },
+    NavigateToMyWork,
+    EditEpicDueDate { epic_id: usize },
+    EditStoryDueDate { story_id: usize },
+    AddComment { item_id: usize },
+    AddLabel { item_id: usize },
+    RemoveLabel { item_id: usize },
+    NavigateToHistory { item_id: usize },
     CreateEpic,
     CloseEpic { epic_id: usize },
     ReopenEpic { epic_id: usize },
@@ -18,9 +58,74 @@ pub enum Action {
     CreateStory { epic_id: usize },
     UpdateStoryStatus { epic_id: usize, story_id: usize },
     DeleteStory { epic_id: usize, story_id: usize },
+    CreateSubtask { story_id: usize },
+    UpdateSubtaskStatus { subtask_id: usize },
+    DeleteSubtask { story_id: usize, subtask_id: usize },
+    Undo,
+    Redo,
     Exit,
 }
 
+impl Action {
+    // whether the action may change the database and should be recorded in the undo history
+    pub fn is_undoable(&self) -> bool {
+        match self {
+            Self::CreateEpic
+            | Self::CloseEpic { .. }
+            | Self::ReopenEpic { .. }
+            | Self::DeleteEpic { .. }
+            | Self::CreateStory { .. }
+            | Self::UpdateStoryStatus { .. }
+            | Self::DeleteStory { .. }
+            | Self::CreateSubtask { .. }
+            | Self::UpdateSubtaskStatus { .. }
+            | Self::DeleteSubtask { .. }
+            | Self::EditEpicName { .. }
+            | Self::EditEpicDescription { .. }
+            | Self::EditStoryName { .. }
+            | Self::EditStoryDescription { .. }
+            | Self::EditStoryPoints { .. }
+            | Self::EditEpicPriority { .. }
+            | Self::EditStoryPriority { .. }
+            | Self::EditStoryType { .. }
+            | Self::EditBugReport { .. }
+            | Self::LinkStory { .. }
+            | Self::UnlinkStory { .. }
+            | Self::MoveStory { .. }
+            | Self::CreateSprint
+            | Self::AddStoryToSprint { .. }
+            | Self::RemoveStoryFromSprint { .. }
+            | Self::StartSprint { .. }
+            | Self::CompleteSprint { .. }
+            | Self::MoveCard { .. }
+            | Self::AssignEpic { .. }
+            | Self::AssignStory { .. }
+            | Self::EditEpicDueDate { .. }
+            | Self::EditStoryDueDate { .. }
+            | Self::AddComment { .. }
+            | Self::AddLabel { .. }
+            | Self::RemoveLabel { .. } => true,
+            Self::NavigateToEpicDetail { .. }
+            | Self::NavigateToStoryDetail { .. }
+            | Self::NavigateToSubtaskDetail { .. }
+            | Self::NavigateToPreviousPage
+            | Self::GetEpicName { .. }
+            | Self::GetEpicDescription { .. }
+            | Self::GetStoryName { .. }
+            | Self::GetStoryDescription { .. }
+            | Self::NavigateToHistory { .. }
+            | Self::NavigateToMyWork
+            | Self::NavigateToSprints
+            | Self::NavigateToSprintPlanning { .. }
+            | Self::NavigateToActiveSprint
+            | Self::NavigateToBoard { .. }
+            | Self::Undo
+            | Self::Redo
+            | Self::Exit => false,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Status {
     Open,
@@ -29,6 +134,11 @@ pub enum Status {
     Closed,
 }
 
+impl Status {
+    // in workflow order, as the columns of the board
+    pub const ALL: [Status; 4] = [Self::Open, Self::InProgress, Self::Resolved, Self::Closed];
+}
+
 impl Display for Status {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         return match self {
@@ -48,22 +158,252 @@ impl Display for Status {
     }
 }
 
+// declared from most to least urgent, so sorting by priority puts blockers first
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Priority {
+    Blocker,
+    Critical,
+    #[default]
+    Major,
+    Minor,
+    Trivial,
+}
+
+impl Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Blocker => write!(f, "BLOCKER"),
+            Self::Critical => write!(f, "CRITICAL"),
+            Self::Major => write!(f, "MAJOR"),
+            Self::Minor => write!(f, "MINOR"),
+            Self::Trivial => write!(f, "TRIVIAL"),
+        }
+    }
+}
+
+// what kind of work a child of an epic is, every one of them is stored as a Story
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IssueType {
+    Bug,
+    Task,
+    #[default]
+    Story,
+    Spike,
+}
+
+impl IssueType {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.to_lowercase().as_str() {
+            "bug" => Some(Self::Bug),
+            "task" => Some(Self::Task),
+            "story" => Some(Self::Story),
+            "spike" => Some(Self::Spike),
+            _ => None,
+        }
+    }
+}
+
+impl Display for IssueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bug => write!(f, "BUG"),
+            Self::Task => write!(f, "TASK"),
+            Self::Story => write!(f, "STORY"),
+            Self::Spike => write!(f, "SPIKE"),
+        }
+    }
+}
+
+// the kinds of links between stories, stored on the story the link starts from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum LinkKind {
+    Blocks,
+    RelatesTo,
+}
+
+impl LinkKind {
+    // how the link reads from the story it starts from
+    pub fn outbound_label(&self) -> &'static str {
+        match self {
+            Self::Blocks => "blocks",
+            Self::RelatesTo => "relates to",
+        }
+    }
+
+    // how the link reads from the story it points at
+    pub fn inbound_label(&self) -> &'static str {
+        match self {
+            Self::Blocks => "is blocked by",
+            Self::RelatesTo => "relates to",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Link {
+    pub kind: LinkKind,
+    pub target: usize,
+}
+
+// how badly a bug hurts, declared from worst to mildest
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Severity {
+    Critical,
+    #[default]
+    Major,
+    Minor,
+    Cosmetic,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Critical => write!(f, "CRITICAL"),
+            Self::Major => write!(f, "MAJOR"),
+            Self::Minor => write!(f, "MINOR"),
+            Self::Cosmetic => write!(f, "COSMETIC"),
+        }
+    }
+}
+
+// the details only bugs carry
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BugReport {
+    pub severity: Severity,
+    pub steps_to_reproduce: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Id,
+    Priority,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventKind {
+    Created,
+    Deleted,
+    StatusChanged {
+        from: Status,
+        to: Status,
+    },
+    FieldChanged {
+        field: String,
+        from: String,
+        to: String,
+    },
+    Closed,
+    Reopened,
+}
+
+impl Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Created => write!(f, "created"),
+            Self::Deleted => write!(f, "deleted"),
+            Self::StatusChanged { from, to } => write!(f, "status changed from {} to {}", from, to),
+            Self::FieldChanged { field, from, to } => {
+                write!(f, "{} changed from '{}' to '{}'", field, from, to)
+            }
+            Self::Closed => write!(f, "closed"),
+            Self::Reopened => write!(f, "reopened"),
+        }
+    }
+}
+
+// a single entry of the append-only audit log. Epics and stories share one id space, so the
+// item id alone identifies what was changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    pub timestamp: DateTime<Utc>,
+    pub actor: String,
+    pub item_id: usize,
+    pub kind: EventKind,
+}
+
+// the smallest unit of work, owned by a single story
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Subtask {
+    pub name: String,
+    pub description: String,
+    pub status: Status,
+}
+
+impl Subtask {
+    pub fn new(name: String, description: String) -> Self {
+        Self {
+            name,
+            description,
+            status: Status::Open,
+        }
+    }
+
+    // fields besides status whose changes are recorded in the audit log
+    pub fn audited_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("name", self.name.clone()),
+            ("description", self.description.clone()),
+        ]
+    }
+}
+
+// a comment on an epic or story, oldest first in the item's thread
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Comment {
+    pub author: String,
+    pub timestamp: DateTime<Utc>,
+    pub body: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Epic {
     pub name: String,
     pub description: String,
     pub stories: Vec<usize>,
     pub status: Status,
+    pub priority: Priority,
+    // username of the person responsible for the epic
+    pub owner: Option<String>,
+    pub labels: BTreeSet<String>,
+    // set by JiraDatabase, None for items stored before they were recorded
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub due: Option<NaiveDate>,
+    pub comments: Vec<Comment>,
 }
 
 impl Epic {
     pub fn new(name: String, description: String) -> Self {
-        return Epic {
+        Epic {
             name,
             description,
             stories: vec![],
             status: Status::Open,
-        };
+            priority: Priority::default(),
+            owner: None,
+            labels: BTreeSet::new(),
+            created_at: None,
+            updated_at: None,
+            due: None,
+            comments: vec![],
+        }
+    }
+
+    // fields besides status whose changes are recorded in the audit log
+    pub fn audited_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("name", self.name.clone()),
+            ("description", self.description.clone()),
+            ("priority", self.priority.to_string()),
+            ("owner", self.owner.clone().unwrap_or_default()),
+            ("labels", join_labels(&self.labels)),
+            ("due", format_due(self.due)),
+        ]
     }
 }
 
@@ -72,68 +412,491 @@ pub struct Story {
     pub name: String,
     pub description: String,
     pub status: Status,
+    // story point estimate, None while unestimated
+    pub points: Option<u32>,
+    pub priority: Priority,
+    // usernames of who works on the story and who filed it
+    pub assignee: Option<String>,
+    pub reporter: Option<String>,
+    pub labels: BTreeSet<String>,
+    // set by JiraDatabase, None for items stored before they were recorded
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub due: Option<NaiveDate>,
+    pub comments: Vec<Comment>,
+    // ids of the story's sub-tasks, in the order they were added
+    pub subtasks: Vec<usize>,
+    pub issue_type: IssueType,
+    // set exactly when the issue is a bug, see Story::set_issue_type
+    pub bug: Option<BugReport>,
+    // outbound links to other stories, in the order they were added
+    pub links: Vec<Link>,
+    // name of the story's workflow state, None for the first state of its status
+    pub state: Option<String>,
 }
 
 impl Story {
     pub fn new(name: String, description: String) -> Self {
-        return Story {
+        Story {
             name,
             description,
             status: Status::Open,
-        };
+            points: None,
+            priority: Priority::default(),
+            assignee: None,
+            reporter: None,
+            labels: BTreeSet::new(),
+            created_at: None,
+            updated_at: None,
+            due: None,
+            comments: vec![],
+            subtasks: vec![],
+            issue_type: IssueType::default(),
+            bug: None,
+            links: vec![],
+            state: None,
+        }
+    }
+
+    // keeps the bug report in step with the type, bugs start out with an empty one
+    pub fn set_issue_type(&mut self, issue_type: IssueType) {
+        self.issue_type = issue_type;
+        match issue_type {
+            IssueType::Bug => {
+                self.bug.get_or_insert_with(BugReport::default);
+            }
+            IssueType::Task | IssueType::Story | IssueType::Spike => self.bug = None,
+        }
+    }
+
+    // fields besides status whose changes are recorded in the audit log
+    pub fn audited_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("name", self.name.clone()),
+            ("description", self.description.clone()),
+            (
+                "points",
+                self.points
+                    .map_or(String::new(), |points| points.to_string()),
+            ),
+            ("priority", self.priority.to_string()),
+            ("assignee", self.assignee.clone().unwrap_or_default()),
+            ("reporter", self.reporter.clone().unwrap_or_default()),
+            ("labels", join_labels(&self.labels)),
+            ("due", format_due(self.due)),
+            ("type", self.issue_type.to_string()),
+            (
+                "severity",
+                self.bug
+                    .as_ref()
+                    .map_or(String::new(), |bug| bug.severity.to_string()),
+            ),
+            (
+                "steps to reproduce",
+                self.bug
+                    .as_ref()
+                    .map_or(String::new(), |bug| bug.steps_to_reproduce.clone()),
+            ),
+            (
+                "links",
+                self.links
+                    .iter()
+                    .map(|link| format!("{} {}", link.kind.outbound_label(), link.target))
+                    .join(", "),
+            ),
+            ("state", self.state.clone().unwrap_or_default()),
+        ]
+    }
+}
+
+// sprints are planned, then run one at a time, and kept around once completed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SprintState {
+    #[default]
+    Planned,
+    Active,
+    Completed,
+}
+
+impl Display for SprintState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Planned => write!(f, "PLANNED"),
+            Self::Active => write!(f, "ACTIVE"),
+            Self::Completed => write!(f, "COMPLETED"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sprint {
+    pub name: String,
+    pub goal: String,
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+    pub state: SprintState,
+    // ids of the sprint's stories in the order they were pulled in. Completed sprints keep
+    // the stories finished during them.
+    pub stories: Vec<usize>,
+}
+
+impl Sprint {
+    pub fn new(name: String, goal: String) -> Self {
+        Self {
+            name,
+            goal,
+            start: None,
+            end: None,
+            state: SprintState::default(),
+            stories: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct User {
+    // display name, the registry is keyed by username
+    pub name: String,
+}
+
+// one line of an epic or story listing, shared by the pages and the command line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ItemRow {
+    pub key: String,
+    pub id: usize,
+    pub name: String,
+    pub description: String,
+    pub status: Status,
+    // a story's estimate, or the total estimate of an epic's stories
+    pub points: Option<u32>,
+    pub priority: Priority,
+    // a story's assignee or an epic's owner
+    pub assignee: Option<String>,
+    // space separated, labels never contain whitespace and this keeps the row flat for csv
+    pub labels: String,
+    pub due: Option<NaiveDate>,
+    // None for epics
+    pub issue_type: Option<IssueType>,
+}
+
+impl ItemRow {
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        is_overdue(self.due, self.status, today)
+    }
+}
+
+// resolved and closed items are done, so they can't be late anymore
+pub fn is_overdue(due: Option<NaiveDate>, status: Status, today: NaiveDate) -> bool {
+    match status {
+        Status::Resolved | Status::Closed => false,
+        Status::Open | Status::InProgress => due.is_some_and(|due| due < today),
     }
 }
 
+fn format_due(due: Option<NaiveDate>) -> String {
+    due.map_or(String::new(), |due| due.to_string())
+}
+
+pub fn join_labels(labels: &BTreeSet<String>) -> String {
+    labels.iter().join(" ")
+}
+
+// the status of an epic or story along with its other audited fields
+type AuditedItem = (Status, Vec<(&'static str, String)>);
+
+// bump whenever the serialized shape of DBState changes, and add the matching migration to
+// db::MIGRATIONS
+pub const CURRENT_SCHEMA_VERSION: u32 = 12;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DBState {
+    pub schema_version: u32,
     pub last_item_id: usize,
+    // sprints are numbered apart from issues
+    pub last_sprint_id: usize,
+    pub revision: u64,
     pub epics: HashMap<usize, Epic>,
     pub stories: HashMap<usize, Story>,
+    pub users: HashMap<String, User>,
+    pub subtasks: HashMap<usize, Subtask>,
+    pub sprints: HashMap<usize, Sprint>,
 }
 
 impl DBState {
     pub fn new() -> Self {
-        return Self {
+        Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
             last_item_id: 0,
+            last_sprint_id: 0,
+            revision: 0,
             epics: HashMap::new(),
             stories: HashMap::new(),
+            users: HashMap::new(),
+            subtasks: HashMap::new(),
+            sprints: HashMap::new(),
+        }
+    }
+
+    fn audited_items(&self) -> HashMap<usize, AuditedItem> {
+        self.epics
+            .iter()
+            .map(|(id, epic)| (*id, (epic.status, epic.audited_fields())))
+            .chain(
+                self.stories
+                    .iter()
+                    .map(|(id, story)| (*id, (story.status, story.audited_fields()))),
+            )
+            .chain(
+                self.subtasks
+                    .iter()
+                    .map(|(id, subtask)| (*id, (subtask.status, subtask.audited_fields()))),
+            )
+            .collect()
+    }
+
+    // stamps the epics and stories that were created or changed since `before`
+    pub fn touch_changed_items(&mut self, before: &DBState, now: DateTime<Utc>) {
+        for (id, epic) in self.epics.iter_mut() {
+            if before.epics.get(id) != Some(epic) {
+                epic.created_at.get_or_insert(now);
+                epic.updated_at = Some(now);
+            }
+        }
+        for (id, story) in self.stories.iter_mut() {
+            if before.stories.get(id) != Some(story) {
+                story.created_at.get_or_insert(now);
+                story.updated_at = Some(now);
+            }
+        }
+    }
+
+    // the labels of an epic or story
+    pub fn labels(&self, item_id: usize) -> Option<&BTreeSet<String>> {
+        match self.epics.get(&item_id) {
+            Some(epic) => Some(&epic.labels),
+            None => self.stories.get(&item_id).map(|story| &story.labels),
+        }
+    }
+
+    // the comment thread of an epic or story
+    pub fn comments(&self, item_id: usize) -> Option<&[Comment]> {
+        match self.epics.get(&item_id) {
+            Some(epic) => Some(&epic.comments),
+            None => self.stories.get(&item_id).map(|story| &story.comments[..]),
+        }
+    }
+
+    // the epic a story belongs to
+    pub fn story_epic(&self, story_id: usize) -> Option<usize> {
+        self.epics
+            .iter()
+            .find(|(_, epic)| epic.stories.contains(&story_id))
+            .map(|(id, _)| *id)
+    }
+
+    // total and completed story points of an epic's estimated stories
+    pub fn epic_points(&self, epic_id: usize) -> (u32, u32) {
+        self.epics[&epic_id]
+            .stories
+            .iter()
+            .map(|id| &self.stories[id])
+            .fold((0, 0), |(total, completed), story| {
+                let points = story.points.unwrap_or(0);
+                match story.status {
+                    Status::Resolved | Status::Closed => (total + points, completed + points),
+                    _ => (total + points, completed),
+                }
+            })
+    }
+
+    // links from other stories to this one, as (source story id, kind) ordered by source
+    pub fn inbound_links(&self, story_id: usize) -> Vec<(usize, LinkKind)> {
+        self.stories
+            .iter()
+            .flat_map(|(id, story)| {
+                story
+                    .links
+                    .iter()
+                    .filter(|link| link.target == story_id)
+                    .map(|link| (*id, link.kind))
+            })
+            .sorted()
+            .collect()
+    }
+
+    // stories blocking this one that aren't resolved or closed yet
+    pub fn open_blockers(&self, story_id: usize) -> Vec<usize> {
+        self.inbound_links(story_id)
+            .into_iter()
+            .filter(|(id, kind)| {
+                *kind == LinkKind::Blocks
+                    && matches!(self.stories[id].status, Status::Open | Status::InProgress)
+            })
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    // whether following "blocks" links from one story eventually reaches the other
+    pub fn blocks_transitively(&self, from: usize, to: usize) -> bool {
+        let mut pending = vec![from];
+        let mut visited = HashSet::new();
+        while let Some(story_id) = pending.pop() {
+            if story_id == to {
+                return true;
+            }
+            if !visited.insert(story_id) {
+                continue;
+            }
+            if let Some(story) = self.stories.get(&story_id) {
+                pending.extend(
+                    story
+                        .links
+                        .iter()
+                        .filter(|link| link.kind == LinkKind::Blocks)
+                        .map(|link| link.target),
+                );
+            }
+        }
+        false
+    }
+
+    // drops links and sprint entries pointing at stories that no longer exist
+    pub fn remove_dangling_references(&mut self) {
+        let story_ids: HashSet<usize> = self.stories.keys().copied().collect();
+        for story in self.stories.values_mut() {
+            story.links.retain(|link| story_ids.contains(&link.target));
+        }
+        for sprint in self.sprints.values_mut() {
+            sprint.stories.retain(|id| story_ids.contains(id));
+        }
+    }
+
+    pub fn active_sprint(&self) -> Option<usize> {
+        self.sprints
+            .iter()
+            .find(|(_, sprint)| sprint.state == SprintState::Active)
+            .map(|(id, _)| *id)
+    }
+
+    // the planned or active sprint a story has been pulled into, if any
+    pub fn story_sprint(&self, story_id: usize) -> Option<usize> {
+        self.sprints
+            .iter()
+            .find(|(_, sprint)| {
+                sprint.state != SprintState::Completed && sprint.stories.contains(&story_id)
+            })
+            .map(|(id, _)| *id)
+    }
+
+    // the story a sub-task belongs to
+    pub fn subtask_story(&self, subtask_id: usize) -> Option<usize> {
+        self.stories
+            .iter()
+            .find(|(_, story)| story.subtasks.contains(&subtask_id))
+            .map(|(id, _)| *id)
+    }
+
+    // the status a story's sub-tasks roll up to, like update_epic_status does for epics.
+    // Stories without sub-tasks have none and keep whatever status they were given.
+    pub fn subtask_rollup_status(&self, story_id: usize) -> Option<Status> {
+        let statuses = self.stories[&story_id]
+            .subtasks
+            .iter()
+            .map(|id| self.subtasks[id].status)
+            .collect_vec();
+        if statuses.is_empty() {
+            return None;
+        }
+
+        let status = if statuses.iter().all(|status| *status == Status::Closed) {
+            Status::Closed
+        } else if statuses
+            .iter()
+            .all(|status| matches!(status, Status::Resolved | Status::Closed))
+        {
+            Status::Resolved
+        } else if statuses.iter().all(|status| *status == Status::Open) {
+            Status::Open
+        } else {
+            Status::InProgress
         };
+        Some(status)
     }
 
-    pub fn update_epic_status(&mut self, epic_id: usize) {
-        let current_status = self.epics[&epic_id].status.clone();
-        let mut closed_count: usize = 0;
-        let mut resolved_count: usize = 0;
+    // the statuses of an epic's stories, in the epic's order
+    pub fn epic_story_statuses(&self, epic_id: usize) -> Vec<Status> {
+        self.epics[&epic_id]
+            .stories
+            .iter()
+            .map(|id| self.stories[id].status)
+            .collect()
+    }
 
-        if self.epics[&epic_id].stories.is_empty() {
-            self.epics.get_mut(&epic_id).unwrap().status = Status::InProgress;
+    // rolls the story statuses up into the epic as described on EpicRollup. Closed epics are
+    // left alone, see JiraDatabase::reopen_epic.
+    pub fn update_epic_status(&mut self, epic_id: usize, rollup: &EpicRollup) {
+        if self.epics[&epic_id].status == Status::Closed {
             return;
         }
+        let status = rollup.status(&self.epic_story_statuses(epic_id));
+        self.epics.get_mut(&epic_id).unwrap().status = status;
+    }
+}
+
+// works out what happened to each epic and story between two states of the database
+pub fn diff_events(before: &DBState, after: &DBState) -> Vec<(usize, EventKind)> {
+    let mut events = vec![];
 
-        for story_id in &self.epics[&epic_id].stories {
-            let story = &self.stories[&story_id];
-            if story.status == current_status {
+    let before_items = before.audited_items();
+    let after_items = after.audited_items();
+
+    for (id, (status, fields)) in after_items.iter().sorted_by_key(|(id, _)| **id) {
+        let (previous_status, previous_fields) = match before_items.get(id) {
+            Some(previous) => previous,
+            None => {
+                events.push((*id, EventKind::Created));
                 continue;
             }
+        };
 
-            match story.status {
-                Status::InProgress => {
-                    self.epics.get_mut(&epic_id).unwrap().status = Status::InProgress;
-                    break;
-                }
-                Status::Closed => closed_count += 1,
-                Status::Resolved => resolved_count += 1,
-                _ => continue,
+        for ((field, previous_value), (_, value)) in previous_fields.iter().zip(fields) {
+            // a new workflow state of another status is recorded as the status change below
+            let status_changed = previous_status != status;
+            if previous_value != value && !(*field == "state" && status_changed) {
+                events.push((
+                    *id,
+                    EventKind::FieldChanged {
+                        field: field.to_string(),
+                        from: previous_value.clone(),
+                        to: value.clone(),
+                    },
+                ));
             }
         }
 
-        if (resolved_count + closed_count) as usize == self.epics[&epic_id].stories.len() {
-            self.epics.get_mut(&epic_id).unwrap().status = Status::Resolved;
-            return;
+        match previous_status {
+            previous_status if previous_status == status => {}
+            Status::Closed if after.epics.contains_key(id) => {
+                events.push((*id, EventKind::Reopened))
+            }
+            _ if *status == Status::Closed && after.epics.contains_key(id) => {
+                events.push((*id, EventKind::Closed))
+            }
+            previous_status => events.push((
+                *id,
+                EventKind::StatusChanged {
+                    from: *previous_status,
+                    to: *status,
+                },
+            )),
         }
+    }
 
-        if closed_count as usize == self.epics[&epic_id].stories.len() {
-            self.epics.get_mut(&epic_id).unwrap().status = Status::Closed;
+    for id in before_items.keys().sorted() {
+        if !after_items.contains_key(id) {
+            events.push((*id, EventKind::Deleted));
         }
     }
+
+    events
 }
@@ -1,26 +1,50 @@
 #![allow(unused)]
 use crate::io_utils::*;
-use anyhow::{Ok, Result};
+use anyhow::{anyhow, Ok, Result};
+use itertools::Itertools;
 use std::rc::Rc;
 
 use crate::{
-    db::JiraDatabase,
-    models::{Action, Status},
-    ui::{EpicDetail, HomePage, Page, Prompts, StoryDetail},
+    db::{BlockedError, ConflictError, JiraDatabase},
+    models::{Action, BugReport, DBState, IssueType, SprintState, Status},
+    ui::{
+        ActiveSprintPage, BoardPage, EpicDetail, HistoryPage, HomePage, MyWorkPage, Page, Prompts,
+        SprintPlanning, SprintsPage, StoryDetail, SubtaskDetail,
+    },
 };
 
+// how many actions can be undone. The history lives in memory and only covers this session's
+// own actions; it isn't written next to the database, so it ends when the app exits. Pages
+// take "undo" rather than "u", which several of them already use for other commands.
+const HISTORY_LIMIT: usize = 100;
+
+// a snapshot to go back to, along with the revision this session's own last write left the
+// database at. Other sessions may share the database, so restoring is only safe while the
+// revision is unchanged; otherwise their later changes would be thrown away.
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    snapshot: DBState,
+    revision: u64,
+}
+
 pub struct Navigator {
     pages: Vec<Box<dyn Page>>,
     prompts: Prompts,
     db: Rc<JiraDatabase>,
+    // snapshots of the database taken before each undoable action
+    undo_stack: Vec<HistoryEntry>,
+    // snapshots of the database taken before each undo
+    redo_stack: Vec<HistoryEntry>,
 }
 
 impl Navigator {
     pub fn new(db: Rc<JiraDatabase>) -> Self {
         return Self {
-            pages: vec![Box::new(HomePage { db: Rc::clone(&db) })],
+            pages: vec![Box::new(HomePage::new(Rc::clone(&db)))],
             prompts: Prompts::new(),
             db,
+            undo_stack: vec![],
+            redo_stack: vec![],
         };
     }
 
@@ -30,13 +54,106 @@ impl Navigator {
     }
 
     pub fn handle_action(&mut self, action: Action) -> Result<()> {
+        loop {
+            // read straight from the backend so the revision this session last saw is kept
+            let snapshot = match action.is_undoable() {
+                true => Some(self.db.database.read_db()?),
+                false => None,
+            };
+
+            match self.perform_action(action.clone()) {
+                Err(error) if error.is::<ConflictError>() => {
+                    // someone else changed the database since the current page was drawn
+                    if (self.prompts.reload_and_retry)() {
+                        self.db.view_db()?;
+                        continue;
+                    }
+                    println!("Cancelled!\nPress Enter to continue...");
+                    wait_for_key_press();
+                    return Ok(());
+                }
+                Err(error) => return Err(error),
+                Result::Ok(()) => {
+                    if let Some(snapshot) = snapshot {
+                        self.record_undo(snapshot)?;
+                    }
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn record_undo(&mut self, snapshot: DBState) -> Result<()> {
+        // cancelled prompts leave the database untouched and there is nothing to undo
+        let revision = self.db.database.read_db()?.revision;
+        if revision == snapshot.revision {
+            return Ok(());
+        }
+
+        self.undo_stack.push(HistoryEntry { snapshot, revision });
+        if self.undo_stack.len() > HISTORY_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+
+        Ok(())
+    }
+
+    // restores the entry's snapshot and returns the entry that reverts the restore again. Returns
+    // None instead when someone else wrote to the database after the entry was recorded.
+    fn restore_entry(&self, entry: &HistoryEntry) -> Result<Option<HistoryEntry>> {
+        let current_state = self.db.read_db()?;
+        if current_state.revision != entry.revision {
+            return Ok(None);
+        }
+
+        self.db.restore(entry.snapshot.clone())?;
+        Ok(Some(HistoryEntry {
+            snapshot: current_state,
+            revision: self.db.database.read_db()?.revision,
+        }))
+    }
+
+    // undo and redo can remove the epic or story a page is showing, so drop those pages
+    fn remove_missing_pages(&mut self) -> Result<()> {
+        let db_state = self.db.read_db()?;
+
+        let first_missing_page = self.pages.iter().position(|page| {
+            let page = page.as_any();
+            if let Some(epic_detail) = page.downcast_ref::<EpicDetail>() {
+                return !db_state.epics.contains_key(&epic_detail.epic_id);
+            }
+            if let Some(story_detail) = page.downcast_ref::<StoryDetail>() {
+                return !db_state.stories.contains_key(&story_detail.story_id);
+            }
+            if let Some(subtask_detail) = page.downcast_ref::<SubtaskDetail>() {
+                return !db_state.subtasks.contains_key(&subtask_detail.subtask_id);
+            }
+            if let Some(BoardPage {
+                epic_id: Some(epic_id),
+                ..
+            }) = page.downcast_ref::<BoardPage>()
+            {
+                return !db_state.epics.contains_key(epic_id);
+            }
+            if let Some(sprint_planning) = page.downcast_ref::<SprintPlanning>() {
+                return !db_state.sprints.contains_key(&sprint_planning.sprint_id);
+            }
+            false
+        });
+
+        if let Some(index) = first_missing_page {
+            self.pages.truncate(index);
+        }
+
+        Ok(())
+    }
+
+    fn perform_action(&mut self, action: Action) -> Result<()> {
         match action {
             Action::NavigateToEpicDetail { epic_id } => {
                 // create a new EpicDetail instance and add it to the pages vector
-                let epic_page = EpicDetail {
-                    epic_id,
-                    db: self.db.clone(),
-                };
+                let epic_page = EpicDetail::new(epic_id, self.db.clone());
                 self.pages.push(Box::new(epic_page));
             }
             Action::NavigateToStoryDetail { epic_id, story_id } => {
@@ -48,30 +165,251 @@ impl Navigator {
                 };
                 self.pages.push(Box::new(story_page));
             }
+            Action::NavigateToSubtaskDetail {
+                story_id,
+                subtask_id,
+            } => {
+                let subtask_page = SubtaskDetail {
+                    story_id,
+                    subtask_id,
+                    db: self.db.clone(),
+                };
+                self.pages.push(Box::new(subtask_page));
+            }
+            Action::NavigateToHistory { item_id } => {
+                let history_page = HistoryPage {
+                    item_id,
+                    db: self.db.clone(),
+                };
+                self.pages.push(Box::new(history_page));
+            }
+            Action::NavigateToMyWork => {
+                let my_work_page = MyWorkPage {
+                    db: self.db.clone(),
+                };
+                self.pages.push(Box::new(my_work_page));
+            }
+            Action::NavigateToSprints => {
+                let sprints_page = SprintsPage {
+                    db: self.db.clone(),
+                };
+                self.pages.push(Box::new(sprints_page));
+            }
+            Action::NavigateToSprintPlanning { sprint_id } => {
+                let planning_page = SprintPlanning {
+                    sprint_id,
+                    db: self.db.clone(),
+                };
+                self.pages.push(Box::new(planning_page));
+            }
+            Action::NavigateToActiveSprint => {
+                let active_sprint_page = ActiveSprintPage {
+                    db: self.db.clone(),
+                };
+                self.pages.push(Box::new(active_sprint_page));
+            }
+            Action::NavigateToBoard { epic_id } => {
+                let board_page = BoardPage {
+                    epic_id,
+                    db: self.db.clone(),
+                };
+                self.pages.push(Box::new(board_page));
+            }
             Action::NavigateToPreviousPage => {
                 // remove the last page from the pages vector
                 self.pages.pop();
             }
             Action::GetEpicName { epic_id } => {
-                let name = &self.db.read_db()?.epics[&epic_id].name;
+                let name = self.db.read_epic(epic_id)?.name;
                 println!("Name: {}\nPress Enter to continue...", name);
                 wait_for_key_press();
             }
             Action::GetEpicDescription { epic_id } => {
-                let description = &self.db.read_db()?.epics[&epic_id].description;
+                let description = self.db.read_epic(epic_id)?.description;
                 println!("Description: {}\nPress Enter to continue...", description);
                 wait_for_key_press();
             }
             Action::GetStoryName { story_id } => {
-                let name = &self.db.read_db()?.stories[&story_id].name;
+                let name = self.db.read_story(story_id)?.name;
                 println!("Name: {}\nPress Enter to continue...", name);
                 wait_for_key_press();
             }
             Action::GetStoryDescription { story_id } => {
-                let description = &self.db.read_db()?.stories[&story_id].description;
+                let description = self.db.read_story(story_id)?.description;
                 println!("Description: {}\nPress Enter to continue...", description);
                 wait_for_key_press();
             }
+            Action::EditEpicName { epic_id } => {
+                let epic = self.db.read_epic(epic_id)?;
+                let name = (self.prompts.edit_name)(&epic.name);
+                self.db.update_epic(epic_id, name, epic.description)?;
+            }
+            Action::EditEpicDescription { epic_id } => {
+                let epic = self.db.read_epic(epic_id)?;
+                let description = (self.prompts.edit_description)(&epic.description);
+                self.db.update_epic(epic_id, epic.name, description)?;
+            }
+            Action::EditStoryName { story_id } => {
+                let story = self.db.read_story(story_id)?;
+                let name = (self.prompts.edit_name)(&story.name);
+                self.db.update_story(story_id, name, story.description)?;
+            }
+            Action::EditStoryDescription { story_id } => {
+                let story = self.db.read_story(story_id)?;
+                let description = (self.prompts.edit_description)(&story.description);
+                self.db.update_story(story_id, story.name, description)?;
+            }
+            Action::EditStoryPoints { story_id } => {
+                let current = self.db.read_story(story_id)?.points;
+                let points = (self.prompts.estimate)(&self.db.config.point_scale, current);
+                self.db.update_story_points(story_id, points)?;
+            }
+            Action::EditEpicPriority { epic_id } => {
+                let current = self.db.read_epic(epic_id)?.priority;
+                let priority = (self.prompts.priority)(current);
+                self.db.update_epic_priority(epic_id, priority)?;
+            }
+            Action::EditStoryPriority { story_id } => {
+                let current = self.db.read_story(story_id)?.priority;
+                let priority = (self.prompts.priority)(current);
+                self.db.update_story_priority(story_id, priority)?;
+            }
+            Action::EditStoryType { story_id } => {
+                let current = self.db.read_story(story_id)?.issue_type;
+                let issue_type = (self.prompts.issue_type)(current);
+                self.db.update_story_type(story_id, issue_type)?;
+                // a new bug starts with an empty report, so ask for it right away
+                if issue_type == IssueType::Bug && current != IssueType::Bug {
+                    let bug = (self.prompts.bug_report)(&BugReport::default());
+                    self.db.update_bug_report(story_id, bug)?;
+                }
+            }
+            Action::EditBugReport { story_id } => match self.db.read_story(story_id)?.bug {
+                Some(current) => {
+                    let bug = (self.prompts.bug_report)(&current);
+                    self.db.update_bug_report(story_id, bug)?;
+                }
+                None => {
+                    println!("Only bugs have a severity and steps to reproduce!\nPress Enter to continue...");
+                    wait_for_key_press();
+                }
+            },
+            Action::LinkStory { story_id } => {
+                if let Some(input) = (self.prompts.link_story)() {
+                    let other_id = self
+                        .db
+                        .config
+                        .parse_issue_key(&input.key)
+                        .ok_or_else(|| anyhow!("Invalid issue key '{}'!", input.key))?;
+                    match input.inbound {
+                        true => self.db.link_stories(other_id, input.kind, story_id)?,
+                        false => self.db.link_stories(story_id, input.kind, other_id)?,
+                    }
+                }
+            }
+            Action::MoveStory { story_id } => {
+                let db_state = self.db.read_db()?;
+                let current_epic_id = db_state.story_epic(story_id);
+                let epic_keys = db_state
+                    .epics
+                    .iter()
+                    .filter(|(id, epic)| {
+                        Some(**id) != current_epic_id && epic.status != Status::Closed
+                    })
+                    .map(|(id, _)| *id)
+                    .sorted()
+                    .map(|id| self.db.config.issue_key(id))
+                    .collect::<Vec<_>>();
+                if let Some(key) = (self.prompts.move_story)(&epic_keys) {
+                    let epic_id = self
+                        .db
+                        .config
+                        .parse_issue_key(&key)
+                        .ok_or_else(|| anyhow!("Invalid issue key '{}'!", key))?;
+                    self.db.move_story(story_id, epic_id)?;
+                }
+            }
+            Action::UnlinkStory { story_id } => {
+                let db_state = self.db.read_db()?;
+                let linked_keys = db_state
+                    .stories
+                    .get(&story_id)
+                    .ok_or_else(|| anyhow!("Invalid Story ID!"))?
+                    .links
+                    .iter()
+                    .map(|link| link.target)
+                    .chain(
+                        db_state
+                            .inbound_links(story_id)
+                            .into_iter()
+                            .map(|(id, _)| id),
+                    )
+                    .unique()
+                    .map(|id| self.db.config.issue_key(id))
+                    .collect::<Vec<_>>();
+                if let Some(key) = (self.prompts.unlink_story)(&linked_keys) {
+                    let other_id = self
+                        .db
+                        .config
+                        .parse_issue_key(&key)
+                        .ok_or_else(|| anyhow!("Invalid issue key '{}'!", key))?;
+                    self.db.unlink_stories(story_id, other_id)?;
+                }
+            }
+            Action::EditEpicDueDate { epic_id } => {
+                let current = self.db.read_epic(epic_id)?.due;
+                let due = (self.prompts.due_date)(current);
+                self.db.update_epic_due_date(epic_id, due)?;
+            }
+            Action::EditStoryDueDate { story_id } => {
+                let current = self.db.read_story(story_id)?.due;
+                let due = (self.prompts.due_date)(current);
+                self.db.update_story_due_date(story_id, due)?;
+            }
+            Action::AssignEpic { epic_id } => {
+                let db_state = self.db.read_db()?;
+                let usernames = db_state.users.keys().cloned().sorted().collect::<Vec<_>>();
+                let epic = db_state
+                    .epics
+                    .get(&epic_id)
+                    .ok_or_else(|| anyhow!("Invalid Epic ID!"))?;
+                let owner = (self.prompts.assign)(&usernames, epic.owner.as_deref());
+                self.db.assign_epic(epic_id, owner)?;
+            }
+            Action::AssignStory { story_id } => {
+                let db_state = self.db.read_db()?;
+                let usernames = db_state.users.keys().cloned().sorted().collect::<Vec<_>>();
+                let story = db_state
+                    .stories
+                    .get(&story_id)
+                    .ok_or_else(|| anyhow!("Invalid Story ID!"))?;
+                let assignee = (self.prompts.assign)(&usernames, story.assignee.as_deref());
+                self.db.assign_story(story_id, assignee)?;
+            }
+            Action::AddComment { item_id } => {
+                let body = (self.prompts.comment)();
+                if !body.trim().is_empty() {
+                    self.db.add_comment(item_id, body)?;
+                }
+            }
+            Action::AddLabel { item_id } => {
+                let label = (self.prompts.add_label)();
+                if !label.trim().is_empty() {
+                    self.db.add_label(item_id, &label)?;
+                }
+            }
+            Action::RemoveLabel { item_id } => {
+                let db_state = self.db.read_db()?;
+                let labels = db_state
+                    .labels(item_id)
+                    .into_iter()
+                    .flatten()
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if let Some(label) = (self.prompts.remove_label)(&labels) {
+                    self.db.remove_label(item_id, &label)?;
+                }
+            }
             Action::CreateEpic => {
                 // prompt the user to create a new epic and persist it in the database
                 let new_epic = (self.prompts.create_epic)();
@@ -90,7 +428,7 @@ impl Navigator {
             }
             Action::ReopenEpic { epic_id } => {
                 if (self.prompts.reopen_epic)() {
-                    self.db.update_epic_status(epic_id)?;
+                    self.db.reopen_epic(epic_id)?;
                     println!("Epic was reopened!\nPress Enter to continue...");
                     wait_for_key_press();
                 } else {
@@ -112,23 +450,55 @@ impl Navigator {
             }
             Action::CreateStory { epic_id } => {
                 // prompt the user to create a new story and persist it in the database
-                let new_story = (self.prompts.create_story)();
+                let mut new_story = (self.prompts.create_story)();
+                new_story.points = (self.prompts.estimate)(&self.db.config.point_scale, None);
                 self.db.create_story(new_story, epic_id)?;
                 println!("Story was created!\nPress Enter to continue...");
                 wait_for_key_press();
             }
             Action::UpdateStoryStatus { epic_id, story_id } => {
                 // prompt the user to update status and persist it in the database
-                if &self.db.read_db()?.epics[&epic_id].status == &Status::Closed {
+                if self.db.read_epic(epic_id)?.status == Status::Closed {
                     println!("Cannot change the status of a Story from a closed Epic!\nPress Enter to continue...");
                     wait_for_key_press();
                 } else {
-                    let new_status = (self.prompts.update_status)();
-                    self.db.update_story_status(story_id, new_status)?;
-                    println!("Story status updated successfully!\nPress Enter to continue...");
+                    // only the transitions the workflow allows are offered
+                    let workflow = &self.db.config.workflow;
+                    let current = workflow.story_state(&self.db.read_story(story_id)?);
+                    let next_states = workflow.next_states(&current);
+                    match (self.prompts.transition)(&current, &next_states) {
+                        Some(new_state) => match self.db.update_story_state(story_id, &new_state) {
+                            Err(error) if error.is::<BlockedError>() => {
+                                // resolving a blocked story is allowed, but only once confirmed
+                                if (self.prompts.resolve_blocked)(&error.to_string()) {
+                                    self.db.force_story_state(story_id, &new_state)?;
+                                    println!("Story status updated successfully!\nPress Enter to continue...");
+                                } else {
+                                    println!("Cancelled!\nPress Enter to continue...");
+                                }
+                            }
+                            Err(error) => return Err(error),
+                            Result::Ok(()) => {
+                                println!(
+                                    "Story status updated successfully!\nPress Enter to continue..."
+                                )
+                            }
+                        },
+                        None => println!("Cancelled!\nPress Enter to continue..."),
+                    }
                     wait_for_key_press();
                 }
             }
+            Action::MoveCard { story_id, state } => {
+                match self.db.update_story_state(story_id, &state) {
+                    Err(error) if error.is::<BlockedError>() => {
+                        if (self.prompts.resolve_blocked)(&error.to_string()) {
+                            self.db.force_story_state(story_id, &state)?;
+                        }
+                    }
+                    result => result?,
+                }
+            }
             Action::DeleteStory { epic_id, story_id } => {
                 // prompt the user to delete the story and persist it in the database
                 if (self.prompts.delete_story)() {
@@ -141,6 +511,133 @@ impl Navigator {
                     wait_for_key_press()
                 }
             }
+            Action::CreateSubtask { story_id } => {
+                let new_subtask = (self.prompts.create_subtask)();
+                self.db.create_subtask(new_subtask, story_id)?;
+                println!("Sub-task was created!\nPress Enter to continue...");
+                wait_for_key_press();
+            }
+            Action::UpdateSubtaskStatus { subtask_id } => {
+                let new_status = (self.prompts.update_status)();
+                self.db.update_subtask_status(subtask_id, new_status)?;
+                println!("Sub-task status updated successfully!\nPress Enter to continue...");
+                wait_for_key_press();
+            }
+            Action::DeleteSubtask {
+                story_id,
+                subtask_id,
+            } => {
+                if (self.prompts.delete_subtask)() {
+                    self.db.delete_subtask(story_id, subtask_id)?;
+                    println!("Sub-task successfully deleted!\nPress Enter to continue...");
+                    wait_for_key_press();
+                    self.pages.pop();
+                } else {
+                    println!("Cancelled!\nPress Enter to continue...");
+                    wait_for_key_press()
+                }
+            }
+            Action::CreateSprint => {
+                let new_sprint = (self.prompts.create_sprint)();
+                self.db.create_sprint(new_sprint)?;
+                println!("Sprint was created!\nPress Enter to continue...");
+                wait_for_key_press();
+            }
+            Action::AddStoryToSprint {
+                sprint_id,
+                story_id,
+            } => {
+                self.db.add_story_to_sprint(sprint_id, story_id)?;
+            }
+            Action::RemoveStoryFromSprint {
+                sprint_id,
+                story_id,
+            } => {
+                self.db.remove_story_from_sprint(sprint_id, story_id)?;
+            }
+            Action::StartSprint { sprint_id } => {
+                self.db.start_sprint(sprint_id)?;
+                println!("Sprint started!\nPress Enter to continue...");
+                wait_for_key_press();
+            }
+            Action::CompleteSprint { sprint_id } => {
+                let db_state = self.db.read_db()?;
+                let planned_keys = db_state
+                    .sprints
+                    .iter()
+                    .filter(|(_, sprint)| sprint.state == SprintState::Planned)
+                    .map(|(id, _)| *id)
+                    .sorted()
+                    .map(|id| self.db.config.sprint_key(id))
+                    .collect::<Vec<_>>();
+                match (self.prompts.complete_sprint)(&planned_keys) {
+                    Some(next_key) => {
+                        let next_sprint_id = match next_key {
+                            Some(key) => Some(
+                                self.db
+                                    .config
+                                    .parse_sprint_key(&key)
+                                    .ok_or_else(|| anyhow!("Invalid sprint key '{}'!", key))?,
+                            ),
+                            None => None,
+                        };
+                        self.db.complete_sprint(sprint_id, next_sprint_id)?;
+                        println!("Sprint completed!\nPress Enter to continue...");
+                        wait_for_key_press();
+                        self.pages.pop();
+                    }
+                    None => {
+                        println!("Cancelled!\nPress Enter to continue...");
+                        wait_for_key_press()
+                    }
+                }
+            }
+            Action::Undo => match self.undo_stack.last() {
+                Some(entry) => match self.restore_entry(entry)? {
+                    Some(redo_entry) => {
+                        self.undo_stack.pop();
+                        // the next entry now follows on from this restore
+                        if let Some(entry) = self.undo_stack.last_mut() {
+                            entry.revision = redo_entry.revision;
+                        }
+                        self.redo_stack.push(redo_entry);
+                        self.remove_missing_pages()?;
+                    }
+                    None => {
+                        // every older entry predates the other change as well
+                        self.undo_stack.clear();
+                        self.redo_stack.clear();
+                        println!("The database was changed by someone else since, so this can't be undone!\nPress Enter to continue...");
+                        wait_for_key_press();
+                    }
+                },
+                None => {
+                    println!("Nothing to undo!\nPress Enter to continue...");
+                    wait_for_key_press();
+                }
+            },
+            Action::Redo => match self.redo_stack.last() {
+                Some(entry) => match self.restore_entry(entry)? {
+                    Some(undo_entry) => {
+                        self.redo_stack.pop();
+                        if let Some(entry) = self.redo_stack.last_mut() {
+                            entry.revision = undo_entry.revision;
+                        }
+                        self.undo_stack.push(undo_entry);
+                        self.remove_missing_pages()?;
+                    }
+                    None => {
+                        self.undo_stack.clear();
+                        self.redo_stack.clear();
+                        println!("The database was changed by someone else since, so this can't be redone!\nPress Enter to continue...");
+                        wait_for_key_press();
+                    }
+                },
+                None => {
+                    println!("Nothing to redo!\nPress Enter to continue...");
+                    wait_for_key_press();
+                }
+            },
             Action::Exit => {
                 // remove all pages from the pages vector
                 self.pages.clear();
@@ -166,14 +663,15 @@ mod tests {
     use super::*;
     use crate::{
         db::test_utils::MockDB,
-        models::{Epic, Status, Story},
+        models::{Epic, LinkKind, Priority, Severity, Sprint, Status, Story, Subtask},
+        ui::LinkInput,
     };
+    use chrono::NaiveDate;
+    use std::cell::Cell;
 
     #[test]
     fn should_start_on_home_page() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
         let nav = Navigator::new(db);
 
         assert_eq!(nav.get_page_count(), 1);
@@ -188,9 +686,7 @@ mod tests {
 
     #[test]
     fn handle_action_should_navigate_pages() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
         let mut nav = Navigator::new(db);
 
@@ -246,9 +742,7 @@ mod tests {
 
     #[test]
     fn handle_action_should_clear_pages_on_exit() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
         let mut nav = Navigator::new(db);
 
@@ -267,9 +761,7 @@ mod tests {
 
     #[test]
     fn handle_action_should_handle_create_epic() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
         let mut nav = Navigator::new(Rc::clone(&db));
 
@@ -290,9 +782,7 @@ mod tests {
 
     #[test]
     fn handle_action_should_handle_close_epic() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
@@ -314,11 +804,41 @@ mod tests {
         assert_eq!(db_state.epics[&epic_id].status, Status::Closed);
     }
 
+    #[test]
+    fn handle_action_should_handle_reopen_epic() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+        db.update_story_status(story_id, Status::InProgress)
+            .ok()
+            .unwrap();
+        db.close_epic(epic_id).ok().unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.reopen_epic = Box::new(|| true);
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::ReopenEpic { epic_id })
+            .ok()
+            .unwrap();
+
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(db_state.epics[&epic_id].status, Status::InProgress);
+        // reopening an epic that isn't closed is an error
+        assert!(nav.handle_action(Action::ReopenEpic { epic_id }).is_err());
+    }
+
     #[test]
     fn handle_action_should_handle_delete_epic() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
             .ok()
@@ -341,9 +861,7 @@ mod tests {
 
     #[test]
     fn handle_action_should_handle_create_story() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
             .ok()
@@ -353,6 +871,7 @@ mod tests {
 
         let mut prompts = Prompts::new();
         prompts.create_story = Box::new(|| Story::new("name".to_owned(), "description".to_owned()));
+        prompts.estimate = Box::new(|_, _| Some(8));
 
         nav.set_prompts(prompts);
 
@@ -366,13 +885,12 @@ mod tests {
         let story = db_state.stories.into_iter().next().unwrap().1;
         assert_eq!(story.name, "name".to_owned());
         assert_eq!(story.description, "description".to_owned());
+        assert_eq!(story.points, Some(8));
     }
 
     #[test]
     fn handle_action_should_handle_update_story() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
             .ok()
@@ -385,7 +903,11 @@ mod tests {
         let mut nav = Navigator::new(Rc::clone(&db));
 
         let mut prompts = Prompts::new();
-        prompts.update_status = Box::new(|| Status::InProgress);
+        prompts.transition = Box::new(|current, next_states| {
+            assert_eq!(current, "OPEN");
+            assert_eq!(next_states, ["IN PROGRESS", "RESOLVED", "CLOSED"]);
+            Some(next_states[0].clone())
+        });
 
         nav.set_prompts(prompts);
 
@@ -399,9 +921,7 @@ mod tests {
 
     #[test]
     fn handle_action_should_handle_delete_story() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
             .ok()
@@ -425,4 +945,946 @@ mod tests {
         let db_state = db.read_db().ok().unwrap();
         assert_eq!(db_state.stories.len(), 0);
     }
+
+    #[test]
+    fn handle_action_should_handle_edit_epic() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("nmae".to_owned(), "descrption".to_owned()))
+            .ok()
+            .unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.edit_name = Box::new(|_| "name".to_owned());
+        prompts.edit_description = Box::new(|_| "description".to_owned());
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::EditEpicName { epic_id })
+            .ok()
+            .unwrap();
+        nav.handle_action(Action::EditEpicDescription { epic_id })
+            .ok()
+            .unwrap();
+
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(db_state.epics[&epic_id].name, "name".to_owned());
+        assert_eq!(
+            db_state.epics[&epic_id].description,
+            "description".to_owned()
+        );
+    }
+
+    #[test]
+    fn handle_action_should_handle_edit_story() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(
+                Story::new("nmae".to_owned(), "descrption".to_owned()),
+                epic_id,
+            )
+            .ok()
+            .unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.edit_name = Box::new(|_| "name".to_owned());
+        prompts.edit_description = Box::new(|current| current.to_owned());
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::EditStoryName { story_id })
+            .ok()
+            .unwrap();
+        nav.handle_action(Action::EditStoryDescription { story_id })
+            .ok()
+            .unwrap();
+
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(db_state.stories[&story_id].name, "name".to_owned());
+        assert_eq!(
+            db_state.stories[&story_id].description,
+            "descrption".to_owned()
+        );
+    }
+
+    #[test]
+    fn handle_action_should_handle_edit_story_points() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.estimate = Box::new(|point_scale, current| {
+            assert_eq!(current, None);
+            point_scale.last().copied()
+        });
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::EditStoryPoints { story_id })
+            .ok()
+            .unwrap();
+
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(db_state.stories[&story_id].points, Some(21));
+    }
+
+    #[test]
+    fn handle_action_should_handle_edit_priority() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.priority = Box::new(|current| match current {
+            Priority::Major => Priority::Blocker,
+            _ => Priority::Trivial,
+        });
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::EditEpicPriority { epic_id })
+            .ok()
+            .unwrap();
+        nav.handle_action(Action::EditStoryPriority { story_id })
+            .ok()
+            .unwrap();
+        nav.handle_action(Action::EditStoryPriority { story_id })
+            .ok()
+            .unwrap();
+
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(db_state.epics[&epic_id].priority, Priority::Blocker);
+        assert_eq!(db_state.stories[&story_id].priority, Priority::Trivial);
+    }
+
+    #[test]
+    fn handle_action_should_handle_assign() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        db.add_user("bob".to_owned(), "Bob".to_owned()).unwrap();
+        db.add_user("alice".to_owned(), "Alice".to_owned()).unwrap();
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.assign = Box::new(|usernames, current| {
+            assert_eq!(usernames, ["alice".to_owned(), "bob".to_owned()]);
+            match current {
+                None => Some(usernames[0].clone()),
+                Some(_) => None,
+            }
+        });
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::AssignEpic { epic_id })
+            .ok()
+            .unwrap();
+        nav.handle_action(Action::AssignStory { story_id })
+            .ok()
+            .unwrap();
+        nav.handle_action(Action::AssignStory { story_id })
+            .ok()
+            .unwrap();
+
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(db_state.epics[&epic_id].owner, Some("alice".to_owned()));
+        assert_eq!(db_state.stories[&story_id].assignee, None);
+    }
+
+    #[test]
+    fn handle_action_should_handle_edit_due_date() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.due_date = Box::new(|current| match current {
+            None => NaiveDate::from_ymd_opt(2030, 1, 31),
+            Some(_) => None,
+        });
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::EditEpicDueDate { epic_id })
+            .ok()
+            .unwrap();
+        nav.handle_action(Action::EditStoryDueDate { story_id })
+            .ok()
+            .unwrap();
+        nav.handle_action(Action::EditStoryDueDate { story_id })
+            .ok()
+            .unwrap();
+
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(
+            db_state.epics[&epic_id].due,
+            NaiveDate::from_ymd_opt(2030, 1, 31)
+        );
+        assert_eq!(db_state.stories[&story_id].due, None);
+    }
+
+    #[test]
+    fn handle_action_should_confirm_resolving_blocked_stories() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let blocker_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+        db.link_stories(blocker_id, LinkKind::Blocks, story_id)
+            .ok()
+            .unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.transition = Box::new(|_, _| Some("RESOLVED".to_owned()));
+        prompts.resolve_blocked = Box::new(|_| false);
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateStoryStatus { epic_id, story_id })
+            .ok()
+            .unwrap();
+        assert_eq!(
+            db.read_db().ok().unwrap().stories[&story_id].status,
+            Status::Open
+        );
+
+        let mut prompts = Prompts::new();
+        prompts.transition = Box::new(|_, _| Some("RESOLVED".to_owned()));
+        prompts.resolve_blocked = Box::new(|warning| {
+            assert_eq!(warning, "PROJ-3 is still blocked by PROJ-2");
+            true
+        });
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateStoryStatus { epic_id, story_id })
+            .ok()
+            .unwrap();
+        assert_eq!(
+            db.read_db().ok().unwrap().stories[&story_id].status,
+            Status::Resolved
+        );
+    }
+
+    #[test]
+    fn handle_action_should_handle_move_story() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+        let other_epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let closed_epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        db.close_epic(closed_epic_id).ok().unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.move_story = Box::new(|epic_keys| {
+            // neither the story's own epic nor closed epics are offered
+            assert_eq!(epic_keys, ["PROJ-3".to_owned()]);
+            Some(epic_keys[0].clone())
+        });
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::MoveStory { story_id })
+            .ok()
+            .unwrap();
+
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(db_state.story_epic(story_id), Some(other_epic_id));
+    }
+
+    #[test]
+    fn handle_action_should_handle_sprints() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.create_sprint = Box::new(|| Sprint::new("sprint".to_owned(), "".to_owned()));
+        prompts.complete_sprint = Box::new(|sprint_keys| {
+            // the sprint being completed is no longer planned
+            assert_eq!(sprint_keys, ["SPRINT-2".to_owned()]);
+            Some(Some(sprint_keys[0].clone()))
+        });
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::CreateSprint).ok().unwrap();
+        nav.handle_action(Action::CreateSprint).ok().unwrap();
+        let (sprint_id, next_sprint_id) = (1, 2);
+
+        nav.handle_action(Action::NavigateToSprints).ok().unwrap();
+        nav.handle_action(Action::NavigateToSprintPlanning { sprint_id })
+            .ok()
+            .unwrap();
+        nav.handle_action(Action::AddStoryToSprint {
+            sprint_id,
+            story_id,
+        })
+        .ok()
+        .unwrap();
+        nav.handle_action(Action::StartSprint { sprint_id })
+            .ok()
+            .unwrap();
+        assert_eq!(db.read_db().ok().unwrap().active_sprint(), Some(sprint_id));
+
+        nav.handle_action(Action::NavigateToActiveSprint)
+            .ok()
+            .unwrap();
+        assert_eq!(nav.get_page_count(), 4);
+        nav.handle_action(Action::CompleteSprint { sprint_id })
+            .ok()
+            .unwrap();
+
+        // completing the sprint leaves its page
+        assert_eq!(nav.get_page_count(), 3);
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(db_state.sprints[&sprint_id].state, SprintState::Completed);
+        assert_eq!(db_state.sprints[&next_sprint_id].stories, vec![story_id]);
+
+        // undoing the creation of the sprint drops its planning page
+        for _ in 0..5 {
+            nav.handle_action(Action::Undo).ok().unwrap();
+        }
+        assert!(!db.read_db().ok().unwrap().sprints.contains_key(&sprint_id));
+        assert_eq!(nav.get_page_count(), 2);
+    }
+
+    #[test]
+    fn handle_action_should_handle_board() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+        let blocker_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+        db.link_stories(blocker_id, LinkKind::Blocks, story_id)
+            .ok()
+            .unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.resolve_blocked = Box::new(|_| false);
+        prompts.create_epic = Box::new(|| Epic::new("".to_owned(), "".to_owned()));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::NavigateToBoard {
+            epic_id: Some(epic_id),
+        })
+        .ok()
+        .unwrap();
+        let current_page = nav.get_current_page().unwrap();
+        assert!(current_page.as_any().downcast_ref::<BoardPage>().is_some());
+
+        nav.handle_action(Action::MoveCard {
+            story_id,
+            state: "IN PROGRESS".to_owned(),
+        })
+        .ok()
+        .unwrap();
+        assert_eq!(
+            db.read_db().ok().unwrap().stories[&story_id].status,
+            Status::InProgress
+        );
+
+        // moving a blocked card to resolved asks first
+        nav.handle_action(Action::MoveCard {
+            story_id,
+            state: "RESOLVED".to_owned(),
+        })
+        .ok()
+        .unwrap();
+        assert_eq!(
+            db.read_db().ok().unwrap().stories[&story_id].status,
+            Status::InProgress
+        );
+
+        // the board goes away with its epic
+        nav.handle_action(Action::CreateEpic).ok().unwrap();
+        let new_epic_id = db.read_db().ok().unwrap().last_item_id;
+        nav.handle_action(Action::NavigateToBoard {
+            epic_id: Some(new_epic_id),
+        })
+        .ok()
+        .unwrap();
+        assert_eq!(nav.get_page_count(), 3);
+        nav.handle_action(Action::Undo).ok().unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+    }
+
+    #[test]
+    fn handle_action_should_handle_links() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+        let other_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.link_story = Box::new(|| {
+            Some(LinkInput {
+                kind: LinkKind::Blocks,
+                inbound: true,
+                key: "PROJ-3".to_owned(),
+            })
+        });
+        prompts.unlink_story = Box::new(|keys| {
+            assert_eq!(keys, ["PROJ-3".to_owned()]);
+            Some(keys[0].clone())
+        });
+        nav.set_prompts(prompts);
+
+        // "is blocked by" is stored on the blocking story
+        nav.handle_action(Action::LinkStory { story_id })
+            .ok()
+            .unwrap();
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(db_state.open_blockers(story_id), vec![other_id]);
+
+        nav.handle_action(Action::UnlinkStory { story_id })
+            .ok()
+            .unwrap();
+        let db_state = db.read_db().ok().unwrap();
+        assert!(db_state.stories[&other_id].links.is_empty());
+    }
+
+    #[test]
+    fn handle_action_should_handle_issue_types() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.issue_type = Box::new(|_| IssueType::Bug);
+        prompts.bug_report = Box::new(|current| BugReport {
+            severity: Severity::Minor,
+            steps_to_reproduce: current.steps_to_reproduce.clone() + "step",
+        });
+        nav.set_prompts(prompts);
+
+        // turning the story into a bug asks for its report as well
+        nav.handle_action(Action::EditStoryType { story_id })
+            .ok()
+            .unwrap();
+        nav.handle_action(Action::EditBugReport { story_id })
+            .ok()
+            .unwrap();
+
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(db_state.stories[&story_id].issue_type, IssueType::Bug);
+        assert_eq!(
+            db_state.stories[&story_id].bug,
+            Some(BugReport {
+                severity: Severity::Minor,
+                steps_to_reproduce: "stepstep".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn handle_action_should_handle_subtasks() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.create_subtask = Box::new(|| Subtask::new("write tests".to_owned(), "".to_owned()));
+        prompts.update_status = Box::new(|| Status::Resolved);
+        prompts.delete_subtask = Box::new(|| true);
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::CreateSubtask { story_id })
+            .ok()
+            .unwrap();
+        let subtask_id = db.read_db().ok().unwrap().stories[&story_id].subtasks[0];
+        assert_eq!(
+            db.read_db().ok().unwrap().subtasks[&subtask_id].name,
+            "write tests"
+        );
+
+        nav.handle_action(Action::NavigateToSubtaskDetail {
+            story_id,
+            subtask_id,
+        })
+        .ok()
+        .unwrap();
+        let current_page = nav.get_current_page().unwrap();
+        assert!(current_page
+            .as_any()
+            .downcast_ref::<SubtaskDetail>()
+            .is_some());
+
+        nav.handle_action(Action::UpdateSubtaskStatus { subtask_id })
+            .ok()
+            .unwrap();
+        assert_eq!(
+            db.read_db().ok().unwrap().subtasks[&subtask_id].status,
+            Status::Resolved
+        );
+
+        nav.handle_action(Action::DeleteSubtask {
+            story_id,
+            subtask_id,
+        })
+        .ok()
+        .unwrap();
+        assert!(db.read_db().ok().unwrap().subtasks.is_empty());
+        assert_eq!(nav.get_page_count(), 1);
+    }
+
+    #[test]
+    fn handle_action_should_handle_add_comment() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.comment = Box::new(|| "needs a design review".to_owned());
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::AddComment { item_id: epic_id })
+            .ok()
+            .unwrap();
+
+        // an empty comment cancels instead of failing
+        let mut prompts = Prompts::new();
+        prompts.comment = Box::new(String::new);
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::AddComment { item_id: epic_id })
+            .ok()
+            .unwrap();
+
+        let db_state = db.read_db().ok().unwrap();
+        let comments = &db_state.epics[&epic_id].comments;
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].body, "needs a design review");
+    }
+
+    #[test]
+    fn handle_action_should_handle_labels() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        db.add_label(epic_id, "backend").ok().unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.add_label = Box::new(|| "frontend".to_owned());
+        prompts.remove_label = Box::new(|labels| {
+            assert_eq!(labels, ["backend".to_owned(), "frontend".to_owned()]);
+            Some("backend".to_owned())
+        });
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::AddLabel { item_id: epic_id })
+            .ok()
+            .unwrap();
+        nav.handle_action(Action::RemoveLabel { item_id: epic_id })
+            .ok()
+            .unwrap();
+
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(
+            db_state.epics[&epic_id].labels.iter().collect::<Vec<_>>(),
+            vec!["frontend"]
+        );
+    }
+
+    #[test]
+    fn handle_action_should_navigate_to_my_work() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let mut nav = Navigator::new(db);
+
+        nav.handle_action(Action::NavigateToMyWork).ok().unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+
+        let current_page = nav.get_current_page().unwrap();
+        assert!(current_page.as_any().downcast_ref::<MyWorkPage>().is_some());
+    }
+
+    #[test]
+    fn handle_action_should_reload_and_retry_on_conflict() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+        // another process writes after this session last read the database
+        let db_state = db.view_db().ok().unwrap();
+        db.database.write_db(&db_state).ok().unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.create_epic = Box::new(|| Epic::new("name".to_owned(), "description".to_owned()));
+        prompts.reload_and_retry = Box::new(|| true);
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::CreateEpic).ok().unwrap();
+
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(db_state.epics.len(), 1);
+    }
+
+    #[test]
+    fn handle_action_should_detect_changes_made_while_a_page_is_shown() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let retried = Rc::new(Cell::new(false));
+        let mut prompts = Prompts::new();
+        prompts.create_epic = Box::new(|| Epic::new("name".to_owned(), "description".to_owned()));
+        prompts.reload_and_retry = Box::new({
+            let retried = Rc::clone(&retried);
+            move || {
+                retried.set(true);
+                true
+            }
+        });
+
+        nav.set_prompts(prompts);
+
+        nav.get_current_page().unwrap().draw_page().ok().unwrap();
+
+        // another process writes while the page is shown
+        let db_state = db.read_db().ok().unwrap();
+        db.database.write_db(&db_state).ok().unwrap();
+
+        let action = nav
+            .get_current_page()
+            .unwrap()
+            .handle_input("c")
+            .ok()
+            .unwrap();
+        nav.handle_action(action.unwrap()).ok().unwrap();
+
+        assert!(retried.get());
+        assert_eq!(db.read_db().ok().unwrap().epics.len(), 1);
+    }
+
+    #[test]
+    fn handle_action_should_fail_for_items_deleted_by_someone_else() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+        db.delete_epic(epic_id).ok().unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        assert!(nav.handle_action(Action::EditEpicName { epic_id }).is_err());
+        assert!(nav.handle_action(Action::AssignEpic { epic_id }).is_err());
+        assert!(nav
+            .handle_action(Action::EditStoryPoints { story_id })
+            .is_err());
+        assert!(nav
+            .handle_action(Action::UpdateStoryStatus { epic_id, story_id })
+            .is_err());
+    }
+
+    #[test]
+    fn handle_action_should_not_write_on_conflict_without_retry() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+        let db_state = db.view_db().ok().unwrap();
+        db.database.write_db(&db_state).ok().unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.create_epic = Box::new(|| Epic::new("name".to_owned(), "description".to_owned()));
+        prompts.reload_and_retry = Box::new(|| false);
+
+        nav.set_prompts(prompts);
+
+        let result = nav.handle_action(Action::CreateEpic);
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(db_state.epics.len(), 0);
+    }
+
+    #[test]
+    fn handle_action_should_undo_and_redo() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.create_epic = Box::new(|| Epic::new("name".to_owned(), "description".to_owned()));
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::CreateEpic).ok().unwrap();
+        nav.handle_action(Action::CreateEpic).ok().unwrap();
+        assert_eq!(db.read_db().ok().unwrap().epics.len(), 2);
+
+        nav.handle_action(Action::Undo).ok().unwrap();
+        nav.handle_action(Action::Undo).ok().unwrap();
+        assert_eq!(db.read_db().ok().unwrap().epics.len(), 0);
+
+        nav.handle_action(Action::Redo).ok().unwrap();
+        nav.handle_action(Action::Redo).ok().unwrap();
+        assert_eq!(db.read_db().ok().unwrap().epics.len(), 2);
+    }
+
+    #[test]
+    fn handle_action_should_not_undo_over_changes_made_by_someone_else() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.create_epic = Box::new(|| Epic::new("".to_owned(), "".to_owned()));
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::CreateEpic).ok().unwrap();
+        nav.handle_action(Action::CreateEpic).ok().unwrap();
+        // another session writes to the same database
+        db.create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+
+        nav.handle_action(Action::Undo).ok().unwrap();
+
+        assert_eq!(db.read_db().ok().unwrap().epics.len(), 3);
+        assert!(nav.undo_stack.is_empty());
+        assert!(nav.redo_stack.is_empty());
+    }
+
+    #[test]
+    fn handle_action_should_not_redo_over_changes_made_by_someone_else() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.create_epic = Box::new(|| Epic::new("".to_owned(), "".to_owned()));
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::CreateEpic).ok().unwrap();
+        nav.handle_action(Action::Undo).ok().unwrap();
+        db.create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+
+        nav.handle_action(Action::Redo).ok().unwrap();
+
+        assert_eq!(db.read_db().ok().unwrap().epics.len(), 1);
+        assert!(nav.redo_stack.is_empty());
+    }
+
+    #[test]
+    fn handle_action_should_undo_delete_epic_with_its_stories() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .ok()
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .ok()
+            .unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.delete_epic = Box::new(|| true);
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id })
+            .ok()
+            .unwrap();
+        nav.handle_action(Action::DeleteEpic { epic_id })
+            .ok()
+            .unwrap();
+        assert_eq!(db.read_db().ok().unwrap().epics.len(), 0);
+
+        nav.handle_action(Action::Undo).ok().unwrap();
+
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(db_state.epics[&epic_id].stories, vec![story_id]);
+        assert!(db_state.stories.contains_key(&story_id));
+    }
+
+    #[test]
+    fn handle_action_should_leave_pages_removed_by_undo() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.create_epic = Box::new(|| Epic::new("".to_owned(), "".to_owned()));
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::CreateEpic).ok().unwrap();
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id: 1 })
+            .ok()
+            .unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+
+        nav.handle_action(Action::Undo).ok().unwrap();
+
+        assert_eq!(nav.get_page_count(), 1);
+        let current_page = nav.get_current_page().unwrap();
+        assert!(current_page.as_any().downcast_ref::<HomePage>().is_some());
+    }
+
+    #[test]
+    fn handle_action_should_clear_redo_after_new_action() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.create_epic = Box::new(|| Epic::new("".to_owned(), "".to_owned()));
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::CreateEpic).ok().unwrap();
+        nav.handle_action(Action::Undo).ok().unwrap();
+        nav.handle_action(Action::CreateEpic).ok().unwrap();
+
+        assert_eq!(nav.redo_stack.len(), 0);
+        assert_eq!(nav.undo_stack.len(), 1);
+
+        // ids handed out before the undo are never reused
+        let db_state = db.read_db().ok().unwrap();
+        assert_eq!(db_state.epics.keys().collect::<Vec<_>>(), vec![&2]);
+    }
+
+    #[test]
+    fn handle_action_should_navigate_to_history() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+        let mut nav = Navigator::new(db);
+
+        nav.handle_action(Action::NavigateToHistory { item_id: 1 })
+            .ok()
+            .unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+
+        let current_page = nav.get_current_page().unwrap();
+        let history_page = current_page.as_any().downcast_ref::<HistoryPage>();
+        assert!(history_page.is_some());
+    }
 }
@@ -3,4 +3,3 @@ mod prompts;
 
 pub use pages::*;
 pub use prompts::*;
-
@@ -1,16 +1,18 @@
 #![allow(unused_imports, dead_code)]
 use std::any::Any;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use anyhow::anyhow;
 use anyhow::Result;
+use chrono::Local;
 use itertools::Itertools;
 
-use crate::db::JiraDatabase;
-use crate::models::Action;
+use crate::db::{filter_rows, filter_rows_by_type, JiraDatabase};
+use crate::models::{Action, Comment, DBState, IssueType, SortOrder, SprintState, Status};
 
 mod page_helpers;
-use page_helpers::*;
+pub use page_helpers::*;
 
 pub trait Page {
     fn draw_page(&self) -> Result<()>;
@@ -20,49 +22,150 @@ pub trait Page {
 
 pub struct HomePage {
     pub db: Rc<JiraDatabase>,
+    // toggled from the page itself, so it lives in a Cell
+    pub sort_order: Cell<SortOrder>,
+    pub label_filter: RefCell<Option<String>>,
+}
+
+impl HomePage {
+    pub fn new(db: Rc<JiraDatabase>) -> Self {
+        Self {
+            db,
+            sort_order: Cell::default(),
+            label_filter: RefCell::default(),
+        }
+    }
+}
+
+// switches a listing between id and priority order
+fn toggle_sort_order(sort_order: &Cell<SortOrder>) {
+    sort_order.set(match sort_order.get() {
+        SortOrder::Id => SortOrder::Priority,
+        SortOrder::Priority => SortOrder::Id,
+    });
+}
+
+// "f <label>" filters a listing by label and a bare "f" clears the filter
+fn parse_label_filter(input: &str) -> Option<Option<String>> {
+    match input {
+        "f" => Some(None),
+        input => input
+            .strip_prefix("f ")
+            .map(str::trim)
+            .filter(|label| !label.is_empty())
+            .map(|label| Some(label.to_owned())),
+    }
+}
+
+// "t <type>" filters a listing by issue type and a bare "t" clears the filter
+fn parse_type_filter(input: &str) -> Option<Option<IssueType>> {
+    match input {
+        "t" => Some(None),
+        input => input
+            .strip_prefix("t ")
+            .and_then(|issue_type| IssueType::parse(issue_type.trim()))
+            .map(Some),
+    }
+}
+
+fn print_comments(comments: &[Comment]) {
+    if comments.is_empty() {
+        return;
+    }
+    println!();
+    println!("---------------------------- COMMENTS ----------------------------");
+    for comment in comments {
+        for line in get_comment_lines(comment, 64) {
+            println!("{}", line);
+        }
+    }
+}
+
+// outbound links first, then the links other stories have to this one
+fn print_links(db: &JiraDatabase, db_state: &DBState, story_id: usize) {
+    let outbound = db_state.stories[&story_id]
+        .links
+        .iter()
+        .map(|link| (link.kind.outbound_label(), link.target));
+    let inbound = db_state
+        .inbound_links(story_id)
+        .into_iter()
+        .map(|(id, kind)| (kind.inbound_label(), id));
+    let links = outbound.chain(inbound).collect_vec();
+    if links.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("------------------------------ LINKS ------------------------------");
+    for (label, id) in links {
+        let story = &db_state.stories[&id];
+        println!(
+            "{} | {} | {} | {}",
+            get_column_string(label, 13),
+            get_column_string(&db.config.issue_key(id), 11),
+            get_column_string(&story.name, 18),
+            get_column_string(&story.status.to_string(), 11)
+        );
+    }
+}
+
+fn print_label_filter(label_filter: &RefCell<Option<String>>) {
+    if let Some(label) = label_filter.borrow().as_deref() {
+        println!("filtered by label: {}", label);
+    }
 }
 
 impl Page for HomePage {
     fn draw_page(&self) -> Result<()> {
+        self.db.view_db()?;
+
         println!("----------------------------- EPICS -----------------------------");
-        println!("     id     |               name               |      status      ");
+        print_label_filter(&self.label_filter);
+        println!("{}", ROW_HEADER);
 
-        // TODO: print out epics using get_column_string(). also make sure the epics are sorted by id
-        let db = self.db.read_db()?;
-        let epics = db.epics;
-        for id in epics.keys().sorted() {
-            let epic = &epics[id];
-            println!(
-                "{} | {} | {}",
-                get_column_string(&id.to_string(), 11),
-                get_column_string(&epic.name, 32),
-                get_column_string(&epic.description, 17)
-            )
+        let today = Local::now().date_naive();
+        let rows = self.db.epic_rows(self.sort_order.get())?;
+        for row in filter_rows(rows, self.label_filter.borrow().as_deref()) {
+            println!("{}", get_listing_row_string(&row, today));
         }
 
         println!();
         println!();
 
-        println!("[q]uit | [c]reate epic | epic [:id:]");
+        println!("[q]uit | [c]reate epic | [s]ort by id/priority | [f]ilter :label: | [m]y work | [sp]rints | [b]oard | epic [:key:] | undo | redo");
 
         Ok(())
     }
 
     fn handle_input(&self, input: &str) -> Result<Option<Action>> {
         let epics = self.db.read_db()?.epics;
+        if let Some(label) = parse_label_filter(input) {
+            self.label_filter.replace(label);
+            return Ok(None);
+        }
         // match against the user input and return the corresponding action. If the user input was invalid return None.
         return match input {
             "q" => Ok(Some(Action::Exit)),
             "c" => Ok(Some(Action::CreateEpic)),
-            x => match x.parse::<usize>() {
-                Ok(id) => {
+            "m" => Ok(Some(Action::NavigateToMyWork)),
+            "sp" => Ok(Some(Action::NavigateToSprints)),
+            "b" => Ok(Some(Action::NavigateToBoard { epic_id: None })),
+            "s" => {
+                toggle_sort_order(&self.sort_order);
+                Ok(None)
+            }
+            "undo" => Ok(Some(Action::Undo)),
+            "redo" => Ok(Some(Action::Redo)),
+            x => match self.db.config.parse_issue_key(x) {
+                Some(id) => {
                     if let None = epics.get(&id) {
                         Ok(None)
                     } else {
                         Ok(Some(Action::NavigateToEpicDetail { epic_id: (id) }))
                     }
                 }
-                Err(_) => Ok(None),
+                None => Ok(None),
             },
         };
     }
@@ -75,61 +178,99 @@ impl Page for HomePage {
 pub struct EpicDetail {
     pub epic_id: usize,
     pub db: Rc<JiraDatabase>,
+    pub sort_order: Cell<SortOrder>,
+    pub label_filter: RefCell<Option<String>>,
+    pub type_filter: Cell<Option<IssueType>>,
 }
 
 impl EpicDetail {
     pub fn new(epic_id: usize, db: Rc<JiraDatabase>) -> Self {
-        return Self { epic_id, db };
+        Self {
+            epic_id,
+            db,
+            sort_order: Cell::default(),
+            label_filter: RefCell::default(),
+            type_filter: Cell::default(),
+        }
     }
 }
 
 impl Page for EpicDetail {
     fn draw_page(&self) -> Result<()> {
-        let db_state = self.db.read_db()?;
+        let db_state = self.db.view_db()?;
         let epic = db_state
             .epics
             .get(&self.epic_id)
             .ok_or_else(|| anyhow!("could not find epic!"))?;
 
         println!("------------------------------ EPIC ------------------------------");
-        println!("  id  |     name     |         description         |    status    ");
+        println!("     id     |     name     | description  | priority |   status   ");
 
-        // TODO: print out epic details using get_column_string()
         println!(
-            "{:<5} | {} | {} | {}",
-            &self.epic_id.to_string(),
+            "{} | {} | {} | {} | {}",
+            get_column_string(&self.db.config.issue_key(self.epic_id), 11),
             get_column_string(&epic.name, 12),
-            get_column_string(&epic.description, 27),
-            get_column_string(&epic.status.to_string(), 13)
+            get_column_string(&epic.description, 12),
+            get_column_string(&epic.priority.to_string(), 8),
+            get_column_string(&epic.status.to_string(), 11)
+        );
+
+        let (total_points, completed_points) = db_state.epic_points(self.epic_id);
+        println!(
+            "owner: {} | points: {} of {} completed",
+            get_user_string(&db_state, epic.owner.as_deref()),
+            completed_points,
+            total_points
+        );
+        println!("labels: {}", get_labels_string(&epic.labels));
+        let today = Local::now().date_naive();
+        println!(
+            "{}",
+            get_dates_string(
+                epic.created_at,
+                epic.updated_at,
+                epic.due,
+                epic.status,
+                today
+            )
         );
 
         println!();
 
         println!("---------------------------- STORIES ----------------------------");
-        println!("     id     |               name               |      status      ");
+        print_label_filter(&self.label_filter);
+        if let Some(issue_type) = self.type_filter.get() {
+            println!("filtered by type: {}", issue_type);
+        }
+        println!("{}", ROW_HEADER);
 
-        // TODO: print out stories using get_column_string(). also make sure the stories are sorted by id
-        let stories = &db_state.stories;
-        for story_id in &epic.stories {
-            println!(
-                "{} | {} | {}",
-                get_column_string(&story_id.to_string(), 11),
-                get_column_string(&stories[&story_id].name, 32),
-                get_column_string(&stories[&story_id].status.to_string(), 17)
-            );
+        let rows = self.db.story_rows(self.epic_id, self.sort_order.get())?;
+        let rows = filter_rows_by_type(rows, self.type_filter.get());
+        for row in filter_rows(rows, self.label_filter.borrow().as_deref()) {
+            println!("{}", get_listing_row_string(&row, today));
         }
 
+        print_comments(&epic.comments);
+
         println!();
         println!();
 
-        println!("[p]revious | [cl]ose epic | [r]eopen epic | [d]elete epic | [cr]eate story | [e]pic [n]ame | [e]pic [d]escription | story [:id:]");
+        println!("[p]revious | [cl]ose epic | [r]eopen epic | [d]elete epic | [cr]eate story | [e]pic [n]ame | [e]pic [d]escription | [u]pdate [n]ame | [u]pdate [d]escription | [pr]iority | [a]ssign owner | [d]ue [d]ate | [l]abel [a]dd | [l]abel [r]emove | [co]mment | [s]ort by id/priority | [f]ilter :label: | filter by [t]ype :type: | [b]oard | [h]istory | story [:key:] | undo | redo");
 
         Ok(())
     }
 
     fn handle_input(&self, input: &str) -> Result<Option<Action>> {
         let epic_id = self.epic_id;
-        let stories = &self.db.read_db()?.epics[&epic_id].stories;
+        let stories = self.db.read_epic(epic_id)?.stories;
+        if let Some(label) = parse_label_filter(input) {
+            self.label_filter.replace(label);
+            return Ok(None);
+        }
+        if let Some(issue_type) = parse_type_filter(input) {
+            self.type_filter.set(issue_type);
+            return Ok(None);
+        }
 
         // match against the user input and return the corresponding action. If the user input was invalid return None.
         return match input {
@@ -152,15 +293,51 @@ impl Page for EpicDetail {
             "ed" => Ok(Some(Action::GetEpicDescription {
                 epic_id: self.epic_id,
             })),
-            input => match input.parse::<usize>() {
-                Ok(id) => match stories.contains(&id) {
+            "un" => Ok(Some(Action::EditEpicName {
+                epic_id: self.epic_id,
+            })),
+            "ud" => Ok(Some(Action::EditEpicDescription {
+                epic_id: self.epic_id,
+            })),
+            "pr" => Ok(Some(Action::EditEpicPriority {
+                epic_id: self.epic_id,
+            })),
+            "a" => Ok(Some(Action::AssignEpic {
+                epic_id: self.epic_id,
+            })),
+            "dd" => Ok(Some(Action::EditEpicDueDate {
+                epic_id: self.epic_id,
+            })),
+            "co" => Ok(Some(Action::AddComment {
+                item_id: self.epic_id,
+            })),
+            "la" => Ok(Some(Action::AddLabel {
+                item_id: self.epic_id,
+            })),
+            "lr" => Ok(Some(Action::RemoveLabel {
+                item_id: self.epic_id,
+            })),
+            "s" => {
+                toggle_sort_order(&self.sort_order);
+                Ok(None)
+            }
+            "b" => Ok(Some(Action::NavigateToBoard {
+                epic_id: Some(self.epic_id),
+            })),
+            "h" => Ok(Some(Action::NavigateToHistory {
+                item_id: self.epic_id,
+            })),
+            "undo" => Ok(Some(Action::Undo)),
+            "redo" => Ok(Some(Action::Redo)),
+            input => match self.db.config.parse_issue_key(input) {
+                Some(id) => match stories.contains(&id) {
                     true => Ok(Some(Action::NavigateToStoryDetail {
                         epic_id,
                         story_id: id,
                     })),
                     false => Ok(None),
                 },
-                Err(_) => Ok(None),
+                None => Ok(None),
             },
         };
     }
@@ -178,39 +355,100 @@ pub struct StoryDetail {
 
 impl Page for StoryDetail {
     fn draw_page(&self) -> Result<()> {
-        let db_state = self.db.read_db()?;
+        let db_state = self.db.view_db()?;
         let story = db_state
             .stories
             .get(&self.story_id)
             .ok_or_else(|| anyhow!("could not find story!"))?;
 
         println!("------------------------------ STORY ------------------------------");
-        println!("  id  |     name     |         description         |    status    ");
+        println!("{}", ROW_HEADER);
 
-        // TODO: print out story details using get_column_string()
         println!(
-            "{} | {} | {} | {}",
-            get_column_string(&self.story_id.to_string(), 5),
-            get_column_string(&story.name, 12),
-            get_column_string(&story.description, 27),
-            get_column_string(&story.status.to_string(), 13)
+            "{} | {} | {} | {} | {} | {}",
+            get_column_string(&self.db.config.issue_key(self.story_id), 11),
+            get_column_string(&story.name, 18),
+            get_column_string(&story.issue_type.to_string(), 5),
+            get_column_string(&story.priority.to_string(), 8),
+            get_column_string(&get_points_string(story.points), 6),
+            get_column_string(&story.status.to_string(), 11)
+        );
+        println!("state: {}", self.db.config.workflow.story_state(story));
+        println!(
+            "epic: {} | assignee: {} | reporter: {}",
+            db_state
+                .story_epic(self.story_id)
+                .map_or("-".to_owned(), |epic_id| self.db.config.issue_key(epic_id)),
+            get_user_string(&db_state, story.assignee.as_deref()),
+            get_user_string(&db_state, story.reporter.as_deref())
+        );
+        println!("labels: {}", get_labels_string(&story.labels));
+        println!(
+            "{}",
+            get_dates_string(
+                story.created_at,
+                story.updated_at,
+                story.due,
+                story.status,
+                Local::now().date_naive()
+            )
         );
 
+        // the description is shown in full rather than cut off to fit a column
+        println!();
+        println!("Description:");
+        for line in get_wrapped_lines(&story.description, 64) {
+            println!("  {}", line);
+        }
+
+        if let Some(bug) = &story.bug {
+            println!();
+            println!("Severity: {}", bug.severity);
+            println!("Steps to Reproduce:");
+            for line in get_wrapped_lines(&bug.steps_to_reproduce, 64) {
+                println!("  {}", line);
+            }
+        }
+
+        print_links(&self.db, &db_state, self.story_id);
+
+        println!();
+        println!("---------------------------- SUB-TASKS ---------------------------");
+        println!("     id     |               name               |   status   ");
+        for subtask_id in &story.subtasks {
+            let subtask = &db_state.subtasks[subtask_id];
+            println!(
+                "{} | {} | {}",
+                get_column_string(&self.db.config.issue_key(*subtask_id), 11),
+                get_column_string(&subtask.name, 32),
+                get_column_string(&subtask.status.to_string(), 11)
+            );
+        }
+
+        print_comments(&story.comments);
+
         println!();
         println!();
 
         println!(
-            "[p]revious | [u]pdate story | [s]tory [n]ame | [s]tory [d]escription | [d]elete story"
+            "[p]revious | [u]pdate story | [s]tory [n]ame | [s]tory [d]escription | [u]pdate [n]ame | [u]pdate [d]escription | [u]pdate [p]oints | [pr]iority | [ty]pe | [bu]g report | [li]nk | [u]n[l]ink | [m]o[v]e to epic | [a]ssign | [d]ue [d]ate | [l]abel [a]dd | [l]abel [r]emove | [co]mment | [c]reate [s]ub-task | [d]elete story | [h]istory | sub-task [:key:] | undo | redo"
         );
 
         Ok(())
     }
 
     fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        // the story may have been moved to another epic since the page was opened
+        let epic_id = self
+            .db
+            .read_db()?
+            .story_epic(self.story_id)
+            .unwrap_or(self.epic_id);
+
         return Ok(match input {
             "p" => Some(Action::NavigateToPreviousPage),
             "u" => Some(Action::UpdateStoryStatus {
-                epic_id: self.epic_id,
+                epic_id,
                 story_id: self.story_id,
             }),
             "sn" => Some(Action::GetStoryName {
@@ -219,11 +457,71 @@ impl Page for StoryDetail {
             "sd" => Some(Action::GetStoryDescription {
                 story_id: self.story_id,
             }),
+            "un" => Some(Action::EditStoryName {
+                story_id: self.story_id,
+            }),
+            "ud" => Some(Action::EditStoryDescription {
+                story_id: self.story_id,
+            }),
+            "up" => Some(Action::EditStoryPoints {
+                story_id: self.story_id,
+            }),
+            "pr" => Some(Action::EditStoryPriority {
+                story_id: self.story_id,
+            }),
+            "ty" => Some(Action::EditStoryType {
+                story_id: self.story_id,
+            }),
+            "bu" => Some(Action::EditBugReport {
+                story_id: self.story_id,
+            }),
+            "li" => Some(Action::LinkStory {
+                story_id: self.story_id,
+            }),
+            "ul" => Some(Action::UnlinkStory {
+                story_id: self.story_id,
+            }),
+            "a" => Some(Action::AssignStory {
+                story_id: self.story_id,
+            }),
+            "dd" => Some(Action::EditStoryDueDate {
+                story_id: self.story_id,
+            }),
+            "co" => Some(Action::AddComment {
+                item_id: self.story_id,
+            }),
+            "cs" => Some(Action::CreateSubtask {
+                story_id: self.story_id,
+            }),
+            "la" => Some(Action::AddLabel {
+                item_id: self.story_id,
+            }),
+            "lr" => Some(Action::RemoveLabel {
+                item_id: self.story_id,
+            }),
+            "mv" => Some(Action::MoveStory {
+                story_id: self.story_id,
+            }),
             "d" => Some(Action::DeleteStory {
-                epic_id: self.epic_id,
+                epic_id,
                 story_id: self.story_id,
             }),
-            _ => None,
+            "h" => Some(Action::NavigateToHistory {
+                item_id: self.story_id,
+            }),
+            "undo" => Some(Action::Undo),
+            "redo" => Some(Action::Redo),
+            input => {
+                let subtasks = self.db.read_story(self.story_id)?.subtasks;
+                self.db
+                    .config
+                    .parse_issue_key(input)
+                    .filter(|id| subtasks.contains(id))
+                    .map(|subtask_id| Action::NavigateToSubtaskDetail {
+                        story_id: self.story_id,
+                        subtask_id,
+                    })
+            }
         });
     }
 
@@ -232,163 +530,579 @@ impl Page for StoryDetail {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::db::test_utils::MockDB;
-    use crate::models::{Epic, Story};
-
-    mod home_page {
-        use super::*;
+pub struct SubtaskDetail {
+    pub story_id: usize,
+    pub subtask_id: usize,
+    pub db: Rc<JiraDatabase>,
+}
 
-        #[test]
-        fn draw_page_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+impl Page for SubtaskDetail {
+    fn draw_page(&self) -> Result<()> {
+        let db_state = self.db.view_db()?;
+        let subtask = db_state
+            .subtasks
+            .get(&self.subtask_id)
+            .ok_or_else(|| anyhow!("could not find sub-task!"))?;
+
+        println!("---------------------------- SUB-TASK ----------------------------");
+        println!("     id     |               name               |   status   ");
+        println!(
+            "{} | {} | {}",
+            get_column_string(&self.db.config.issue_key(self.subtask_id), 11),
+            get_column_string(&subtask.name, 32),
+            get_column_string(&subtask.status.to_string(), 11)
+        );
+        println!("story: {}", self.db.config.issue_key(self.story_id));
 
-            let page = HomePage { db };
-            assert_eq!(page.draw_page().is_ok(), true);
+        println!();
+        println!("Description:");
+        for line in get_wrapped_lines(&subtask.description, 64) {
+            println!("  {}", line);
         }
 
-        #[test]
-        fn handle_input_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+        println!();
+        println!();
 
-            let page = HomePage { db };
-            assert_eq!(page.handle_input("").is_ok(), true);
-        }
+        println!("[p]revious | [u]pdate sub-task | [d]elete sub-task | [h]istory | undo | redo");
 
-        #[test]
-        fn handle_input_should_return_the_correct_actions() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+        Ok(())
+    }
 
-            let epic = Epic::new("".to_owned(), "".to_owned());
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        Ok(match input {
+            "p" => Some(Action::NavigateToPreviousPage),
+            "u" => Some(Action::UpdateSubtaskStatus {
+                subtask_id: self.subtask_id,
+            }),
+            "d" => Some(Action::DeleteSubtask {
+                story_id: self.story_id,
+                subtask_id: self.subtask_id,
+            }),
+            "h" => Some(Action::NavigateToHistory {
+                item_id: self.subtask_id,
+            }),
+            "undo" => Some(Action::Undo),
+            "redo" => Some(Action::Redo),
+            _ => None,
+        })
+    }
 
-            let epic_id = db.create_epic(epic).unwrap();
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
 
-            let page = HomePage { db };
+// everything owned by or assigned to the current user
+pub struct MyWorkPage {
+    pub db: Rc<JiraDatabase>,
+}
 
-            let q = "q";
-            let c = "c";
-            let valid_epic_id = epic_id.to_string();
-            let invalid_epic_id = "999";
-            let junk_input = "j983f2j";
-            let junk_input_with_valid_prefix = "q983f2j";
-            let input_with_trailing_white_spaces = "q\n";
+impl Page for MyWorkPage {
+    fn draw_page(&self) -> Result<()> {
+        self.db.view_db()?;
+        let user = self.db.config.current_user();
 
-            assert_eq!(page.handle_input(q).unwrap(), Some(Action::Exit));
-            assert_eq!(page.handle_input(c).unwrap(), Some(Action::CreateEpic));
-            assert_eq!(
-                page.handle_input(&valid_epic_id).unwrap(),
-                Some(Action::NavigateToEpicDetail { epic_id: 1 })
-            );
-            assert_eq!(page.handle_input(invalid_epic_id).unwrap(), None);
-            assert_eq!(page.handle_input(junk_input).unwrap(), None);
-            assert_eq!(
-                page.handle_input(junk_input_with_valid_prefix).unwrap(),
-                None
-            );
-            assert_eq!(
-                page.handle_input(input_with_trailing_white_spaces).unwrap(),
-                None
-            );
+        println!("---------------------------- MY WORK -----------------------------");
+        println!("user: {}", user);
+        println!("{}", ROW_HEADER);
+
+        let today = Local::now().date_naive();
+        for row in self.db.work_rows(&user)? {
+            println!("{}", get_listing_row_string(&row, today));
         }
-    }
 
-    mod epic_detail_page {
-        use super::*;
+        println!();
+        println!();
 
-        #[test]
-        fn draw_page_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+        println!("[p]revious | item [:key:] | undo | redo");
 
-            let epic_id = db
-                .create_epic(Epic::new("".to_owned(), "".to_owned()))
-                .ok()
-                .unwrap();
+        Ok(())
+    }
 
-            let page = EpicDetail { epic_id, db };
-            assert_eq!(page.draw_page().is_ok(), true);
-        }
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        let db_state = self.db.read_db()?;
+        let user = self.db.config.current_user();
 
-        #[test]
-        fn handle_input_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
-            let epic_id = db
-                .create_epic(Epic::new("".to_owned(), "".to_owned()))
-                .unwrap();
+        Ok(match input {
+            "p" => Some(Action::NavigateToPreviousPage),
+            "undo" => Some(Action::Undo),
+            "redo" => Some(Action::Redo),
+            input => match self.db.config.parse_issue_key(input) {
+                Some(id) if !self.db.work_rows(&user)?.iter().any(|row| row.id == id) => None,
+                Some(id) if db_state.epics.contains_key(&id) => {
+                    Some(Action::NavigateToEpicDetail { epic_id: id })
+                }
+                Some(id) => db_state
+                    .story_epic(id)
+                    .map(|epic_id| Action::NavigateToStoryDetail {
+                        epic_id,
+                        story_id: id,
+                    }),
+                None => None,
+            },
+        })
+    }
 
-            let page = EpicDetail { epic_id, db };
-            assert_eq!(page.handle_input("").is_ok(), true);
-        }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
 
-        #[test]
-        fn draw_page_should_throw_error_for_invalid_epic_id() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+pub struct SprintsPage {
+    pub db: Rc<JiraDatabase>,
+}
 
-            let page = EpicDetail { epic_id: 999, db };
-            assert_eq!(page.draw_page().is_err(), true);
+impl Page for SprintsPage {
+    fn draw_page(&self) -> Result<()> {
+        let db_state = self.db.view_db()?;
+
+        println!("---------------------------- SPRINTS -----------------------------");
+        println!("{}", SPRINT_ROW_HEADER);
+
+        for id in db_state.sprints.keys().sorted() {
+            let key = self.db.config.sprint_key(*id);
+            println!("{}", get_sprint_row_string(&key, &db_state.sprints[id]));
         }
 
-        #[test]
-        fn handle_input_should_return_the_correct_actions() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+        println!();
+        println!();
 
-            let epic_id = db
-                .create_epic(Epic::new("".to_owned(), "".to_owned()))
-                .unwrap();
-            let story_id = db
-                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
-                .unwrap();
+        println!("[p]revious | [c]reate sprint | [a]ctive sprint | sprint [:key:] | undo | redo");
 
-            let page = EpicDetail { epic_id, db };
+        Ok(())
+    }
 
-            let p = "p";
-            let u = "cl";
-            let d = "d";
-            let c = "cr";
-            let invalid_story_id = "999";
-            let junk_input = "j983f2j";
-            let junk_input_with_valid_prefix = "p983f2j";
-            let input_with_trailing_white_spaces = "p\n";
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        let db_state = self.db.read_db()?;
 
-            assert_eq!(
-                page.handle_input(p).unwrap(),
-                Some(Action::NavigateToPreviousPage)
+        Ok(match input {
+            "p" => Some(Action::NavigateToPreviousPage),
+            "c" => Some(Action::CreateSprint),
+            "a" => db_state
+                .active_sprint()
+                .map(|_| Action::NavigateToActiveSprint),
+            "undo" => Some(Action::Undo),
+            "redo" => Some(Action::Redo),
+            input => self
+                .db
+                .config
+                .parse_sprint_key(input)
+                .filter(|id| db_state.sprints.contains_key(id))
+                .map(|sprint_id| Action::NavigateToSprintPlanning { sprint_id }),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// the stories of a sprint next to the backlog they are pulled from
+pub struct SprintPlanning {
+    pub sprint_id: usize,
+    pub db: Rc<JiraDatabase>,
+}
+
+impl Page for SprintPlanning {
+    fn draw_page(&self) -> Result<()> {
+        let db_state = self.db.view_db()?;
+        let sprint = db_state
+            .sprints
+            .get(&self.sprint_id)
+            .ok_or_else(|| anyhow!("Invalid Sprint ID!"))?;
+        let today = Local::now().date_naive();
+
+        println!("------------------------------ SPRINT ------------------------------");
+        println!("{}", SPRINT_ROW_HEADER);
+        println!(
+            "{}",
+            get_sprint_row_string(&self.db.config.sprint_key(self.sprint_id), sprint)
+        );
+        println!("goal: {}", sprint.goal);
+
+        println!();
+
+        println!("{}", ROW_HEADER);
+        for row in self.db.sprint_rows(self.sprint_id)? {
+            println!("{}", get_listing_row_string(&row, today));
+        }
+
+        if sprint.state != SprintState::Completed {
+            println!();
+            println!("----------------------------- BACKLOG ------------------------------");
+            println!("{}", ROW_HEADER);
+            for row in self.db.backlog_rows()? {
+                println!("{}", get_listing_row_string(&row, today));
+            }
+        }
+
+        println!();
+        println!();
+
+        println!("[p]revious | [a]dd :key: | [r]emove :key: | [st]art sprint | undo | redo");
+
+        Ok(())
+    }
+
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        let sprint_id = self.sprint_id;
+        let parse_key = |prefix: &str| {
+            input
+                .strip_prefix(prefix)
+                .and_then(|key| self.db.config.parse_issue_key(key.trim()))
+        };
+
+        if let Some(story_id) = parse_key("a ") {
+            let in_backlog = self.db.backlog_rows()?.iter().any(|row| row.id == story_id);
+            return Ok(in_backlog.then_some(Action::AddStoryToSprint {
+                sprint_id,
+                story_id,
+            }));
+        }
+        if let Some(story_id) = parse_key("r ") {
+            let in_sprint = self.db.read_sprint(sprint_id)?.stories.contains(&story_id);
+            return Ok(in_sprint.then_some(Action::RemoveStoryFromSprint {
+                sprint_id,
+                story_id,
+            }));
+        }
+
+        Ok(match input {
+            "p" => Some(Action::NavigateToPreviousPage),
+            "st" => Some(Action::StartSprint { sprint_id }),
+            "undo" => Some(Action::Undo),
+            "redo" => Some(Action::Redo),
+            _ => None,
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct ActiveSprintPage {
+    pub db: Rc<JiraDatabase>,
+}
+
+impl Page for ActiveSprintPage {
+    fn draw_page(&self) -> Result<()> {
+        let db_state = self.db.view_db()?;
+
+        println!("-------------------------- ACTIVE SPRINT ---------------------------");
+        // undoing the start of the sprint leaves this page without one
+        let Some(sprint_id) = db_state.active_sprint() else {
+            println!("No sprint is active.");
+            println!();
+            println!("[p]revious | undo | redo");
+            return Ok(());
+        };
+        let sprint = &db_state.sprints[&sprint_id];
+
+        println!("{}", SPRINT_ROW_HEADER);
+        println!(
+            "{}",
+            get_sprint_row_string(&self.db.config.sprint_key(sprint_id), sprint)
+        );
+        println!("goal: {}", sprint.goal);
+
+        println!();
+
+        println!("{}", ROW_HEADER);
+        let today = Local::now().date_naive();
+        for row in self.db.sprint_rows(sprint_id)? {
+            println!("{}", get_listing_row_string(&row, today));
+        }
+
+        println!();
+        println!();
+
+        println!("[p]revious | [co]mplete sprint | story [:key:] | undo | redo");
+
+        Ok(())
+    }
+
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        let db_state = self.db.read_db()?;
+        let Some(sprint_id) = db_state.active_sprint() else {
+            return Ok(match input {
+                "p" => Some(Action::NavigateToPreviousPage),
+                "undo" => Some(Action::Undo),
+                "redo" => Some(Action::Redo),
+                _ => None,
+            });
+        };
+
+        Ok(match input {
+            "p" => Some(Action::NavigateToPreviousPage),
+            "co" => Some(Action::CompleteSprint { sprint_id }),
+            "undo" => Some(Action::Undo),
+            "redo" => Some(Action::Redo),
+            input => self
+                .db
+                .config
+                .parse_issue_key(input)
+                .filter(|id| db_state.sprints[&sprint_id].stories.contains(id))
+                .and_then(|story_id| {
+                    db_state
+                        .story_epic(story_id)
+                        .map(|epic_id| Action::NavigateToStoryDetail { epic_id, story_id })
+                }),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// stories as cards in a column per status
+pub struct BoardPage {
+    // the whole project is shown without an epic
+    pub epic_id: Option<usize>,
+    pub db: Rc<JiraDatabase>,
+}
+
+// "> <key>" moves a card one column right and "< <key>" one column left
+fn parse_card_move(input: &str) -> Option<(&str, isize)> {
+    if let Some(key) = input.strip_prefix("> ") {
+        return Some((key.trim(), 1));
+    }
+    input.strip_prefix("< ").map(|key| (key.trim(), -1))
+}
+
+impl Page for BoardPage {
+    fn draw_page(&self) -> Result<()> {
+        let db_state = self.db.view_db()?;
+        let rows = self.db.board_rows(self.epic_id)?;
+
+        println!("------------------------------ BOARD -------------------------------");
+        match self.epic_id {
+            Some(epic_id) => {
+                let epic = db_state
+                    .epics
+                    .get(&epic_id)
+                    .ok_or_else(|| anyhow!("Invalid Epic ID!"))?;
+                println!("epic: {} {}", self.db.config.issue_key(epic_id), epic.name)
+            }
+            None => println!("all epics"),
+        }
+        println!();
+
+        let workflow = &self.db.config.workflow;
+        let columns = workflow
+            .states
+            .iter()
+            .map(|state| {
+                let cards = rows
+                    .iter()
+                    .filter(|row| {
+                        // the rows are read separately, so a story may be gone by now
+                        db_state
+                            .stories
+                            .get(&row.id)
+                            .is_some_and(|story| workflow.story_state(story) == state.name)
+                    })
+                    .map(|row| format!("{} {}", row.key, row.name))
+                    .collect_vec();
+                (state.name.to_uppercase(), cards)
+            })
+            .collect_vec();
+        for line in get_board_lines(&columns) {
+            println!("{}", line);
+        }
+
+        println!();
+        println!();
+
+        println!(
+            "[p]revious | move card [>] :key: | move card [<] :key: | story [:key:] | undo | redo"
+        );
+
+        Ok(())
+    }
+
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        let db_state = self.db.read_db()?;
+        let on_board = |key: &str| {
+            self.db
+                .config
+                .parse_issue_key(key)
+                .filter(|id| match self.epic_id {
+                    Some(epic_id) => db_state
+                        .epics
+                        .get(&epic_id)
+                        .is_some_and(|epic| epic.stories.contains(id)),
+                    None => db_state.stories.contains_key(id),
+                })
+        };
+
+        if let Some((key, step)) = parse_card_move(input) {
+            let workflow = &self.db.config.workflow;
+            return Ok(on_board(key).and_then(|story_id| {
+                let current = workflow.story_state(&db_state.stories[&story_id]);
+                let column = workflow
+                    .states
+                    .iter()
+                    .position(|state| state.name == current)?;
+                let state = workflow.states.get(column.checked_add_signed(step)?)?;
+                Some(Action::MoveCard {
+                    story_id,
+                    state: state.name.clone(),
+                })
+            }));
+        }
+
+        Ok(match input {
+            "p" => Some(Action::NavigateToPreviousPage),
+            "undo" => Some(Action::Undo),
+            "redo" => Some(Action::Redo),
+            input => on_board(input).and_then(|story_id| {
+                db_state
+                    .story_epic(story_id)
+                    .map(|epic_id| Action::NavigateToStoryDetail { epic_id, story_id })
+            }),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct HistoryPage {
+    pub item_id: usize,
+    pub db: Rc<JiraDatabase>,
+}
+
+impl Page for HistoryPage {
+    fn draw_page(&self) -> Result<()> {
+        self.db.view_db()?;
+        let events = self.db.read_events(self.item_id)?;
+
+        println!("---------------------------- HISTORY -----------------------------");
+        println!("        date        |     user     |             event             ");
+
+        for event in &events {
+            println!(
+                "{} | {} | {}",
+                get_column_string(
+                    &event
+                        .timestamp
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string(),
+                    19
+                ),
+                get_column_string(&event.actor, 12),
+                get_column_string(&event.kind.to_string(), 30)
+            );
+        }
+
+        println!();
+        println!();
+
+        println!("[p]revious | undo | redo");
+
+        Ok(())
+    }
+
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        Ok(match input {
+            "p" => Some(Action::NavigateToPreviousPage),
+            "undo" => Some(Action::Undo),
+            "redo" => Some(Action::Redo),
+            _ => None,
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::MockDB;
+    use crate::models::{Epic, LinkKind, Story, Subtask};
+
+    mod home_page {
+        use super::*;
+
+        #[test]
+        fn draw_page_should_not_throw_error() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let page = HomePage::new(db);
+            assert_eq!(page.draw_page().is_ok(), true);
+        }
+
+        #[test]
+        fn handle_input_should_not_throw_error() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let page = HomePage::new(db);
+            assert_eq!(page.handle_input("").is_ok(), true);
+        }
+
+        #[test]
+        fn handle_input_should_return_the_correct_actions() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let epic = Epic::new("".to_owned(), "".to_owned());
+
+            let epic_id = db.create_epic(epic).unwrap();
+
+            let page = HomePage::new(db);
+
+            let q = "q";
+            let c = "c";
+            let valid_epic_id = epic_id.to_string();
+            let valid_epic_key = format!("PROJ-{}", epic_id);
+            let epic_key_from_other_project = format!("OTHER-{}", epic_id);
+            let invalid_epic_id = "999";
+            let junk_input = "j983f2j";
+            let junk_input_with_valid_prefix = "q983f2j";
+            let input_with_trailing_white_spaces = "q\n";
+
+            assert_eq!(page.handle_input(q).unwrap(), Some(Action::Exit));
+            assert_eq!(page.handle_input(c).unwrap(), Some(Action::CreateEpic));
+            assert_eq!(
+                page.handle_input("m").unwrap(),
+                Some(Action::NavigateToMyWork)
             );
             assert_eq!(
-                page.handle_input(u).unwrap(),
-                Some(Action::CloseEpic { epic_id: 1 })
+                page.handle_input("sp").unwrap(),
+                Some(Action::NavigateToSprints)
             );
             assert_eq!(
-                page.handle_input(d).unwrap(),
-                Some(Action::DeleteEpic { epic_id: 1 })
+                page.handle_input("b").unwrap(),
+                Some(Action::NavigateToBoard { epic_id: None })
             );
+            assert_eq!(page.handle_input("s").unwrap(), None);
+            assert_eq!(page.sort_order.get(), SortOrder::Priority);
+            assert_eq!(page.handle_input("s").unwrap(), None);
+            assert_eq!(page.sort_order.get(), SortOrder::Id);
+            assert_eq!(page.handle_input("f frontend").unwrap(), None);
+            assert_eq!(page.label_filter.borrow().as_deref(), Some("frontend"));
+            assert_eq!(page.handle_input("f").unwrap(), None);
+            assert_eq!(page.label_filter.borrow().as_deref(), None);
+            assert_eq!(page.handle_input("undo").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("redo").unwrap(), Some(Action::Redo));
             assert_eq!(
-                page.handle_input(c).unwrap(),
-                Some(Action::CreateStory { epic_id: 1 })
+                page.handle_input(&valid_epic_id).unwrap(),
+                Some(Action::NavigateToEpicDetail { epic_id: 1 })
             );
             assert_eq!(
-                page.handle_input(&story_id.to_string()).unwrap(),
-                Some(Action::NavigateToStoryDetail {
-                    epic_id: 1,
-                    story_id: 2
-                })
+                page.handle_input(&valid_epic_key).unwrap(),
+                Some(Action::NavigateToEpicDetail { epic_id: 1 })
             );
-            assert_eq!(page.handle_input(invalid_story_id).unwrap(), None);
+            assert_eq!(
+                page.handle_input(&epic_key_from_other_project).unwrap(),
+                None
+            );
+            assert_eq!(page.handle_input(invalid_epic_id).unwrap(), None);
             assert_eq!(page.handle_input(junk_input).unwrap(), None);
             assert_eq!(
                 page.handle_input(junk_input_with_valid_prefix).unwrap(),
@@ -401,82 +1115,44 @@ mod tests {
         }
     }
 
-    mod story_detail_page {
-        use std::borrow::BorrowMut;
-
+    mod epic_detail_page {
         use super::*;
 
         #[test]
         fn draw_page_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
             let epic_id = db
                 .create_epic(Epic::new("".to_owned(), "".to_owned()))
                 .ok()
                 .unwrap();
-            let story_id = db
-                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
-                .ok()
-                .unwrap();
 
-            let page = StoryDetail {
-                epic_id,
-                story_id,
-                db,
-            };
+            let page = EpicDetail::new(epic_id, db);
             assert_eq!(page.draw_page().is_ok(), true);
         }
 
         #[test]
         fn handle_input_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
-
-            let epic_id = (&db
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db
                 .create_epic(Epic::new("".to_owned(), "".to_owned()))
-                .unwrap())
-                .to_owned();
-            let story_id = db
-                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
                 .unwrap();
 
-            let page = StoryDetail {
-                epic_id,
-                story_id,
-                db,
-            };
+            let page = EpicDetail::new(epic_id, db);
             assert_eq!(page.handle_input("").is_ok(), true);
         }
 
         #[test]
-        fn draw_page_should_throw_error_for_invalid_story_id() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
-
-            let epic_id = db
-                .create_epic(Epic::new("".to_owned(), "".to_owned()))
-                .unwrap();
-            let _ = db
-                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
-                .unwrap();
+        fn draw_page_should_throw_error_for_invalid_epic_id() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
-            let page = StoryDetail {
-                epic_id,
-                story_id: 999,
-                db,
-            };
+            let page = EpicDetail::new(999, db);
             assert_eq!(page.draw_page().is_err(), true);
         }
 
         #[test]
         fn handle_input_should_return_the_correct_actions() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
             let epic_id = db
                 .create_epic(Epic::new("".to_owned(), "".to_owned()))
@@ -485,16 +1161,13 @@ mod tests {
                 .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
                 .unwrap();
 
-            let page = StoryDetail {
-                epic_id,
-                story_id,
-                db,
-            };
+            let page = EpicDetail::new(epic_id, db);
 
             let p = "p";
-            let u = "u";
+            let u = "cl";
             let d = "d";
-            let some_number = "1";
+            let c = "cr";
+            let invalid_story_id = "999";
             let junk_input = "j983f2j";
             let junk_input_with_valid_prefix = "p983f2j";
             let input_with_trailing_white_spaces = "p\n";
@@ -505,13 +1178,316 @@ mod tests {
             );
             assert_eq!(
                 page.handle_input(u).unwrap(),
-                Some(Action::UpdateStoryStatus { epic_id, story_id })
+                Some(Action::CloseEpic { epic_id: 1 })
             );
             assert_eq!(
                 page.handle_input(d).unwrap(),
-                Some(Action::DeleteStory { epic_id, story_id })
+                Some(Action::DeleteEpic { epic_id: 1 })
             );
-            assert_eq!(page.handle_input(some_number).unwrap(), None);
+            assert_eq!(
+                page.handle_input(c).unwrap(),
+                Some(Action::CreateStory { epic_id: 1 })
+            );
+            assert_eq!(page.handle_input("undo").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("redo").unwrap(), Some(Action::Redo));
+            assert_eq!(
+                page.handle_input(&story_id.to_string()).unwrap(),
+                Some(Action::NavigateToStoryDetail {
+                    epic_id: 1,
+                    story_id: 2
+                })
+            );
+            assert_eq!(
+                page.handle_input(&format!("PROJ-{}", story_id)).unwrap(),
+                Some(Action::NavigateToStoryDetail {
+                    epic_id: 1,
+                    story_id: 2
+                })
+            );
+            assert_eq!(
+                page.handle_input("h").unwrap(),
+                Some(Action::NavigateToHistory { item_id: epic_id })
+            );
+            assert_eq!(
+                page.handle_input("un").unwrap(),
+                Some(Action::EditEpicName { epic_id })
+            );
+            assert_eq!(
+                page.handle_input("ud").unwrap(),
+                Some(Action::EditEpicDescription { epic_id })
+            );
+            assert_eq!(
+                page.handle_input("pr").unwrap(),
+                Some(Action::EditEpicPriority { epic_id })
+            );
+            assert_eq!(
+                page.handle_input("a").unwrap(),
+                Some(Action::AssignEpic { epic_id })
+            );
+            assert_eq!(
+                page.handle_input("dd").unwrap(),
+                Some(Action::EditEpicDueDate { epic_id })
+            );
+            assert_eq!(
+                page.handle_input("co").unwrap(),
+                Some(Action::AddComment { item_id: epic_id })
+            );
+            assert_eq!(
+                page.handle_input("la").unwrap(),
+                Some(Action::AddLabel { item_id: epic_id })
+            );
+            assert_eq!(
+                page.handle_input("lr").unwrap(),
+                Some(Action::RemoveLabel { item_id: epic_id })
+            );
+            assert_eq!(page.handle_input("s").unwrap(), None);
+            assert_eq!(page.sort_order.get(), SortOrder::Priority);
+            assert_eq!(page.handle_input("f tech-debt").unwrap(), None);
+            assert_eq!(page.label_filter.borrow().as_deref(), Some("tech-debt"));
+            assert_eq!(page.handle_input("t bug").unwrap(), None);
+            assert_eq!(page.type_filter.get(), Some(IssueType::Bug));
+            assert_eq!(page.handle_input("t chore").unwrap(), None);
+            assert_eq!(page.type_filter.get(), Some(IssueType::Bug));
+            assert_eq!(page.handle_input("t").unwrap(), None);
+            assert_eq!(page.type_filter.get(), None);
+            assert_eq!(page.handle_input(invalid_story_id).unwrap(), None);
+            assert_eq!(page.handle_input(junk_input).unwrap(), None);
+            assert_eq!(
+                page.handle_input(junk_input_with_valid_prefix).unwrap(),
+                None
+            );
+            assert_eq!(
+                page.handle_input(input_with_trailing_white_spaces).unwrap(),
+                None
+            );
+        }
+    }
+
+    mod story_detail_page {
+        use std::borrow::BorrowMut;
+
+        use super::*;
+
+        #[test]
+        fn draw_page_should_not_throw_error() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .ok()
+                .unwrap();
+            let story_id = db
+                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                .ok()
+                .unwrap();
+
+            let page = StoryDetail {
+                epic_id,
+                story_id,
+                db,
+            };
+            assert_eq!(page.draw_page().is_ok(), true);
+        }
+
+        #[test]
+        fn handle_input_should_not_throw_error() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let epic_id = (&db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap())
+                .to_owned();
+            let story_id = db
+                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+
+            let page = StoryDetail {
+                epic_id,
+                story_id,
+                db,
+            };
+            assert_eq!(page.handle_input("").is_ok(), true);
+        }
+
+        #[test]
+        fn handle_input_should_use_the_epic_a_story_was_moved_to() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap();
+            let story_id = db
+                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+            let other_epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap();
+
+            let page = StoryDetail {
+                epic_id,
+                story_id,
+                db: Rc::clone(&db),
+            };
+            db.move_story(story_id, other_epic_id).unwrap();
+
+            assert_eq!(
+                page.handle_input("d").unwrap(),
+                Some(Action::DeleteStory {
+                    epic_id: other_epic_id,
+                    story_id
+                })
+            );
+        }
+
+        #[test]
+        fn draw_page_should_show_links() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap();
+            let story_id = db
+                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+            let other_id = db
+                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+            db.link_stories(story_id, LinkKind::Blocks, other_id)
+                .unwrap();
+            db.link_stories(other_id, LinkKind::RelatesTo, story_id)
+                .unwrap();
+
+            let page = StoryDetail {
+                epic_id,
+                story_id,
+                db,
+            };
+            assert!(page.draw_page().is_ok());
+        }
+
+        #[test]
+        fn draw_page_should_throw_error_for_invalid_story_id() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap();
+            let _ = db
+                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+
+            let page = StoryDetail {
+                epic_id,
+                story_id: 999,
+                db,
+            };
+            assert_eq!(page.draw_page().is_err(), true);
+        }
+
+        #[test]
+        fn handle_input_should_return_the_correct_actions() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap();
+            let story_id = db
+                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+
+            let page = StoryDetail {
+                epic_id,
+                story_id,
+                db,
+            };
+
+            let p = "p";
+            let u = "u";
+            let d = "d";
+            let some_number = "1";
+            let junk_input = "j983f2j";
+            let junk_input_with_valid_prefix = "p983f2j";
+            let input_with_trailing_white_spaces = "p\n";
+
+            assert_eq!(
+                page.handle_input(p).unwrap(),
+                Some(Action::NavigateToPreviousPage)
+            );
+            assert_eq!(
+                page.handle_input(u).unwrap(),
+                Some(Action::UpdateStoryStatus { epic_id, story_id })
+            );
+            assert_eq!(
+                page.handle_input(d).unwrap(),
+                Some(Action::DeleteStory { epic_id, story_id })
+            );
+            assert_eq!(
+                page.handle_input("h").unwrap(),
+                Some(Action::NavigateToHistory { item_id: story_id })
+            );
+            assert_eq!(
+                page.handle_input("un").unwrap(),
+                Some(Action::EditStoryName { story_id })
+            );
+            assert_eq!(
+                page.handle_input("ud").unwrap(),
+                Some(Action::EditStoryDescription { story_id })
+            );
+            assert_eq!(
+                page.handle_input("up").unwrap(),
+                Some(Action::EditStoryPoints { story_id })
+            );
+            assert_eq!(
+                page.handle_input("pr").unwrap(),
+                Some(Action::EditStoryPriority { story_id })
+            );
+            assert_eq!(
+                page.handle_input("a").unwrap(),
+                Some(Action::AssignStory { story_id })
+            );
+            assert_eq!(
+                page.handle_input("dd").unwrap(),
+                Some(Action::EditStoryDueDate { story_id })
+            );
+            assert_eq!(
+                page.handle_input("co").unwrap(),
+                Some(Action::AddComment { item_id: story_id })
+            );
+            assert_eq!(
+                page.handle_input("la").unwrap(),
+                Some(Action::AddLabel { item_id: story_id })
+            );
+            assert_eq!(
+                page.handle_input("lr").unwrap(),
+                Some(Action::RemoveLabel { item_id: story_id })
+            );
+            assert_eq!(
+                page.handle_input("cs").unwrap(),
+                Some(Action::CreateSubtask { story_id })
+            );
+            assert_eq!(
+                page.handle_input("ty").unwrap(),
+                Some(Action::EditStoryType { story_id })
+            );
+            assert_eq!(
+                page.handle_input("bu").unwrap(),
+                Some(Action::EditBugReport { story_id })
+            );
+            assert_eq!(
+                page.handle_input("li").unwrap(),
+                Some(Action::LinkStory { story_id })
+            );
+            assert_eq!(
+                page.handle_input("ul").unwrap(),
+                Some(Action::UnlinkStory { story_id })
+            );
+            assert_eq!(
+                page.handle_input("mv").unwrap(),
+                Some(Action::MoveStory { story_id })
+            );
+            assert_eq!(page.handle_input("undo").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("redo").unwrap(), Some(Action::Redo));
+            assert_eq!(page.handle_input(some_number).unwrap(), None);
             assert_eq!(page.handle_input(junk_input).unwrap(), None);
             assert_eq!(
                 page.handle_input(junk_input_with_valid_prefix).unwrap(),
@@ -523,4 +1499,495 @@ mod tests {
             );
         }
     }
+
+    mod subtask_detail_page {
+        use super::*;
+
+        fn database_with_subtask() -> (Rc<JiraDatabase>, usize, usize) {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap();
+            let story_id = db
+                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+            let subtask_id = db
+                .create_subtask(Subtask::new("".to_owned(), "".to_owned()), story_id)
+                .unwrap();
+            (db, story_id, subtask_id)
+        }
+
+        #[test]
+        fn draw_page_should_not_throw_error() {
+            let (db, story_id, subtask_id) = database_with_subtask();
+
+            let story_page = StoryDetail {
+                epic_id: 1,
+                story_id,
+                db: Rc::clone(&db),
+            };
+            assert!(story_page.draw_page().is_ok());
+
+            let page = SubtaskDetail {
+                story_id,
+                subtask_id,
+                db,
+            };
+            assert!(page.draw_page().is_ok());
+        }
+
+        #[test]
+        fn draw_page_should_throw_error_for_invalid_subtask_id() {
+            let (db, story_id, _) = database_with_subtask();
+
+            let page = SubtaskDetail {
+                story_id,
+                subtask_id: 999,
+                db,
+            };
+            assert!(page.draw_page().is_err());
+        }
+
+        #[test]
+        fn story_detail_should_navigate_to_its_subtasks() {
+            let (db, story_id, subtask_id) = database_with_subtask();
+            let other_story_id = db
+                .create_story(Story::new("".to_owned(), "".to_owned()), 1)
+                .unwrap();
+
+            let page = StoryDetail {
+                epic_id: 1,
+                story_id,
+                db: Rc::clone(&db),
+            };
+            assert_eq!(
+                page.handle_input(&db.config.issue_key(subtask_id)).unwrap(),
+                Some(Action::NavigateToSubtaskDetail {
+                    story_id,
+                    subtask_id
+                })
+            );
+
+            let other_page = StoryDetail {
+                epic_id: 1,
+                story_id: other_story_id,
+                db: Rc::clone(&db),
+            };
+            assert_eq!(
+                other_page
+                    .handle_input(&db.config.issue_key(subtask_id))
+                    .unwrap(),
+                None
+            );
+        }
+
+        #[test]
+        fn handle_input_should_return_the_correct_actions() {
+            let (db, story_id, subtask_id) = database_with_subtask();
+
+            let page = SubtaskDetail {
+                story_id,
+                subtask_id,
+                db,
+            };
+
+            assert_eq!(
+                page.handle_input("p").unwrap(),
+                Some(Action::NavigateToPreviousPage)
+            );
+            assert_eq!(
+                page.handle_input("u").unwrap(),
+                Some(Action::UpdateSubtaskStatus { subtask_id })
+            );
+            assert_eq!(
+                page.handle_input("d").unwrap(),
+                Some(Action::DeleteSubtask {
+                    story_id,
+                    subtask_id
+                })
+            );
+            assert_eq!(
+                page.handle_input("h").unwrap(),
+                Some(Action::NavigateToHistory {
+                    item_id: subtask_id
+                })
+            );
+            assert_eq!(page.handle_input("undo").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("j983f2j").unwrap(), None);
+            assert_eq!(page.handle_input("p\n").unwrap(), None);
+        }
+    }
+
+    mod my_work_page {
+        use super::*;
+
+        fn database_for(user: &str) -> Rc<JiraDatabase> {
+            let mut db = JiraDatabase::with_database(Box::new(MockDB::new()));
+            db.config.user = Some(user.to_owned());
+            db.add_user(user.to_owned(), "".to_owned()).unwrap();
+            Rc::new(db)
+        }
+
+        #[test]
+        fn draw_page_should_not_throw_error() {
+            let db = database_for("alice");
+
+            let epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap();
+            db.assign_epic(epic_id, Some("alice".to_owned())).unwrap();
+
+            let page = MyWorkPage { db };
+            assert!(page.draw_page().is_ok());
+        }
+
+        #[test]
+        fn handle_input_should_return_the_correct_actions() {
+            let db = database_for("alice");
+
+            let epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap();
+            let story_id = db
+                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+            let other_story_id = db
+                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+            db.assign_epic(epic_id, Some("alice".to_owned())).unwrap();
+            db.assign_story(story_id, Some("alice".to_owned())).unwrap();
+
+            let page = MyWorkPage { db };
+
+            assert_eq!(
+                page.handle_input("p").unwrap(),
+                Some(Action::NavigateToPreviousPage)
+            );
+            assert_eq!(page.handle_input("undo").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("redo").unwrap(), Some(Action::Redo));
+            assert_eq!(
+                page.handle_input(&format!("PROJ-{}", epic_id)).unwrap(),
+                Some(Action::NavigateToEpicDetail { epic_id })
+            );
+            assert_eq!(
+                page.handle_input(&story_id.to_string()).unwrap(),
+                Some(Action::NavigateToStoryDetail { epic_id, story_id })
+            );
+            assert_eq!(
+                page.handle_input(&other_story_id.to_string()).unwrap(),
+                None
+            );
+            assert_eq!(page.handle_input("999").unwrap(), None);
+            assert_eq!(page.handle_input("p\n").unwrap(), None);
+        }
+    }
+
+    mod sprint_pages {
+        use super::*;
+        use crate::models::Sprint;
+
+        // an epic with two stories and a planned sprint holding the first one
+        fn sprint_database() -> (Rc<JiraDatabase>, Vec<usize>, usize) {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap();
+            let stories = (0..2)
+                .map(|_| {
+                    db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                        .unwrap()
+                })
+                .collect_vec();
+            let sprint_id = db
+                .create_sprint(Sprint::new("sprint".to_owned(), "goal".to_owned()))
+                .unwrap();
+            db.add_story_to_sprint(sprint_id, stories[0]).unwrap();
+            (db, stories, sprint_id)
+        }
+
+        #[test]
+        fn draw_pages_should_not_throw_error() {
+            let (db, _, sprint_id) = sprint_database();
+
+            let sprints_page = SprintsPage { db: db.clone() };
+            let planning_page = SprintPlanning {
+                sprint_id,
+                db: db.clone(),
+            };
+            let active_sprint_page = ActiveSprintPage { db: db.clone() };
+
+            assert!(sprints_page.draw_page().is_ok());
+            assert!(planning_page.draw_page().is_ok());
+            assert!(active_sprint_page.draw_page().is_ok());
+
+            db.start_sprint(sprint_id).unwrap();
+            assert!(active_sprint_page.draw_page().is_ok());
+        }
+
+        #[test]
+        fn sprints_page_should_return_the_correct_actions() {
+            let (db, _, sprint_id) = sprint_database();
+
+            let page = SprintsPage { db: db.clone() };
+
+            assert_eq!(
+                page.handle_input("p").unwrap(),
+                Some(Action::NavigateToPreviousPage)
+            );
+            assert_eq!(page.handle_input("c").unwrap(), Some(Action::CreateSprint));
+            // there is no active sprint yet
+            assert_eq!(page.handle_input("a").unwrap(), None);
+            assert_eq!(
+                page.handle_input(&format!("SPRINT-{}", sprint_id)).unwrap(),
+                Some(Action::NavigateToSprintPlanning { sprint_id })
+            );
+            // sprints are not entered as issue keys
+            assert_eq!(page.handle_input("PROJ-1").unwrap(), None);
+
+            db.start_sprint(sprint_id).unwrap();
+            assert_eq!(
+                page.handle_input("a").unwrap(),
+                Some(Action::NavigateToActiveSprint)
+            );
+        }
+
+        #[test]
+        fn sprint_planning_should_return_the_correct_actions() {
+            let (db, stories, sprint_id) = sprint_database();
+
+            let page = SprintPlanning { sprint_id, db };
+
+            assert_eq!(
+                page.handle_input(&format!("a PROJ-{}", stories[1]))
+                    .unwrap(),
+                Some(Action::AddStoryToSprint {
+                    sprint_id,
+                    story_id: stories[1]
+                })
+            );
+            // already in the sprint
+            assert_eq!(
+                page.handle_input(&format!("a PROJ-{}", stories[0]))
+                    .unwrap(),
+                None
+            );
+            assert_eq!(
+                page.handle_input(&format!("r {}", stories[0])).unwrap(),
+                Some(Action::RemoveStoryFromSprint {
+                    sprint_id,
+                    story_id: stories[0]
+                })
+            );
+            assert_eq!(
+                page.handle_input(&format!("r {}", stories[1])).unwrap(),
+                None
+            );
+            assert_eq!(
+                page.handle_input("st").unwrap(),
+                Some(Action::StartSprint { sprint_id })
+            );
+            assert_eq!(
+                page.handle_input("p").unwrap(),
+                Some(Action::NavigateToPreviousPage)
+            );
+            assert_eq!(page.handle_input("a").unwrap(), None);
+        }
+
+        #[test]
+        fn active_sprint_page_should_return_the_correct_actions() {
+            let (db, stories, sprint_id) = sprint_database();
+
+            let page = ActiveSprintPage { db: db.clone() };
+            assert_eq!(page.handle_input("co").unwrap(), None);
+
+            db.start_sprint(sprint_id).unwrap();
+            assert_eq!(
+                page.handle_input("co").unwrap(),
+                Some(Action::CompleteSprint { sprint_id })
+            );
+            assert_eq!(
+                page.handle_input(&format!("PROJ-{}", stories[0])).unwrap(),
+                Some(Action::NavigateToStoryDetail {
+                    epic_id: 1,
+                    story_id: stories[0]
+                })
+            );
+            assert_eq!(
+                page.handle_input(&format!("PROJ-{}", stories[1])).unwrap(),
+                None
+            );
+            assert_eq!(
+                page.handle_input("p").unwrap(),
+                Some(Action::NavigateToPreviousPage)
+            );
+        }
+    }
+
+    mod board_page {
+        use super::*;
+        use crate::config::WorkflowState;
+
+        #[test]
+        fn draw_page_should_not_throw_error() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap();
+            db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+
+            let project_page = BoardPage {
+                epic_id: None,
+                db: db.clone(),
+            };
+            let epic_page = BoardPage {
+                epic_id: Some(epic_id),
+                db,
+            };
+
+            assert!(project_page.draw_page().is_ok());
+            assert!(epic_page.draw_page().is_ok());
+        }
+
+        #[test]
+        fn handle_input_should_return_the_correct_actions() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap();
+            let story_id = db
+                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+            let other_epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap();
+            let other_story_id = db
+                .create_story(Story::new("".to_owned(), "".to_owned()), other_epic_id)
+                .unwrap();
+            db.update_story_status(other_story_id, Status::Closed)
+                .unwrap();
+
+            let page = BoardPage {
+                epic_id: Some(epic_id),
+                db: db.clone(),
+            };
+
+            assert_eq!(
+                page.handle_input(&format!("> PROJ-{}", story_id)).unwrap(),
+                Some(Action::MoveCard {
+                    story_id,
+                    state: "IN PROGRESS".to_owned()
+                })
+            );
+            // open is the leftmost column
+            assert_eq!(
+                page.handle_input(&format!("< PROJ-{}", story_id)).unwrap(),
+                None
+            );
+            // not on this epic's board
+            assert_eq!(
+                page.handle_input(&format!("< PROJ-{}", other_story_id))
+                    .unwrap(),
+                None
+            );
+            assert_eq!(
+                page.handle_input(&format!("PROJ-{}", story_id)).unwrap(),
+                Some(Action::NavigateToStoryDetail { epic_id, story_id })
+            );
+            assert_eq!(
+                page.handle_input("p").unwrap(),
+                Some(Action::NavigateToPreviousPage)
+            );
+            assert_eq!(page.handle_input("undo").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("redo").unwrap(), Some(Action::Redo));
+            assert_eq!(page.handle_input(">").unwrap(), None);
+
+            let page = BoardPage { epic_id: None, db };
+
+            assert_eq!(
+                page.handle_input(&format!("< PROJ-{}", other_story_id))
+                    .unwrap(),
+                Some(Action::MoveCard {
+                    story_id: other_story_id,
+                    state: "RESOLVED".to_owned()
+                })
+            );
+            // closed is the rightmost column
+            assert_eq!(
+                page.handle_input(&format!("> PROJ-{}", other_story_id))
+                    .unwrap(),
+                None
+            );
+        }
+
+        #[test]
+        fn handle_input_should_follow_the_workflow_columns() {
+            let mut db = JiraDatabase::with_database(Box::new(MockDB::new()));
+            db.config.workflow.states.insert(
+                2,
+                WorkflowState {
+                    name: "In Review".to_owned(),
+                    category: Status::InProgress,
+                },
+            );
+            let epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap();
+            let story_id = db
+                .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+            db.update_story_status(story_id, Status::InProgress)
+                .unwrap();
+
+            let page = BoardPage {
+                epic_id: None,
+                db: Rc::new(db),
+            };
+
+            assert!(page.draw_page().is_ok());
+            assert_eq!(
+                page.handle_input(&format!("> PROJ-{}", story_id)).unwrap(),
+                Some(Action::MoveCard {
+                    story_id,
+                    state: "In Review".to_owned()
+                })
+            );
+        }
+    }
+
+    mod history_page {
+        use super::*;
+
+        #[test]
+        fn draw_page_should_not_throw_error() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let epic_id = db
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap();
+            db.close_epic(epic_id).unwrap();
+
+            let page = HistoryPage {
+                item_id: epic_id,
+                db,
+            };
+            assert!(page.draw_page().is_ok());
+        }
+
+        #[test]
+        fn handle_input_should_return_the_correct_actions() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let page = HistoryPage { item_id: 1, db };
+
+            assert_eq!(
+                page.handle_input("p").unwrap(),
+                Some(Action::NavigateToPreviousPage)
+            );
+            assert_eq!(page.handle_input("undo").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("redo").unwrap(), Some(Action::Redo));
+            assert_eq!(page.handle_input("h").unwrap(), None);
+            assert_eq!(page.handle_input("p\n").unwrap(), None);
+        }
+    }
 }
@@ -1,8 +1,22 @@
-use std::cmp::{max, min};
+use std::{
+    cmp::{max, min},
+    collections::BTreeSet,
+};
 
 use ellipse::Ellipse;
+use itertools::Itertools;
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+
+use crate::models::{is_overdue, Comment, DBState, IssueType, ItemRow, Sprint, Status};
+
+pub const ROW_HEADER: &str =
+    "     id     |        name        | type  | priority | points |   status   ";
 
 pub fn get_column_string(text: &str, width: usize) -> String {
+    // multi-line descriptions are shown on a single row
+    let text = text.lines().join(" ");
+    let text = text.as_str();
     let len = text.len();
 
     return match len.cmp(&width) {
@@ -25,6 +39,178 @@ pub fn get_column_string(text: &str, width: usize) -> String {
     };
 }
 
+pub const SPRINT_ROW_HEADER: &str =
+    "     id     |        name        |   state    |          dates          ";
+
+pub fn get_sprint_row_string(key: &str, sprint: &Sprint) -> String {
+    format!(
+        "{} | {} | {} | {}",
+        get_column_string(key, 11),
+        get_column_string(&sprint.name, 18),
+        get_column_string(&sprint.state.to_string(), 10),
+        get_column_string(&get_sprint_dates_string(sprint), 23)
+    )
+}
+
+// open ends are shown as "?"
+pub fn get_sprint_dates_string(sprint: &Sprint) -> String {
+    let date_string = |date: Option<NaiveDate>| date.map_or("?".to_owned(), |d| d.to_string());
+    format!(
+        "{} - {}",
+        date_string(sprint.start),
+        date_string(sprint.end)
+    )
+}
+
+// a line of an epic or story listing, matching ROW_HEADER
+pub fn get_row_string(row: &ItemRow) -> String {
+    format!(
+        "{} | {} | {} | {} | {} | {}",
+        get_column_string(&row.key, 11),
+        get_column_string(&row.name, 18),
+        get_column_string(&get_issue_type_string(row.issue_type), 5),
+        get_column_string(&row.priority.to_string(), 8),
+        get_column_string(&get_points_string(row.points), 6),
+        get_column_string(&row.status.to_string(), 11)
+    )
+}
+
+pub const BOARD_COLUMN_WIDTH: usize = 16;
+
+// the columns of a board side by side, each a title over its cards
+pub fn get_board_lines(columns: &[(String, Vec<String>)]) -> Vec<String> {
+    let join_cells =
+        |cells: Vec<String>, separator: &str| cells.join(separator).trim_end().to_owned();
+
+    let mut lines = vec![
+        join_cells(
+            columns
+                .iter()
+                .map(|(title, _)| get_column_string(title, BOARD_COLUMN_WIDTH))
+                .collect(),
+            " | ",
+        ),
+        join_cells(
+            columns
+                .iter()
+                .map(|_| "-".repeat(BOARD_COLUMN_WIDTH))
+                .collect(),
+            "-+-",
+        ),
+    ];
+    let height = columns
+        .iter()
+        .map(|(_, cards)| cards.len())
+        .max()
+        .unwrap_or(0);
+    for index in 0..height {
+        lines.push(join_cells(
+            columns
+                .iter()
+                .map(|(_, cards)| {
+                    get_column_string(
+                        cards.get(index).map_or("", String::as_str),
+                        BOARD_COLUMN_WIDTH,
+                    )
+                })
+                .collect(),
+            " | ",
+        ));
+    }
+    lines
+}
+
+// epics have no issue type of their own
+pub fn get_issue_type_string(issue_type: Option<IssueType>) -> String {
+    issue_type.map_or("EPIC".to_owned(), |issue_type| issue_type.to_string())
+}
+
+pub fn get_points_string(points: Option<u32>) -> String {
+    points.map_or("-".to_owned(), |points| points.to_string())
+}
+
+// a user's display name along with their username, or "-" when nobody is set
+pub fn get_user_string(db_state: &DBState, username: Option<&str>) -> String {
+    match username {
+        Some(username) => match db_state.users.get(username) {
+            Some(user) => format!("{} ({})", user.name, username),
+            None => username.to_owned(),
+        },
+        None => "-".to_owned(),
+    }
+}
+
+// a listing row, shown in red once the item is past its due date
+pub fn get_listing_row_string(row: &ItemRow, today: NaiveDate) -> String {
+    let row_string = get_row_string(row);
+    if row.is_overdue(today) {
+        return format!("\x1b[31m{}\x1b[0m", row_string);
+    }
+    row_string
+}
+
+// when an item was created and last updated, and when it is due
+pub fn get_dates_string(
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+    due: Option<NaiveDate>,
+    status: Status,
+    today: NaiveDate,
+) -> String {
+    let timestamp_string = |timestamp: Option<DateTime<Utc>>| {
+        timestamp.map_or("-".to_owned(), |timestamp| {
+            timestamp
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+    };
+    let due_string = match due {
+        Some(due) if is_overdue(Some(due), status, today) => format!("{} (OVERDUE)", due),
+        Some(due) => due.to_string(),
+        None => "-".to_owned(),
+    };
+    format!(
+        "created: {} | updated: {} | due: {}",
+        timestamp_string(created_at),
+        timestamp_string(updated_at),
+        due_string
+    )
+}
+
+pub fn get_labels_string(labels: &BTreeSet<String>) -> String {
+    if labels.is_empty() {
+        return "-".to_owned();
+    }
+    labels.iter().join(", ")
+}
+
+// a comment's author and time followed by its wrapped, indented body
+pub fn get_comment_lines(comment: &Comment, width: usize) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{} ({}):",
+        comment.author,
+        comment
+            .timestamp
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+    )];
+    lines.extend(
+        get_wrapped_lines(&comment.body, width)
+            .into_iter()
+            .map(|line| format!("  {}", line)),
+    );
+    lines
+}
+
+// breaks text into lines of at most `width` characters, keeping its own line breaks
+pub fn get_wrapped_lines(text: &str, width: usize) -> Vec<String> {
+    textwrap::wrap(text, width)
+        .into_iter()
+        .map(|line| line.into_owned())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -62,5 +248,106 @@ mod tests {
         assert_eq!(get_column_string(text2, width), "test  ".to_owned());
         assert_eq!(get_column_string(text3, width), "testme".to_owned());
         assert_eq!(get_column_string(text4, width), "tes...".to_owned());
+        assert_eq!(get_column_string("te\nst", width), "te st ".to_owned());
+    }
+
+    #[test]
+    fn test_get_board_lines() {
+        let columns = vec![
+            (
+                "OPEN".to_owned(),
+                vec!["PROJ-2 a".to_owned(), "PROJ-3 b".to_owned()],
+            ),
+            (
+                "DONE".to_owned(),
+                vec!["PROJ-4 a very long name".to_owned()],
+            ),
+        ];
+
+        assert_eq!(
+            get_board_lines(&columns),
+            vec![
+                "OPEN             | DONE",
+                "-----------------+-----------------",
+                "PROJ-2 a         | PROJ-4 a very...",
+                "PROJ-3 b         |",
+            ]
+        );
+        assert_eq!(get_board_lines(&[]), vec!["", ""]);
+    }
+
+    #[test]
+    fn test_get_issue_type_string() {
+        assert_eq!(get_issue_type_string(None), "EPIC");
+        assert_eq!(get_issue_type_string(Some(IssueType::Spike)), "SPIKE");
+    }
+
+    #[test]
+    fn test_get_listing_row_string() {
+        let today = NaiveDate::from_ymd_opt(2030, 1, 31).unwrap();
+        let mut row = ItemRow {
+            key: "PROJ-1".to_owned(),
+            id: 1,
+            name: "".to_owned(),
+            description: "".to_owned(),
+            status: Status::Open,
+            points: None,
+            priority: Default::default(),
+            assignee: None,
+            labels: "".to_owned(),
+            due: Some(today),
+            issue_type: None,
+        };
+
+        assert_eq!(get_listing_row_string(&row, today), get_row_string(&row));
+
+        row.due = today.pred_opt();
+        assert_eq!(
+            get_listing_row_string(&row, today),
+            format!("\x1b[31m{}\x1b[0m", get_row_string(&row))
+        );
+
+        row.status = Status::Closed;
+        assert_eq!(get_listing_row_string(&row, today), get_row_string(&row));
+    }
+
+    #[test]
+    fn test_get_dates_string() {
+        let today = NaiveDate::from_ymd_opt(2030, 1, 31).unwrap();
+
+        assert_eq!(
+            get_dates_string(None, None, None, Status::Open, today),
+            "created: - | updated: - | due: -"
+        );
+        assert_eq!(
+            get_dates_string(None, None, today.pred_opt(), Status::InProgress, today),
+            "created: - | updated: - | due: 2030-01-30 (OVERDUE)"
+        );
+        assert_eq!(
+            get_dates_string(None, None, today.pred_opt(), Status::Resolved, today),
+            "created: - | updated: - | due: 2030-01-30"
+        );
+    }
+
+    #[test]
+    fn test_get_comment_lines() {
+        let comment = Comment {
+            author: "alice".to_owned(),
+            timestamp: Utc::now(),
+            body: "looks good to me\nship it".to_owned(),
+        };
+
+        let lines = get_comment_lines(&comment, 10);
+        assert!(lines[0].starts_with("alice ("));
+        assert_eq!(lines[1..], ["  looks good", "  to me", "  ship it"]);
+    }
+
+    #[test]
+    fn test_get_wrapped_lines() {
+        assert_eq!(get_wrapped_lines("", 10), vec!["".to_owned()]);
+        assert_eq!(
+            get_wrapped_lines("acceptance criteria:\n- it works", 10),
+            vec!["acceptance", "criteria:", "- it works"]
+        );
     }
 }
@@ -1,16 +1,76 @@
+use chrono::NaiveDate;
+use itertools::Itertools;
+
 use crate::{
-    io_utils::get_user_input,
-    models::{Epic, Status, Story},
+    io_utils::{compose_in_editor, get_user_input},
+    models::{
+        BugReport, Epic, IssueType, LinkKind, Priority, Severity, Sprint, Status, Story, Subtask,
+    },
 };
 
+// typed instead of a description to write it in the user's editor
+const EDITOR_COMMAND: &str = ":e";
+
+// asks for story points from the given point scale, starting from the current estimate
+pub type EstimatePrompt = dyn Fn(&[u32], Option<u32>) -> Option<u32>;
+
+// asks for one of the registered usernames, starting from the current one
+pub type AssignPrompt = dyn Fn(&[String], Option<&str>) -> Option<String>;
+
+// asks which of the given labels to remove, if any
+pub type RemoveLabelPrompt = dyn Fn(&[String]) -> Option<String>;
+
+// asks which of the workflow states a story may move to from its current one it should move to
+pub type TransitionPrompt = dyn Fn(&str, &[String]) -> Option<String>;
+
+// asks which of the given epics to move a story to, if any
+pub type MoveStoryPrompt = dyn Fn(&[String]) -> Option<String>;
+
+// asks which of the linked stories to unlink, if any
+pub type UnlinkPrompt = dyn Fn(&[String]) -> Option<String>;
+
+// asks where the unfinished stories of a completed sprint go: None cancels, Some(None) moves
+// them back to the backlog and Some(Some(key)) into the given planned sprint
+pub type CompleteSprintPrompt = dyn Fn(&[String]) -> Option<Option<String>>;
+
+// a link typed at the link prompt. Inbound links start from the other story, so
+// "is blocked by PROJ-3" is stored as PROJ-3 blocking this story.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkInput {
+    pub kind: LinkKind,
+    pub inbound: bool,
+    pub key: String,
+}
+
 pub struct Prompts {
     pub create_epic: Box<dyn Fn() -> Epic>,
     pub create_story: Box<dyn Fn() -> Story>,
     pub delete_epic: Box<dyn Fn() -> bool>,
     pub delete_story: Box<dyn Fn() -> bool>,
+    pub create_subtask: Box<dyn Fn() -> Subtask>,
+    pub delete_subtask: Box<dyn Fn() -> bool>,
     pub update_status: Box<dyn Fn() -> Status>,
+    pub transition: Box<TransitionPrompt>,
     pub close_epic: Box<dyn Fn() -> bool>,
     pub reopen_epic: Box<dyn Fn() -> bool>,
+    pub reload_and_retry: Box<dyn Fn() -> bool>,
+    pub resolve_blocked: Box<dyn Fn(&str) -> bool>,
+    pub edit_name: Box<dyn Fn(&str) -> String>,
+    pub edit_description: Box<dyn Fn(&str) -> String>,
+    pub estimate: Box<EstimatePrompt>,
+    pub priority: Box<dyn Fn(Priority) -> Priority>,
+    pub issue_type: Box<dyn Fn(IssueType) -> IssueType>,
+    pub bug_report: Box<dyn Fn(&BugReport) -> BugReport>,
+    pub assign: Box<AssignPrompt>,
+    pub add_label: Box<dyn Fn() -> String>,
+    pub remove_label: Box<RemoveLabelPrompt>,
+    pub due_date: Box<dyn Fn(Option<NaiveDate>) -> Option<NaiveDate>>,
+    pub comment: Box<dyn Fn() -> String>,
+    pub link_story: Box<dyn Fn() -> Option<LinkInput>>,
+    pub unlink_story: Box<UnlinkPrompt>,
+    pub move_story: Box<MoveStoryPrompt>,
+    pub create_sprint: Box<dyn Fn() -> Sprint>,
+    pub complete_sprint: Box<CompleteSprintPrompt>,
 }
 
 impl Prompts {
@@ -20,9 +80,30 @@ impl Prompts {
             create_story: Box::new(create_story_prompt),
             delete_epic: Box::new(delete_epic_prompt),
             delete_story: Box::new(delete_story_prompt),
+            create_subtask: Box::new(create_subtask_prompt),
+            delete_subtask: Box::new(delete_subtask_prompt),
             update_status: Box::new(update_status_prompt),
+            transition: Box::new(transition_prompt),
             close_epic: Box::new(close_epic_prompt),
             reopen_epic: Box::new(reopen_epic_prompt),
+            reload_and_retry: Box::new(reload_and_retry_prompt),
+            resolve_blocked: Box::new(resolve_blocked_prompt),
+            edit_name: Box::new(edit_name_prompt),
+            edit_description: Box::new(edit_description_prompt),
+            estimate: Box::new(estimate_prompt),
+            priority: Box::new(priority_prompt),
+            issue_type: Box::new(issue_type_prompt),
+            bug_report: Box::new(bug_report_prompt),
+            assign: Box::new(assign_prompt),
+            add_label: Box::new(add_label_prompt),
+            remove_label: Box::new(remove_label_prompt),
+            due_date: Box::new(due_date_prompt),
+            comment: Box::new(comment_prompt),
+            link_story: Box::new(link_story_prompt),
+            unlink_story: Box::new(unlink_story_prompt),
+            move_story: Box::new(move_story_prompt),
+            create_sprint: Box::new(create_sprint_prompt),
+            complete_sprint: Box::new(complete_sprint_prompt),
         }
     }
 }
@@ -31,20 +112,34 @@ fn create_epic_prompt() -> Epic {
     println!("{:-<29}", "");
     println!("Epic Name:");
     let name = get_user_input();
-    println!("Epic Description:");
-    let description = get_user_input();
+    println!(
+        "Epic Description (type {} to use your editor):",
+        EDITOR_COMMAND
+    );
+    let description = get_description_input("");
 
-    return Epic::new(name, description);
+    let mut epic = Epic::new(name, description);
+    epic.priority = get_priority_input(epic.priority);
+    epic
 }
 
 fn create_story_prompt() -> Story {
     println!("{:-<29}", "");
     println!("Story Name:");
     let name = get_user_input();
-    println!("Story Description:");
-    let description = get_user_input();
+    println!(
+        "Story Description (type {} to use your editor):",
+        EDITOR_COMMAND
+    );
+    let description = get_description_input("");
 
-    return Story::new(name, description);
+    let mut story = Story::new(name, description);
+    story.priority = get_priority_input(story.priority);
+    story.set_issue_type(get_issue_type_input(story.issue_type));
+    if let Some(bug) = &story.bug {
+        story.bug = Some(get_bug_report_input(bug));
+    }
+    story
 }
 
 fn delete_epic_prompt() -> bool {
@@ -77,11 +172,40 @@ fn delete_story_prompt() -> bool {
     }
 }
 
+fn create_subtask_prompt() -> Subtask {
+    println!("{:-<29}", "");
+    println!("Sub-task Name:");
+    let name = get_user_input();
+    println!(
+        "Sub-task Description (type {} to use your editor):",
+        EDITOR_COMMAND
+    );
+    let description = get_description_input("");
+
+    Subtask::new(name, description)
+}
+
+fn delete_subtask_prompt() -> bool {
+    println!("{:-<29}", "");
+    loop {
+        println!("Are you sure you want to delete this sub-task? (Y/n):");
+        match get_user_input().as_str() {
+            "Y" | "y" => return true,
+            "N" | "n" => return false,
+            _ => {
+                println!("Invalid input! Please try again.");
+                continue;
+            }
+        };
+    }
+}
+
 fn update_status_prompt() -> Status {
     println!("{:-<29}", "");
     loop {
-        println!("Please enter new status. (In [P]rogress/[C]losed/[R]esolved):");
+        println!("Please enter new status. ([O]pen/In [P]rogress/[C]losed/[R]esolved):");
         match get_user_input().as_str() {
+            "O" | "o" => return Status::Open,
             "P" | "p" => return Status::InProgress,
             "C" | "c" => return Status::Closed,
             "R" | "r" => return Status::Resolved,
@@ -93,6 +217,44 @@ fn update_status_prompt() -> Status {
     }
 }
 
+fn transition_prompt(current: &str, next_states: &[String]) -> Option<String> {
+    println!("{:-<29}", "");
+    if next_states.is_empty() {
+        println!("{} is a final state, the story cannot move on!", current);
+        return None;
+    }
+    loop {
+        println!(
+            "Move from {} to ({}; leave empty to cancel):",
+            current,
+            next_states
+                .iter()
+                .enumerate()
+                .map(|(index, state)| format!("[{}] {}", index + 1, state))
+                .join("/")
+        );
+        let input = get_user_input();
+        if input.is_empty() {
+            return None;
+        }
+        let chosen = match input.parse::<usize>() {
+            Ok(number) => number
+                .checked_sub(1)
+                .and_then(|index| next_states.get(index)),
+            Err(_) => next_states
+                .iter()
+                .find(|state| state.eq_ignore_ascii_case(&input)),
+        };
+        match chosen {
+            Some(state) => return Some(state.clone()),
+            None => {
+                println!("Invalid input! Please try again.");
+                continue;
+            }
+        }
+    }
+}
+
 fn close_epic_prompt() -> bool {
     println!("{:-<29}", "");
     loop {
@@ -122,3 +284,386 @@ fn reopen_epic_prompt() -> bool {
         }
     }
 }
+
+fn reload_and_retry_prompt() -> bool {
+    println!("{:-<29}", "");
+    loop {
+        println!(
+            "Someone else changed the tracker since you loaded it. Reload and try again? (Y/n)"
+        );
+        match get_user_input().as_str() {
+            "Y" | "y" => return true,
+            "N" | "n" => return false,
+            _ => {
+                println!("Invalid input! Please try again!");
+                continue;
+            }
+        }
+    }
+}
+
+fn edit_name_prompt(current: &str) -> String {
+    println!("{:-<29}", "");
+    println!("Current Name: {}", current);
+    println!("New Name (leave empty to keep the current one):");
+    let name = get_user_input();
+
+    if name.is_empty() {
+        return current.to_owned();
+    }
+    name
+}
+
+fn edit_description_prompt(current: &str) -> String {
+    println!("{:-<29}", "");
+    println!("Current Description: {}", current);
+    println!(
+        "New Description (leave empty to keep the current one, type {} to use your editor):",
+        EDITOR_COMMAND
+    );
+    let description = get_description_input(current);
+
+    if description.is_empty() {
+        return current.to_owned();
+    }
+    description
+}
+
+fn estimate_prompt(point_scale: &[u32], current: Option<u32>) -> Option<u32> {
+    println!("{:-<29}", "");
+    loop {
+        println!(
+            "Story Points ({}; leave empty to keep {}, - for none):",
+            point_scale.iter().join("/"),
+            current.map_or("none".to_owned(), |points| points.to_string())
+        );
+        match get_user_input().as_str() {
+            "" => return current,
+            "-" => return None,
+            input => match input.parse::<u32>() {
+                Ok(points) if point_scale.contains(&points) => return Some(points),
+                _ => {
+                    println!("Invalid input! Please try again.");
+                    continue;
+                }
+            },
+        }
+    }
+}
+
+fn priority_prompt(current: Priority) -> Priority {
+    println!("{:-<29}", "");
+    get_priority_input(current)
+}
+
+fn get_priority_input(current: Priority) -> Priority {
+    loop {
+        println!(
+            "Priority ([B]locker/[C]ritical/[M]ajor/m[I]nor/[T]rivial; leave empty to keep {}):",
+            current
+        );
+        match get_user_input().as_str() {
+            "" => return current,
+            "B" | "b" => return Priority::Blocker,
+            "C" | "c" => return Priority::Critical,
+            "M" | "m" => return Priority::Major,
+            "I" | "i" => return Priority::Minor,
+            "T" | "t" => return Priority::Trivial,
+            _ => {
+                println!("Invalid input! Please try again.");
+                continue;
+            }
+        }
+    }
+}
+
+fn issue_type_prompt(current: IssueType) -> IssueType {
+    println!("{:-<29}", "");
+    get_issue_type_input(current)
+}
+
+fn get_issue_type_input(current: IssueType) -> IssueType {
+    loop {
+        println!(
+            "Type ([B]ug/[T]ask/[S]tory/s[P]ike; leave empty to keep {}):",
+            current
+        );
+        match get_user_input().as_str() {
+            "" => return current,
+            "B" | "b" => return IssueType::Bug,
+            "T" | "t" => return IssueType::Task,
+            "S" | "s" => return IssueType::Story,
+            "P" | "p" => return IssueType::Spike,
+            _ => {
+                println!("Invalid input! Please try again.");
+                continue;
+            }
+        }
+    }
+}
+
+fn bug_report_prompt(current: &BugReport) -> BugReport {
+    println!("{:-<29}", "");
+    get_bug_report_input(current)
+}
+
+fn get_bug_report_input(current: &BugReport) -> BugReport {
+    let severity = loop {
+        println!(
+            "Severity ([C]ritical/[M]ajor/m[I]nor/c[O]smetic; leave empty to keep {}):",
+            current.severity
+        );
+        match get_user_input().as_str() {
+            "" => break current.severity,
+            "C" | "c" => break Severity::Critical,
+            "M" | "m" => break Severity::Major,
+            "I" | "i" => break Severity::Minor,
+            "O" | "o" => break Severity::Cosmetic,
+            _ => {
+                println!("Invalid input! Please try again.");
+                continue;
+            }
+        }
+    };
+    println!(
+        "Steps to Reproduce (type {} to use your editor; leave empty to keep the current steps):",
+        EDITOR_COMMAND
+    );
+    let steps_to_reproduce = match get_description_input(&current.steps_to_reproduce) {
+        steps if steps.is_empty() => current.steps_to_reproduce.clone(),
+        steps => steps,
+    };
+
+    BugReport {
+        severity,
+        steps_to_reproduce,
+    }
+}
+
+fn assign_prompt(usernames: &[String], current: Option<&str>) -> Option<String> {
+    println!("{:-<29}", "");
+    loop {
+        println!(
+            "Assign to ({}; leave empty to keep {}, - for nobody):",
+            usernames.iter().join("/"),
+            current.unwrap_or("nobody")
+        );
+        match get_user_input().as_str() {
+            "" => return current.map(str::to_owned),
+            "-" => return None,
+            input if usernames.iter().any(|username| username == input) => {
+                return Some(input.to_owned())
+            }
+            _ => {
+                println!("Invalid input! Please try again.");
+                continue;
+            }
+        }
+    }
+}
+
+fn add_label_prompt() -> String {
+    println!("{:-<29}", "");
+    println!("New Label (leave empty to cancel):");
+    get_user_input()
+}
+
+fn remove_label_prompt(labels: &[String]) -> Option<String> {
+    println!("{:-<29}", "");
+    if labels.is_empty() {
+        println!("There are no labels to remove!");
+        return None;
+    }
+    loop {
+        println!(
+            "Label to remove ({}; leave empty to cancel):",
+            labels.iter().join("/")
+        );
+        match get_user_input().as_str() {
+            "" => return None,
+            input if labels.iter().any(|label| label == input) => return Some(input.to_owned()),
+            _ => {
+                println!("Invalid input! Please try again.");
+                continue;
+            }
+        }
+    }
+}
+
+fn resolve_blocked_prompt(warning: &str) -> bool {
+    println!("{:-<29}", "");
+    loop {
+        println!("Warning: {}. Change the status anyway? (Y/n)", warning);
+        match get_user_input().as_str() {
+            "Y" | "y" => return true,
+            "N" | "n" => return false,
+            _ => {
+                println!("Invalid input! Please try again.");
+                continue;
+            }
+        }
+    }
+}
+
+fn link_story_prompt() -> Option<LinkInput> {
+    println!("{:-<29}", "");
+    let (kind, inbound) = loop {
+        println!("Link type ([b]locks/[i]s blocked by/[r]elates to; leave empty to cancel):");
+        match get_user_input().as_str() {
+            "" => return None,
+            "B" | "b" => break (LinkKind::Blocks, false),
+            "I" | "i" => break (LinkKind::Blocks, true),
+            "R" | "r" => break (LinkKind::RelatesTo, false),
+            _ => {
+                println!("Invalid input! Please try again.");
+                continue;
+            }
+        }
+    };
+    println!("Story to link to:");
+    Some(LinkInput {
+        kind,
+        inbound,
+        key: get_user_input(),
+    })
+}
+
+fn unlink_story_prompt(keys: &[String]) -> Option<String> {
+    println!("{:-<29}", "");
+    if keys.is_empty() {
+        println!("There are no links to remove!");
+        return None;
+    }
+    loop {
+        println!(
+            "Story to unlink ({}; leave empty to cancel):",
+            keys.iter().join("/")
+        );
+        match get_user_input().as_str() {
+            "" => return None,
+            input if keys.iter().any(|key| key == input) => return Some(input.to_owned()),
+            _ => {
+                println!("Invalid input! Please try again.");
+                continue;
+            }
+        }
+    }
+}
+
+fn move_story_prompt(epic_keys: &[String]) -> Option<String> {
+    println!("{:-<29}", "");
+    if epic_keys.is_empty() {
+        println!("There are no other open epics to move the story to!");
+        return None;
+    }
+    loop {
+        println!(
+            "Move to epic ({}; leave empty to cancel):",
+            epic_keys.iter().join("/")
+        );
+        match get_user_input().as_str() {
+            "" => return None,
+            input if epic_keys.iter().any(|key| key == input) => return Some(input.to_owned()),
+            _ => {
+                println!("Invalid input! Please try again.");
+                continue;
+            }
+        }
+    }
+}
+
+fn create_sprint_prompt() -> Sprint {
+    println!("{:-<29}", "");
+    println!("Sprint Name:");
+    let name = get_user_input();
+    println!("Sprint Goal:");
+    let goal = get_user_input();
+
+    let mut sprint = Sprint::new(name, goal);
+    sprint.start = get_date_input("Start Date");
+    sprint.end = get_date_input("End Date");
+    sprint
+}
+
+fn complete_sprint_prompt(sprint_keys: &[String]) -> Option<Option<String>> {
+    println!("{:-<29}", "");
+    loop {
+        println!(
+            "Move unfinished stories to ({}; b for the backlog, leave empty to cancel):",
+            sprint_keys.iter().chain(["b".to_owned()].iter()).join("/")
+        );
+        match get_user_input().as_str() {
+            "" => return None,
+            "b" => return Some(None),
+            input if sprint_keys.iter().any(|key| key == input) => {
+                return Some(Some(input.to_owned()))
+            }
+            _ => {
+                println!("Invalid input! Please try again.");
+                continue;
+            }
+        }
+    }
+}
+
+fn get_date_input(label: &str) -> Option<NaiveDate> {
+    loop {
+        println!("{} (YYYY-MM-DD; leave empty for none):", label);
+        match get_user_input().as_str() {
+            "" => return None,
+            input => match input.parse::<NaiveDate>() {
+                Ok(date) => return Some(date),
+                Err(_) => {
+                    println!("Invalid input! Please try again.");
+                    continue;
+                }
+            },
+        }
+    }
+}
+
+fn comment_prompt() -> String {
+    println!("{:-<29}", "");
+    println!(
+        "Comment (leave empty to cancel, type {} to use your editor):",
+        EDITOR_COMMAND
+    );
+    get_description_input("")
+}
+
+fn due_date_prompt(current: Option<NaiveDate>) -> Option<NaiveDate> {
+    println!("{:-<29}", "");
+    loop {
+        println!(
+            "Due Date (YYYY-MM-DD; leave empty to keep {}, - for none):",
+            current.map_or("none".to_owned(), |due| due.to_string())
+        );
+        match get_user_input().as_str() {
+            "" => return current,
+            "-" => return None,
+            input => match input.parse::<NaiveDate>() {
+                Ok(due) => return Some(due),
+                Err(_) => {
+                    println!("Invalid input! Please try again.");
+                    continue;
+                }
+            },
+        }
+    }
+}
+
+// reads a single line, or opens the user's editor prefilled with `current` when asked to
+fn get_description_input(current: &str) -> String {
+    let input = get_user_input();
+    if input != EDITOR_COMMAND {
+        return input;
+    }
+
+    match compose_in_editor(current) {
+        Ok(description) => description,
+        Err(error) => {
+            println!("{}\nPlease type the description instead:", error);
+            get_user_input()
+        }
+    }
+}